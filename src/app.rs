@@ -1,24 +1,33 @@
 use std::collections::BTreeMap;
 
-use anyhow::{anyhow, Result};
 use async_trait::async_trait;
-use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use crossterm::event::KeyEvent;
 use std::sync::{Arc, Mutex};
 use tokio::sync::mpsc;
 use tui::{
     backend::Backend,
     layout::{Constraint, Direction, Layout, Rect},
+    style::Modifier,
+    text::Spans,
+    widgets::{Block, Borders, Clear, Tabs},
     Frame,
 };
 
 use crate::{
+    client::{merged::EventSources, LogClient},
+    constant::{ACTIVE_STYLE, KEYMAP, MAX_LOG_GROUP_SELECTION, NORMAL_STYLE},
     event::LogEventEvent,
+    handler::pane_handlers::PaneHandlers,
     key_event_wrapper::KeyEventWrapper,
+    keymap::{Action, Context},
     state::{
-        logevents_state::LogEventsState,
+        search_history::SearchHistory,
         search_state::{SearchMode, SearchState},
     },
-    ui::{event_area::EventArea, help::Help, side_menu::SideMenu, status_bar::StatusBar, Drawable},
+    ui::{
+        event_area::EventArea, help::Help, side_menu::SideMenu, status_bar::StatusBar,
+        textbox::TextBox, Drawable,
+    },
     utils::key_maps_stringify,
 };
 
@@ -29,20 +38,81 @@ pub enum SelectState {
     EventAreas(usize),
 }
 
+/// Event areas shown together, switched by the tab bar
+pub struct Tab<B>
+where
+    B: Backend,
+{
+    name: String,
+    event_areas: Vec<EventArea<B>>,
+    /// handlers of each event area, in the same order
+    panes: Vec<PaneHandlers>,
+    select_state: SelectState,
+}
+
+impl<B> Tab<B>
+where
+    B: Backend,
+{
+    pub fn new(name: &str) -> Self {
+        Tab {
+            name: name.to_string(),
+            event_areas: vec![],
+            panes: vec![],
+            select_state: SelectState::SideMenu,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn log_group_names(&self) -> Vec<String> {
+        self.event_areas
+            .iter()
+            .flat_map(|e| e.log_group_names())
+            .collect()
+    }
+
+    /// The log groups are shown in one merged timeline
+    pub fn is_merged(&self) -> bool {
+        self.event_areas.len() == 1 && self.event_areas[0].is_merged()
+    }
+
+    /// Stop the handlers of all event areas
+    fn close(self) {
+        for pane in self.panes {
+            pane.close();
+        }
+    }
+}
+
 pub struct App<B>
 where
     B: Backend,
 {
     side_menu: SideMenu<B>,
-    event_areas: Vec<EventArea<B>>,
-    logevent_states: [Arc<Mutex<LogEventsState>>; 4],
-    logevent_inst_txs: [mpsc::Sender<LogEventEvent>; 4],
+    tabs: Vec<Tab<B>>,
+    tab_idx: usize,
+    /// the new name of the current tab while renaming
+    tab_name_input: Option<TextBox<B>>,
+    /// the number of the tabs created, for the default names
+    tab_count: usize,
+    log_client: LogClient,
     status_bar: StatusBar<B>,
-    select_state: SelectState,
     show_help: bool,
     fold: bool,
     help: Help<B>,
-    free_idx: [bool; 4],
+    search_history: Arc<Mutex<SearchHistory>>,
+}
+
+/// Columns and rows of the grid for the event areas
+fn grid_size(len: usize) -> (usize, usize) {
+    let mut cols = 1;
+    while cols * cols < len {
+        cols += 1;
+    }
+    (cols, len.div_ceil(cols))
 }
 
 impl<B> App<B>
@@ -52,71 +122,101 @@ where
     pub async fn new(
         side_menu: SideMenu<B>,
         event_areas: Vec<EventArea<B>>,
-        logevent_states: [Arc<Mutex<LogEventsState>>; 4],
-        logevent_inst_txs: [mpsc::Sender<LogEventEvent>; 4],
+        log_client: LogClient,
         status_bar: StatusBar<B>,
         show_help: bool,
         fold: bool,
     ) -> Self {
+        let mut tab = Tab::new("1");
+        tab.event_areas = event_areas;
         App {
             side_menu,
-            event_areas,
-            logevent_states,
-            logevent_inst_txs,
+            tabs: vec![tab],
+            tab_idx: 0,
+            tab_name_input: None,
+            tab_count: 1,
+            log_client,
             status_bar,
-            select_state: SelectState::SideMenu,
             show_help,
             fold,
             help: Help::new(),
-            free_idx: [true, true, true, true],
+            search_history: Arc::new(Mutex::new(SearchHistory::default())),
         }
     }
 
-    pub fn split_event_area(&self, rect: Rect) -> Vec<Rect> {
-        let constaints = [Constraint::Percentage(50), Constraint::Percentage(50)];
-        let base_chunks = Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints(constaints.as_ref())
-            .split(rect);
-        let left_chunks = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints(constaints.as_ref())
-            .split(base_chunks[0]);
-        let right_chunks = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints(constaints.as_ref())
-            .split(base_chunks[1]);
-
-        match self.event_areas.len() {
-            1 => {
-                vec![left_chunks[0]
-                    .union(left_chunks[1])
-                    .union(right_chunks[0])
-                    .union(right_chunks[1])]
-            }
-            2 => {
-                vec![
-                    left_chunks[0].union(left_chunks[1]),
-                    right_chunks[0].union(right_chunks[1]),
-                ]
-            }
-            3 => {
-                vec![
-                    left_chunks[0],
-                    right_chunks[0].union(right_chunks[1]),
-                    left_chunks[1],
-                ]
-            }
-            4 => {
-                vec![
-                    left_chunks[0],
-                    right_chunks[0],
-                    left_chunks[1],
-                    right_chunks[1],
-                ]
-            }
-            _ => vec![],
+    /// Keep the search history in the file instead of the memory
+    pub fn with_search_history(mut self, search_history: SearchHistory) -> Self {
+        self.search_history = Arc::new(Mutex::new(search_history));
+        self
+    }
+
+    /// The current tab
+    pub fn tab(&self) -> &Tab<B> {
+        &self.tabs[self.tab_idx]
+    }
+
+    fn tab_mut(&mut self) -> &mut Tab<B> {
+        &mut self.tabs[self.tab_idx]
+    }
+
+    pub fn tabs(&self) -> &[Tab<B>] {
+        &self.tabs
+    }
+
+    /// Add an empty tab after the current one and switch to it
+    pub fn new_tab(&mut self) {
+        self.tab_count += 1;
+        let tab = Tab::new(&self.tab_count.to_string());
+        self.tabs.insert(self.tab_idx + 1, tab);
+        self.switch_tab(self.tab_idx + 1);
+    }
+
+    /// Close the current tab with its event areas. The last tab is emptied instead.
+    pub fn close_tab(&mut self) {
+        let tab = self.tabs.remove(self.tab_idx);
+        tab.close();
+        if self.tabs.is_empty() {
+            self.tab_count = 1;
+            self.tabs.push(Tab::new("1"));
         }
+        self.switch_tab(self.tab_idx.min(self.tabs.len() - 1));
+    }
+
+    pub fn rename_tab(&mut self, name: &str) {
+        self.tab_mut().name = name.to_string();
+    }
+
+    /// Switch to the tab, and check its log groups in the side menu
+    pub fn switch_tab(&mut self, idx: usize) {
+        if idx < self.tabs.len() {
+            self.tab_idx = idx;
+            let log_groups = self.tab().log_group_names();
+            self.side_menu.set_selected_log_groups(log_groups);
+        }
+    }
+
+    fn next_tab(&mut self) {
+        self.switch_tab((self.tab_idx + 1) % self.tabs.len());
+    }
+
+    fn previous_tab(&mut self) {
+        self.switch_tab((self.tab_idx + self.tabs.len() - 1) % self.tabs.len());
+    }
+
+    /// Split the area into the grid filled from the top left.
+    /// An event area without the one below extends to the bottom.
+    pub fn split_event_area(&self, rect: Rect) -> Vec<Rect> {
+        let len = self.tab().event_areas.len();
+        let (cols, rows) = grid_size(len);
+        let x = |c: usize| rect.x + (rect.width as usize * c / cols) as u16;
+        let y = |r: usize| rect.y + (rect.height as usize * r / rows.max(1)) as u16;
+        (0..len)
+            .map(|i| {
+                let (r, c) = (i / cols, i % cols);
+                let bottom = if i + cols < len { y(r + 1) } else { y(rows) };
+                Rect::new(x(c), y(r), x(c + 1) - x(c), bottom - y(r))
+            })
+            .collect()
     }
 
     pub fn toggle_side_fold(&mut self) {
@@ -127,103 +227,288 @@ where
         self.show_help = is_show;
     }
 
-    pub fn rotate_select_state(&mut self, key: KeyCode) {
-        let event_areas_len = self.event_areas.len();
-        match self.select_state {
+    /// Move the focus by one of the Move* actions
+    pub fn rotate_select_state(&mut self, action: Action) {
+        let tab = self.tab_mut();
+        let event_areas_len = tab.event_areas.len();
+        let (cols, _) = grid_size(event_areas_len);
+        match tab.select_state {
             SelectState::SideMenu => {
-                if let KeyCode::Right = key {
+                if let Action::MoveRight = action {
                     if event_areas_len > 0 {
-                        self.select_state = SelectState::EventAreas(0);
+                        tab.select_state = SelectState::EventAreas(0);
                     }
                 }
             }
-            SelectState::EventAreas(idx) => match key {
-                KeyCode::Left => match idx {
-                    0 => self.select_state = SelectState::SideMenu,
-                    1 => self.select_state = SelectState::EventAreas(0),
-                    2 => self.select_state = SelectState::SideMenu,
-                    3 => self.select_state = SelectState::EventAreas(2),
-                    _ => {}
-                },
-                KeyCode::Right => match idx {
-                    0 => {
-                        if event_areas_len > 1 {
-                            self.select_state = SelectState::EventAreas(1);
-                        }
-                    }
-                    2 => {
-                        if event_areas_len > 3 {
-                            self.select_state = SelectState::EventAreas(3);
+            SelectState::EventAreas(idx) => {
+                let (r, c) = (idx / cols, idx % cols);
+                match action {
+                    Action::MoveLeft => {
+                        tab.select_state = if c == 0 {
+                            SelectState::SideMenu
                         } else {
-                            self.select_state = SelectState::EventAreas(1);
-                        }
+                            SelectState::EventAreas(idx - 1)
+                        };
                     }
-                    _ => {}
-                },
-                KeyCode::Down => match idx {
-                    0 => {
-                        if event_areas_len > 2 {
-                            self.select_state = SelectState::EventAreas(2);
+                    Action::MoveRight if c + 1 < cols => {
+                        // the right column can be shorter than the current one
+                        if let Some(next) = (0..=r)
+                            .rev()
+                            .map(|row| row * cols + c + 1)
+                            .find(|i| *i < event_areas_len)
+                        {
+                            tab.select_state = SelectState::EventAreas(next);
                         }
                     }
-                    1 => {
-                        if event_areas_len > 3 {
-                            self.select_state = SelectState::EventAreas(3);
-                        }
+                    Action::MoveDown if idx + cols < event_areas_len => {
+                        tab.select_state = SelectState::EventAreas(idx + cols);
+                    }
+                    Action::MoveUp if idx >= cols => {
+                        tab.select_state = SelectState::EventAreas(idx - cols);
                     }
                     _ => {}
-                },
-                KeyCode::Up => match idx {
-                    2 => self.select_state = SelectState::EventAreas(0),
-                    3 => self.select_state = SelectState::EventAreas(1),
-                    _ => {}
-                },
-                _ => {}
-            },
+                }
+            }
         }
     }
 
-    /// get index to push the next event_area
-    pub fn get_next_idx(&self) -> Result<usize> {
-        for (idx, is_free) in self.free_idx.iter().enumerate() {
-            if *is_free {
-                return Ok(idx);
+    /// Add the event area of the log group to the current tab and start to fetch with the search condition
+    async fn add_event_area(&mut self, log_group_name: &str, search_state: SearchState) {
+        let pane = PaneHandlers::spawn(self.log_client.clone());
+        let mut logevent_inst_tx = pane.logevent_inst_tx();
+        let event_area = EventArea::new(
+            log_group_name,
+            pane.state(),
+            mpsc::Sender::clone(&logevent_inst_tx),
+        )
+        .with_history(Arc::clone(&self.search_history))
+        .with_search_state(search_state.clone());
+        let tab = self.tab_mut();
+        tab.event_areas.push(event_area);
+        tab.panes.push(pane);
+        let event = LogEventEvent::FetchLogEvents(
+            log_group_name.to_string(),
+            None,
+            Some(search_state),
+            true,
+        );
+        log::info!(
+            "A new log group added, sended an event below to LogEventEventHandler thread.\n{:?}",
+            event
+        );
+        let _ = logevent_inst_tx.send(event).await;
+    }
+
+    /// Add the merged timeline of the log groups to the current tab
+    async fn add_merged_event_area(
+        &mut self,
+        log_group_names: Vec<String>,
+        search_state: SearchState,
+    ) {
+        let sources = EventSources::default();
+        let pane = PaneHandlers::spawn_merged(
+            self.log_client.clone(),
+            log_group_names.clone(),
+            sources.clone(),
+        );
+        let mut logevent_inst_tx = pane.logevent_inst_tx();
+        let event_area = EventArea::new(
+            &log_group_names.join(" + "),
+            pane.state(),
+            mpsc::Sender::clone(&logevent_inst_tx),
+        )
+        .with_merged_log_groups(log_group_names, sources)
+        .with_history(Arc::clone(&self.search_history))
+        .with_search_state(search_state.clone());
+        let event = LogEventEvent::FetchLogEvents(
+            event_area.log_group_name().to_string(),
+            None,
+            Some(search_state),
+            true,
+        );
+        let tab = self.tab_mut();
+        tab.event_areas.push(event_area);
+        tab.panes.push(pane);
+        let _ = logevent_inst_tx.send(event).await;
+    }
+
+    /// Merge the event areas of the current tab into one timeline with the search condition of
+    /// the focused one, or split the merged timeline into the event areas of its log groups.
+    pub async fn toggle_merged_view(&mut self) {
+        let tab = self.tab();
+        let search_state = match tab.select_state {
+            SelectState::EventAreas(idx) => tab.event_areas.get(idx),
+            SelectState::SideMenu => tab.event_areas.first(),
+        }
+        .map(|e| e.search_state());
+        let search_state = match search_state {
+            Some(search_state) => search_state,
+            None => return,
+        };
+        let is_merged = tab.is_merged();
+        let log_group_names = tab.log_group_names();
+        if !is_merged && log_group_names.len() < 2 {
+            return;
+        }
+        for i in (0..self.tab().event_areas.len()).rev() {
+            self.remove_event_area(i);
+        }
+        if is_merged {
+            for log_group_name in log_group_names {
+                self.add_event_area(&log_group_name, search_state.clone())
+                    .await;
+            }
+        } else {
+            self.add_merged_event_area(log_group_names, search_state)
+                .await;
+        }
+        self.tab_mut().select_state = SelectState::EventAreas(0);
+    }
+
+    /// Remove the event area of the current tab and stop its handlers
+    fn remove_event_area(&mut self, idx: usize) {
+        let tab = self.tab_mut();
+        if idx < tab.event_areas.len() {
+            tab.event_areas.remove(idx);
+        }
+        if idx < tab.panes.len() {
+            tab.panes.remove(idx).close();
+        }
+        if let SelectState::EventAreas(selected) = tab.select_state {
+            if selected >= tab.event_areas.len() {
+                tab.select_state = SelectState::SideMenu;
+            }
+        }
+    }
+
+    /// Open the log groups of the workspace with their search conditions in a tab named after it.
+    /// The current tab is used if it is empty. Log groups over the max selection are ignored.
+    pub async fn open_workspace(&mut self, name: &str, panes: Vec<(String, SearchState)>) {
+        if !self.tab().event_areas.is_empty() {
+            self.new_tab();
+        }
+        self.rename_tab(name);
+        for (log_group_name, search_state) in panes {
+            if self.tab().event_areas.len() >= *MAX_LOG_GROUP_SELECTION {
+                break;
+            }
+            self.add_event_area(&log_group_name, search_state).await;
+        }
+        let log_groups = self.tab().log_group_names();
+        self.side_menu.set_selected_log_groups(log_groups);
+    }
+
+    /// Let the event areas in tail mode fetch the new log events, including the hidden tabs
+    pub fn tail_tick(&mut self) {
+        for pane in self.tabs.iter_mut().flat_map(|tab| tab.panes.iter_mut()) {
+            pane.tick();
+        }
+    }
+
+    /// Add, remove or update the event areas of the current tab by the side menu selection
+    async fn update_event_areas(&mut self) {
+        let selected_log_groups = self.side_menu.selected_log_groups().clone();
+        if self.tab().is_merged() {
+            // the merged timeline is rebuilt with the new log groups
+            if self.tab().log_group_names() == selected_log_groups {
+                return;
+            }
+            let search_state = self.tab().event_areas[0].search_state();
+            self.remove_event_area(0);
+            if selected_log_groups.len() > 1 {
+                self.add_merged_event_area(selected_log_groups, search_state)
+                    .await;
+                return;
+            }
+        }
+        let current_log_groups = self.tab().log_group_names();
+        let idx_to_remove = current_log_groups
+            .iter()
+            .enumerate()
+            .filter(|(_, group)| !selected_log_groups.contains(group))
+            .map(|(i, _)| i)
+            .collect::<Vec<usize>>();
+        // from the last not to shift the rest
+        for i in idx_to_remove.into_iter().rev() {
+            self.remove_event_area(i);
+        }
+        for group in selected_log_groups
+            .iter()
+            .filter(|group| !current_log_groups.contains(group))
+        {
+            self.add_event_area(group, SearchState::new(String::default(), SearchMode::Tail))
+                .await;
+        }
+    }
+
+    fn draw_tab_bar(&mut self, f: &mut Frame<'_, B>, area: Rect)
+    where
+        B: Send,
+    {
+        let titles = self
+            .tabs
+            .iter()
+            .enumerate()
+            .map(|(i, tab)| Spans::from(format!("{}:{}", i + 1, tab.name)))
+            .collect::<Vec<Spans<'_>>>();
+        let tabs = Tabs::new(titles)
+            .select(self.tab_idx)
+            .style(*NORMAL_STYLE)
+            .highlight_style(ACTIVE_STYLE.add_modifier(Modifier::BOLD));
+        f.render_widget(tabs, area);
+        if let Some(name_input) = self.tab_name_input.as_mut() {
+            let mut popup_area = area;
+            popup_area.width = popup_area.width.min(40);
+            popup_area.height = 3;
+            let block = Block::default().borders(Borders::ALL).title("Rename tab");
+            f.render_widget(Clear, popup_area);
+            // the text box has its own border, the title is drawn over it
+            name_input.draw(f, popup_area);
+            f.render_widget(block, popup_area);
+        }
+    }
+
+    async fn handle_tab_name_event(&mut self, event: KeyEvent)
+    where
+        B: Send,
+    {
+        if let Some(mut name_input) = self.tab_name_input.take() {
+            match KEYMAP.action(Context::Dialog, &event) {
+                Some(Action::Confirm) => {
+                    let name = name_input.get_input();
+                    if !name.is_empty() {
+                        self.rename_tab(&name);
+                    }
+                }
+                Some(Action::Cancel) => {}
+                _ => {
+                    name_input.handle_event(event).await;
+                    self.tab_name_input = Some(name_input);
+                }
             }
         }
-        Err(anyhow!("Free idx does not exist. Something wrong."))
     }
 }
 
+/// App without the log groups, fetching from the empty source in memory
+#[cfg(any(test, feature = "testing"))]
 impl<B> Default for App<B>
 where
     B: Backend,
 {
     fn default() -> Self {
-        // dummy channel
-        let (tx1, _rx1): (mpsc::Sender<LogEventEvent>, mpsc::Receiver<LogEventEvent>) =
-            mpsc::channel(1);
-        let (tx2, _rx2): (mpsc::Sender<LogEventEvent>, mpsc::Receiver<LogEventEvent>) =
-            mpsc::channel(1);
-        let (tx3, _rx3): (mpsc::Sender<LogEventEvent>, mpsc::Receiver<LogEventEvent>) =
-            mpsc::channel(1);
-        let (tx4, _rx4): (mpsc::Sender<LogEventEvent>, mpsc::Receiver<LogEventEvent>) =
-            mpsc::channel(1);
         App {
             side_menu: SideMenu::default(),
-            event_areas: vec![],
-            logevent_states: [
-                Arc::new(Mutex::new(LogEventsState::default())),
-                Arc::new(Mutex::new(LogEventsState::default())),
-                Arc::new(Mutex::new(LogEventsState::default())),
-                Arc::new(Mutex::new(LogEventsState::default())),
-            ],
-            logevent_inst_txs: [tx1, tx2, tx3, tx4],
+            tabs: vec![Tab::new("1")],
+            tab_idx: 0,
+            tab_name_input: None,
+            tab_count: 1,
+            log_client: LogClient::from_source(crate::client::memory::MemoryLogSource::new()),
             status_bar: StatusBar::default(),
-            select_state: SelectState::SideMenu,
             show_help: false,
             fold: false,
             help: Help::default(),
-            free_idx: [true, true, true, true],
+            search_history: Arc::new(Mutex::new(SearchHistory::default())),
         }
     }
 }
@@ -248,12 +533,13 @@ where
             .constraints([Constraint::Percentage(left), Constraint::Percentage(right)].as_ref())
             .split(base_chunks[0]);
         // update select state
-        self.side_menu.set_select(match self.select_state {
+        let tab = &mut self.tabs[self.tab_idx];
+        self.side_menu.set_select(match tab.select_state {
             SelectState::SideMenu => true,
             SelectState::EventAreas(_) => false,
         });
-        for (i, v) in self.event_areas.iter_mut().enumerate() {
-            v.set_select(match self.select_state {
+        for (i, v) in tab.event_areas.iter_mut().enumerate() {
+            v.set_select(match tab.select_state {
                 SelectState::SideMenu => false,
                 SelectState::EventAreas(idx) => i == idx,
             })
@@ -266,26 +552,90 @@ where
         } else {
             // draw side menu and event areas
             self.side_menu.draw(f, chunks[0]);
-            let event_area_rects = self.split_event_area(chunks[1]);
-            for (i, v) in self.event_areas.iter_mut().enumerate() {
+            // the tab bar is shown only if there are some tabs
+            let (tab_bar_rect, event_areas_rect) =
+                if self.tabs.len() > 1 || self.tab_name_input.is_some() {
+                    let tab_chunks = Layout::default()
+                        .direction(Direction::Vertical)
+                        .constraints([Constraint::Length(1), Constraint::Min(0)].as_ref())
+                        .split(chunks[1]);
+                    (Some(tab_chunks[0]), tab_chunks[1])
+                } else {
+                    (None, chunks[1])
+                };
+            let event_area_rects = self.split_event_area(event_areas_rect);
+            for (i, v) in self.tab_mut().event_areas.iter_mut().enumerate() {
                 v.draw(f, event_area_rects[i]);
             }
+            if let Some(rect) = tab_bar_rect {
+                self.draw_tab_bar(f, rect);
+            }
             let mut maps: BTreeMap<KeyEventWrapper, String> = BTreeMap::new();
             self.push_key_maps(&mut maps);
             let maps_str = key_maps_stringify(&maps);
             self.status_bar.update_text(&maps_str);
+            // export progress of all event areas
+            let mut progress = self
+                .tabs
+                .iter()
+                .flat_map(|tab| tab.panes.iter())
+                .filter_map(|p| {
+                    p.state()
+                        .try_lock()
+                        .ok()
+                        .and_then(|s| s.export_progress.as_ref().map(|p| p.to_string()))
+                })
+                .collect::<Vec<String>>();
+            // calls of all event areas waiting for the rate limit
+            let queued = self.log_client.queued();
+            if queued > 0 {
+                progress.push(format!("{} requests queued", queued));
+            }
+            self.status_bar.update_progress(if progress.is_empty() {
+                None
+            } else {
+                Some(progress.join(" / "))
+            });
+            // fetch errors of the log groups and the event areas in the current tab
+            let tab = self.tab();
+            let errors = self
+                .side_menu
+                .error()
+                .map(|e| format!("[Log Groups] {}", e.replace('\n', " ")))
+                .into_iter()
+                .chain(
+                    tab.event_areas
+                        .iter()
+                        .zip(tab.panes.iter())
+                        .filter_map(|(area, p)| {
+                            p.state().try_lock().ok().and_then(|s| {
+                                s.error.as_ref().map(|e| {
+                                    format!("[{}] {}", area.log_group_name(), e.replace('\n', " "))
+                                })
+                            })
+                        }),
+                )
+                .collect::<Vec<String>>();
+            self.status_bar.update_error(if errors.is_empty() {
+                None
+            } else {
+                Some(errors.join(" / "))
+            });
             self.status_bar.draw(f, base_chunks[1]);
         }
     }
 
     async fn handle_event(&mut self, event: KeyEvent) -> bool {
-        if let KeyCode::Char('q') = event.code {
+        if self.tab_name_input.is_some() {
+            self.handle_tab_name_event(event).await;
+        } else if KEYMAP.is(Action::CloseHelp, &event) {
             self.toggle_show_help(false);
         } else {
-            let solved = match self.select_state {
+            let tab = &mut self.tabs[self.tab_idx];
+            let solved = match tab.select_state {
                 SelectState::SideMenu => self.side_menu.handle_event(event).await,
                 SelectState::EventAreas(idx) => {
-                    if let Some(event_area) = self.event_areas.get_mut(idx) {
+                    if let Some(event_area) = tab.event_areas.get_mut(idx) {
                         event_area.handle_event(event).await
                     } else {
                         false
@@ -293,85 +643,45 @@ where
                 }
             };
             if !solved {
-                match event.code {
-                    KeyCode::Char('?') => {
+                match KEYMAP.action(Context::Global, &event) {
+                    Some(Action::ShowHelp) => {
                         self.toggle_show_help(true);
                     }
-                    KeyCode::Tab => {
+                    Some(Action::ToggleSideMenu) => {
                         self.toggle_side_fold();
                     }
-                    KeyCode::Right => {
-                        self.rotate_select_state(KeyCode::Right);
+                    Some(
+                        action @ Action::MoveLeft
+                        | action @ Action::MoveRight
+                        | action @ Action::MoveUp
+                        | action @ Action::MoveDown,
+                    ) => {
+                        self.rotate_select_state(action);
+                    }
+                    Some(Action::NewTab) => {
+                        self.new_tab();
+                    }
+                    Some(Action::CloseTab) => {
+                        self.close_tab();
+                    }
+                    Some(Action::RenameTab) => {
+                        let mut name_input = TextBox::new(true);
+                        name_input.set_input(self.tab().name());
+                        self.tab_name_input = Some(name_input);
                     }
-                    KeyCode::Up => {
-                        self.rotate_select_state(KeyCode::Up);
+                    Some(Action::NextTab) => {
+                        self.next_tab();
                     }
-                    KeyCode::Left => {
-                        self.rotate_select_state(KeyCode::Left);
+                    Some(Action::PrevTab) => {
+                        self.previous_tab();
                     }
-                    KeyCode::Down => {
-                        self.rotate_select_state(KeyCode::Down);
+                    Some(Action::ToggleMergedView) => {
+                        self.toggle_merged_view().await;
                     }
-                    KeyCode::Enter => {
-                        if let SelectState::SideMenu = self.select_state {
+                    _ if KEYMAP.is(Action::SelectLogGroup, &event) => {
+                        if let SelectState::SideMenu = self.tab().select_state {
                             // log group selection updated
-                            let current_log_groups = self
-                                .event_areas
-                                .iter()
-                                .map(|i| i.log_group_name())
-                                .collect::<Vec<&str>>();
-                            let log_groups_to_create = self
-                                .side_menu
-                                .selected_log_groups()
-                                .iter()
-                                .filter(|group| !current_log_groups.contains(&group.as_str()))
-                                .collect::<Vec<&String>>();
-                            let mut idx_to_remove = vec![];
-                            current_log_groups
-                                .iter()
-                                .enumerate()
-                                .for_each(|(i, group)| {
-                                    if !self
-                                        .side_menu
-                                        .selected_log_groups()
-                                        .contains(&group.to_string())
-                                    {
-                                        idx_to_remove.push(i);
-                                    }
-                                });
-                            for i in idx_to_remove {
-                                if self.event_areas.len() > i {
-                                    self.event_areas.remove(i);
-                                    self.logevent_states[i].lock().unwrap().reset();
-                                    self.free_idx[i] = true;
-                                }
-                            }
-                            for i in log_groups_to_create {
-                                let idx = self.get_next_idx().unwrap();
-                                self.free_idx[idx] = false;
-                                let state = Arc::clone(&self.logevent_states[idx]);
-                                self.event_areas.push(EventArea::new(
-                                    i,
-                                    state,
-                                    mpsc::Sender::clone(&self.logevent_inst_txs[idx]),
-                                ));
-                                let _ = self.logevent_inst_txs[idx]
-                                    .send(LogEventEvent::FetchLogEvents(
-                                        i.to_string(),
-                                        None,
-                                        Some(SearchState::new(String::default(), SearchMode::Tail)),
-                                        true,
-                                    ))
-                                    .await;
-                                log::info!("A new log group added, sended an event below to LogEventEventHandler thread.\n{:?}",
-                                           LogEventEvent::FetchLogEvents(
-                                               i.to_string(),
-                                               None,
-                                               Some(SearchState::new(String::default(), SearchMode::Tail)),
-                                               true,
-                                           )
-                                );
-                            }
+                            self.update_event_areas().await;
                         }
                     }
                     _ => {}
@@ -385,40 +695,29 @@ where
         &self,
         maps: &'a mut BTreeMap<KeyEventWrapper, String>,
     ) -> &'a mut BTreeMap<KeyEventWrapper, String> {
-        maps.insert(
-            KeyEventWrapper::new(KeyEvent::new(KeyCode::Char('?'), KeyModifiers::NONE)),
-            "Help".to_string(),
-        );
-        maps.insert(
-            KeyEventWrapper::new(KeyEvent::new(KeyCode::Tab, KeyModifiers::NONE)),
-            "Toggle side menu".to_string(),
-        );
-        maps.insert(
-            KeyEventWrapper::new(KeyEvent::new(KeyCode::Right, KeyModifiers::NONE)),
-            "Move".to_string(),
-        );
-        maps.insert(
-            KeyEventWrapper::new(KeyEvent::new(KeyCode::Up, KeyModifiers::NONE)),
-            "Move".to_string(),
-        );
-        maps.insert(
-            KeyEventWrapper::new(KeyEvent::new(KeyCode::Left, KeyModifiers::NONE)),
-            "Move".to_string(),
-        );
-        maps.insert(
-            KeyEventWrapper::new(KeyEvent::new(KeyCode::Down, KeyModifiers::NONE)),
-            "Move".to_string(),
-        );
-        maps.insert(
-            KeyEventWrapper::new(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL)),
-            "Exit".to_string(),
-        );
-        match self.select_state {
+        KEYMAP.push_key_map(maps, Action::ShowHelp, "Help");
+        KEYMAP.push_key_map(maps, Action::ToggleSideMenu, "Toggle side menu");
+        KEYMAP.push_key_map(maps, Action::MoveRight, "Move");
+        KEYMAP.push_key_map(maps, Action::MoveUp, "Move");
+        KEYMAP.push_key_map(maps, Action::MoveLeft, "Move");
+        KEYMAP.push_key_map(maps, Action::MoveDown, "Move");
+        KEYMAP.push_key_map(maps, Action::Exit, "Exit");
+        if self.tabs.len() > 1 {
+            KEYMAP.push_key_map(maps, Action::NextTab, "Next tab");
+            KEYMAP.push_key_map(maps, Action::CloseTab, "Close tab");
+        }
+        if self.tab().is_merged() {
+            KEYMAP.push_key_map(maps, Action::ToggleMergedView, "Split timeline");
+        } else if self.tab().event_areas.len() > 1 {
+            KEYMAP.push_key_map(maps, Action::ToggleMergedView, "Merge timeline");
+        }
+        let tab = self.tab();
+        match tab.select_state {
             SelectState::SideMenu => {
                 self.side_menu.push_key_maps(maps);
             }
             SelectState::EventAreas(idx) => {
-                if let Some(event_area) = self.event_areas.get(idx) {
+                if let Some(event_area) = tab.event_areas.get(idx) {
                     event_area.push_key_maps(maps);
                 }
             }
@@ -513,17 +812,17 @@ mod tests {
         let expect: Vec<Rect> = vec![];
         assert_eq!(expect, result);
         // 1 event area
-        app.event_areas.push(EventArea::default());
+        app.tab_mut().event_areas.push(EventArea::default());
         let result = app.split_event_area(Rect::new(0, 0, 100, 100));
         let expect = vec![Rect::new(0, 0, 100, 100)];
         assert_eq!(expect, result);
         // 2 event areas
-        app.event_areas.push(EventArea::default());
+        app.tab_mut().event_areas.push(EventArea::default());
         let result = app.split_event_area(Rect::new(0, 0, 100, 100));
         let expect = vec![Rect::new(0, 0, 50, 100), Rect::new(50, 0, 50, 100)];
         assert_eq!(expect, result);
         // 3 event areas
-        app.event_areas.push(EventArea::default());
+        app.tab_mut().event_areas.push(EventArea::default());
         let result = app.split_event_area(Rect::new(0, 0, 100, 100));
         let expect = vec![
             Rect::new(0, 0, 50, 50),
@@ -532,7 +831,7 @@ mod tests {
         ];
         assert_eq!(expect, result);
         // 4 event areas
-        app.event_areas.push(EventArea::default());
+        app.tab_mut().event_areas.push(EventArea::default());
         let result = app.split_event_area(Rect::new(0, 0, 100, 100));
         let expect = vec![
             Rect::new(0, 0, 50, 50),
@@ -541,13 +840,38 @@ mod tests {
             Rect::new(50, 50, 50, 50),
         ];
         assert_eq!(expect, result);
+        // 5 event areas
+        app.tab_mut().event_areas.push(EventArea::default());
+        let result = app.split_event_area(Rect::new(0, 0, 100, 100));
+        let expect = vec![
+            Rect::new(0, 0, 33, 50),
+            Rect::new(33, 0, 33, 50),
+            Rect::new(66, 0, 34, 100),
+            Rect::new(0, 50, 33, 50),
+            Rect::new(33, 50, 33, 50),
+        ];
+        assert_eq!(expect, result);
+        // 7 event areas
+        app.tab_mut().event_areas.push(EventArea::default());
+        app.tab_mut().event_areas.push(EventArea::default());
+        let result = app.split_event_area(Rect::new(0, 0, 100, 100));
+        let expect = vec![
+            Rect::new(0, 0, 33, 33),
+            Rect::new(33, 0, 33, 33),
+            Rect::new(66, 0, 34, 33),
+            Rect::new(0, 33, 33, 33),
+            Rect::new(33, 33, 33, 67),
+            Rect::new(66, 33, 34, 67),
+            Rect::new(0, 66, 33, 34),
+        ];
+        assert_eq!(expect, result);
     }
 
     #[tokio::test]
     async fn test_draw() {
         let mut app: App<TestBackend> = App::default();
         test_case(&mut app, Color::Yellow, Color::White, vec![], 30, false);
-        app.event_areas.push(EventArea::default());
+        app.tab_mut().event_areas.push(EventArea::default());
         let lines = vec![
             "┌Log Groups [type to search]─┐query: [], mode: [1 minute]                                           ",
             "│                            │┌Events──────────────────────────────────────────────────────────────┐",
@@ -579,7 +903,7 @@ mod tests {
         test_case(&mut app, Color::Yellow, Color::White, lines, 3, true);
         // event area selected
         app.toggle_side_fold();
-        app.select_state = SelectState::EventAreas(0);
+        app.tab_mut().select_state = SelectState::EventAreas(0);
         let lines = vec![
             "┌Log Groups [type to search]─┐query: [], mode: [1 minute]                                           ",
             "│                            │┌Events──────────────────────────────────────────────────────────────┐",
@@ -589,8 +913,8 @@ mod tests {
             "│                            ││                                                                    │",
             "│                            ││                                                                    │",
             "└────────────────────────────┘└────────────────────────────────────────────────────────────────────┘",
-            "      [?]Help/[C+Ctrl]Exit/[ENTER]Copy to clipboard/[J]Next log event/[K]Prev log event/[S+Ctrl]Open",
-            "                            search dialog/[TAB]Toggle log event open/[←]Move/[↑]Move/[→]Move/[↓]Move",
+            "            [/]Search fetched events/[?]Help/[C]Switch stream column/[C+Ctrl]Exit/[E+Ctrl]Export log",
+            "      events/[ENTER]Copy to clipboard/[F]Filter/[J]Next log event/[K]Prev log event/[L+Ctrl]Open log",
 
 
         ];
@@ -615,20 +939,20 @@ mod tests {
     #[tokio::test]
     async fn test_handle_event_basis() {
         let mut app: App<TestBackend> = App::default();
-        app.event_areas.push(EventArea::default());
+        app.tab_mut().event_areas.push(EventArea::default());
         assert!(!app.fold);
         assert!(
             app.handle_event(KeyEvent::new(KeyCode::Tab, KeyModifiers::NONE))
                 .await
         );
         assert!(app.fold);
-        app.select_state = SelectState::EventAreas(0);
+        app.tab_mut().select_state = SelectState::EventAreas(0);
         assert!(
             app.handle_event(KeyEvent::new(KeyCode::Tab, KeyModifiers::NONE))
                 .await
         );
         assert!(!app.fold);
-        app.event_areas.pop();
+        app.tab_mut().event_areas.pop();
         assert!(
             app.handle_event(KeyEvent::new(KeyCode::Tab, KeyModifiers::NONE))
                 .await
@@ -638,13 +962,13 @@ mod tests {
                 .await
         );
         assert!(app.show_help);
-        app.event_areas.push(EventArea::default());
-        app.select_state = SelectState::SideMenu;
+        app.tab_mut().event_areas.push(EventArea::default());
+        app.tab_mut().select_state = SelectState::SideMenu;
         assert!(
             app.handle_event(KeyEvent::new(KeyCode::Right, KeyModifiers::NONE))
                 .await
         );
-        assert_eq!(SelectState::EventAreas(0), app.select_state);
+        assert_eq!(SelectState::EventAreas(0), app.tab_mut().select_state);
     }
 
     #[tokio::test]
@@ -662,96 +986,302 @@ mod tests {
             m_guard.get_list_items("", &[]);
         }
         // current cursor is the first log group
-        app.select_state = SelectState::SideMenu;
+        app.tab_mut().select_state = SelectState::SideMenu;
         app.side_menu.set_select(true);
         app.side_menu
             .handle_event(KeyEvent::new(KeyCode::Down, KeyModifiers::NONE))
             .await;
 
         // check the curernt state
-        assert_eq!(0, app.event_areas.len());
-        assert_eq!([true, true, true, true], app.free_idx);
+        assert_eq!(0, app.tab_mut().event_areas.len());
+        assert_eq!(0, app.tab_mut().panes.len());
         // fire the Enter event
         assert!(
             app.handle_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE))
                 .await
         );
-        assert_eq!(1, app.event_areas.len());
-        assert_eq!([false, true, true, true], app.free_idx);
+        assert_eq!(1, app.tab_mut().event_areas.len());
+        assert_eq!(1, app.tab_mut().panes.len());
+        // deselect the log group
+        assert!(
+            app.handle_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE))
+                .await
+        );
+        assert_eq!(0, app.tab_mut().event_areas.len());
+        assert_eq!(0, app.tab_mut().panes.len());
+    }
+
+    #[tokio::test]
+    async fn test_open_workspace() {
+        let loggroup_state = Arc::new(Mutex::new(LogGroupsState::new()));
+        let mut app: App<TestBackend> = App {
+            side_menu: SideMenu::new(Arc::clone(&loggroup_state)),
+            ..Default::default()
+        };
+        let panes = (0..=*MAX_LOG_GROUP_SELECTION)
+            .map(|i| {
+                (
+                    format!("log_group_{}", i),
+                    SearchState::new(String::from("ERROR"), SearchMode::OneHour),
+                )
+            })
+            .collect::<Vec<(String, SearchState)>>();
+        app.open_workspace("api", panes).await;
+        // over the max selection
+        let max = *MAX_LOG_GROUP_SELECTION;
+        assert_eq!(max, app.tab_mut().event_areas.len());
+        assert_eq!(max, app.tab_mut().panes.len());
+        assert_eq!(
+            format!("log_group_{}", max - 1),
+            app.tab_mut().event_areas[max - 1].log_group_name()
+        );
+        assert_eq!(max, app.side_menu.selected_log_groups().len());
+        // checked in the side menu after fetched
+        {
+            let mut m_guard = loggroup_state.lock().unwrap();
+            m_guard.log_groups = LogGroups::new(get_log_groups(0, max + 1, false));
+            m_guard.get_list_items("", &[]);
+            assert_eq!(max, m_guard.get_selected_log_group_names().len());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_toggle_merged_view() {
+        let mut app: App<TestBackend> = App::default();
+        let search_state = SearchState::new(String::from("ERROR"), SearchMode::OneHour);
+        // nothing to merge
+        app.toggle_merged_view().await;
+        assert!(!app.tab().is_merged());
+        app.add_event_area("api", SearchState::new(String::default(), SearchMode::Tail))
+            .await;
+        app.add_event_area("worker", search_state.clone()).await;
+        app.tab_mut().select_state = SelectState::EventAreas(1);
+        // merged with the condition of the focused event area
+        app.toggle_merged_view().await;
+        assert!(app.tab().is_merged());
+        assert_eq!(1, app.tab().panes.len());
+        assert_eq!("api + worker", app.tab().event_areas[0].log_group_name());
+        assert_eq!(search_state, app.tab().event_areas[0].search_state());
+        assert_eq!(
+            vec![String::from("api"), String::from("worker")],
+            app.tab().log_group_names()
+        );
+        assert_eq!(SelectState::EventAreas(0), app.tab().select_state);
+        // split with the condition of the merged timeline
+        app.toggle_merged_view().await;
+        assert!(!app.tab().is_merged());
+        assert_eq!(2, app.tab().panes.len());
+        assert_eq!("worker", app.tab().event_areas[1].log_group_name());
+        assert_eq!(search_state, app.tab().event_areas[0].search_state());
+        // rebuilt by the side menu selection
+        app.toggle_merged_view().await;
+        app.side_menu.set_selected_log_groups(vec![
+            String::from("api"),
+            String::from("worker"),
+            String::from("web"),
+        ]);
+        app.update_event_areas().await;
+        assert!(app.tab().is_merged());
+        assert_eq!(3, app.tab().log_group_names().len());
+        app.side_menu
+            .set_selected_log_groups(vec![String::from("web")]);
+        app.update_event_areas().await;
+        assert!(!app.tab().is_merged());
+        assert_eq!(vec![String::from("web")], app.tab().log_group_names());
     }
 
     #[test]
     fn test_rotate_state() {
         let mut app: App<TestBackend> = App::default();
-        app.event_areas.push(EventArea::default());
-        app.event_areas.push(EventArea::default());
-        app.event_areas.push(EventArea::default());
-        app.event_areas.push(EventArea::default());
-        // KeyCode::Left
+        app.tab_mut().event_areas.push(EventArea::default());
+        app.tab_mut().event_areas.push(EventArea::default());
+        app.tab_mut().event_areas.push(EventArea::default());
+        app.tab_mut().event_areas.push(EventArea::default());
+        // Action::MoveLeft
         // EventArea(left-top) -> SideMenu
-        app.select_state = SelectState::EventAreas(0);
-        app.rotate_select_state(KeyCode::Left);
-        assert_eq!(SelectState::SideMenu, app.select_state);
+        app.tab_mut().select_state = SelectState::EventAreas(0);
+        app.rotate_select_state(Action::MoveLeft);
+        assert_eq!(SelectState::SideMenu, app.tab_mut().select_state);
         // EventArea(right-top) -> EventArea(left-top)
-        app.select_state = SelectState::EventAreas(1);
-        app.rotate_select_state(KeyCode::Left);
-        assert_eq!(SelectState::EventAreas(0), app.select_state);
+        app.tab_mut().select_state = SelectState::EventAreas(1);
+        app.rotate_select_state(Action::MoveLeft);
+        assert_eq!(SelectState::EventAreas(0), app.tab_mut().select_state);
         // EventArea(left-bottom) -> SideMenu
-        app.select_state = SelectState::EventAreas(2);
-        app.rotate_select_state(KeyCode::Left);
-        assert_eq!(SelectState::SideMenu, app.select_state);
+        app.tab_mut().select_state = SelectState::EventAreas(2);
+        app.rotate_select_state(Action::MoveLeft);
+        assert_eq!(SelectState::SideMenu, app.tab_mut().select_state);
         // EventArea(right-bottom) -> EventArea(left-bottom)
-        app.select_state = SelectState::EventAreas(3);
-        app.rotate_select_state(KeyCode::Left);
-        assert_eq!(SelectState::EventAreas(2), app.select_state);
-        // KeyCode::Right
+        app.tab_mut().select_state = SelectState::EventAreas(3);
+        app.rotate_select_state(Action::MoveLeft);
+        assert_eq!(SelectState::EventAreas(2), app.tab_mut().select_state);
+        // Action::MoveRight
         // SideMenu -> EventArea(left-top)
-        app.select_state = SelectState::SideMenu;
-        app.rotate_select_state(KeyCode::Right);
-        assert_eq!(SelectState::EventAreas(0), app.select_state);
+        app.tab_mut().select_state = SelectState::SideMenu;
+        app.rotate_select_state(Action::MoveRight);
+        assert_eq!(SelectState::EventAreas(0), app.tab_mut().select_state);
         // EventArea(left-top) -> EventArea(right-top)
-        app.select_state = SelectState::EventAreas(0);
-        app.rotate_select_state(KeyCode::Right);
-        assert_eq!(SelectState::EventAreas(1), app.select_state);
+        app.tab_mut().select_state = SelectState::EventAreas(0);
+        app.rotate_select_state(Action::MoveRight);
+        assert_eq!(SelectState::EventAreas(1), app.tab_mut().select_state);
         // EventArea(right-top) -> no change
-        app.select_state = SelectState::EventAreas(1);
-        app.rotate_select_state(KeyCode::Right);
-        assert_eq!(SelectState::EventAreas(1), app.select_state);
+        app.tab_mut().select_state = SelectState::EventAreas(1);
+        app.rotate_select_state(Action::MoveRight);
+        assert_eq!(SelectState::EventAreas(1), app.tab_mut().select_state);
         // EventArea(left-bottom) -> EventArea(right-bottom)
-        app.select_state = SelectState::EventAreas(2);
-        app.rotate_select_state(KeyCode::Right);
-        assert_eq!(SelectState::EventAreas(3), app.select_state);
+        app.tab_mut().select_state = SelectState::EventAreas(2);
+        app.rotate_select_state(Action::MoveRight);
+        assert_eq!(SelectState::EventAreas(3), app.tab_mut().select_state);
         // EventArea(right-bottom) -> no change
-        app.select_state = SelectState::EventAreas(3);
-        app.rotate_select_state(KeyCode::Right);
-        assert_eq!(SelectState::EventAreas(3), app.select_state);
-        // KeyCode::Down
+        app.tab_mut().select_state = SelectState::EventAreas(3);
+        app.rotate_select_state(Action::MoveRight);
+        assert_eq!(SelectState::EventAreas(3), app.tab_mut().select_state);
+        // Action::MoveDown
         // EventArea(left-top) -> EventArea(left-bottom)
-        app.select_state = SelectState::EventAreas(0);
-        app.rotate_select_state(KeyCode::Down);
-        assert_eq!(SelectState::EventAreas(2), app.select_state);
+        app.tab_mut().select_state = SelectState::EventAreas(0);
+        app.rotate_select_state(Action::MoveDown);
+        assert_eq!(SelectState::EventAreas(2), app.tab_mut().select_state);
         // EventArea(right-top) -> EventArea(right-bottom)
-        app.select_state = SelectState::EventAreas(1);
-        app.rotate_select_state(KeyCode::Down);
-        assert_eq!(SelectState::EventAreas(3), app.select_state);
-        // KeyCode::Up
+        app.tab_mut().select_state = SelectState::EventAreas(1);
+        app.rotate_select_state(Action::MoveDown);
+        assert_eq!(SelectState::EventAreas(3), app.tab_mut().select_state);
+        // Action::MoveUp
         // EventArea(left-bottom) -> EventArea(left-top)
-        app.select_state = SelectState::EventAreas(2);
-        app.rotate_select_state(KeyCode::Up);
-        assert_eq!(SelectState::EventAreas(0), app.select_state);
+        app.tab_mut().select_state = SelectState::EventAreas(2);
+        app.rotate_select_state(Action::MoveUp);
+        assert_eq!(SelectState::EventAreas(0), app.tab_mut().select_state);
         // EventArea(right-bottom) -> EventArea(right-top)
-        app.select_state = SelectState::EventAreas(3);
-        app.rotate_select_state(KeyCode::Up);
-        assert_eq!(SelectState::EventAreas(1), app.select_state);
+        app.tab_mut().select_state = SelectState::EventAreas(3);
+        app.rotate_select_state(Action::MoveUp);
+        assert_eq!(SelectState::EventAreas(1), app.tab_mut().select_state);
+    }
+
+    #[tokio::test]
+    async fn test_tabs() {
+        let mut app: App<TestBackend> = App::default();
+        let panes = vec![(
+            String::from("log_group_0"),
+            SearchState::new(String::default(), SearchMode::Tail),
+        )];
+        // the empty tab is used
+        app.open_workspace("api", panes.clone()).await;
+        assert_eq!(1, app.tabs().len());
+        assert_eq!("api", app.tab().name());
+        app.open_workspace("worker", panes).await;
+        assert_eq!(2, app.tabs().len());
+        assert_eq!("worker", app.tab().name());
+        app.tab_mut().select_state = SelectState::EventAreas(0);
+        // new tab
+        assert!(
+            app.handle_event(KeyEvent::new(KeyCode::Char('t'), KeyModifiers::CONTROL))
+                .await
+        );
+        assert_eq!(3, app.tabs().len());
+        assert_eq!(2, app.tab_idx);
+        assert_eq!("3", app.tab().name());
+        assert!(app.side_menu.selected_log_groups().is_empty());
+        // switch tabs, the focus is kept
+        assert!(
+            app.handle_event(KeyEvent::new(KeyCode::Right, KeyModifiers::CONTROL))
+                .await
+        );
+        assert_eq!(0, app.tab_idx);
+        assert!(
+            app.handle_event(KeyEvent::new(KeyCode::Left, KeyModifiers::CONTROL))
+                .await
+        );
+        assert!(
+            app.handle_event(KeyEvent::new(KeyCode::Left, KeyModifiers::CONTROL))
+                .await
+        );
+        assert_eq!(1, app.tab_idx);
+        assert_eq!(SelectState::EventAreas(0), app.tab().select_state);
+        assert_eq!(
+            &vec![String::from("log_group_0")],
+            app.side_menu.selected_log_groups()
+        );
+        // rename
+        app.handle_event(KeyEvent::new(KeyCode::F(2), KeyModifiers::NONE))
+            .await;
+        app.handle_event(KeyEvent::new(KeyCode::Char('s'), KeyModifiers::NONE))
+            .await;
+        app.handle_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE))
+            .await;
+        assert_eq!("workers", app.tab().name());
+        app.handle_event(KeyEvent::new(KeyCode::F(2), KeyModifiers::NONE))
+            .await;
+        app.handle_event(KeyEvent::new(KeyCode::Char('x'), KeyModifiers::NONE))
+            .await;
+        app.handle_event(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE))
+            .await;
+        assert_eq!("workers", app.tab().name());
+        // close
+        assert!(
+            app.handle_event(KeyEvent::new(KeyCode::Char('w'), KeyModifiers::CONTROL))
+                .await
+        );
+        assert_eq!(
+            vec!["api", "3"],
+            app.tabs().iter().map(|t| t.name()).collect::<Vec<&str>>()
+        );
+        assert_eq!("3", app.tab().name());
+        app.close_tab();
+        app.close_tab();
+        // the last tab is emptied
+        assert_eq!(1, app.tabs().len());
+        assert_eq!("1", app.tab().name());
+        assert!(app.tab().event_areas.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_draw_tab_bar() {
+        let mut app: App<TestBackend> = App::default();
+        app.rename_tab("api");
+        app.new_tab();
+        let mut terminal = get_test_terminal(100, 10);
+        terminal.draw(|f| app.draw(f, f.size())).unwrap();
+        let tab_bar = (30..100)
+            .map(|x| terminal.backend().buffer().get(x, 0).symbol.clone())
+            .collect::<String>();
+        assert_eq!(format!("{:<70}", " 1:api │ 2:2 "), tab_bar);
+        assert_eq!(Color::Yellow, terminal.backend().buffer().get(39, 0).fg);
+        // rename popup
+        let mut name_input = TextBox::new(true);
+        name_input.set_input("web");
+        app.tab_name_input = Some(name_input);
+        terminal.draw(|f| app.draw(f, f.size())).unwrap();
+        let line = |y: u16| {
+            (30..45)
+                .map(|x| terminal.backend().buffer().get(x, y).symbol.clone())
+                .collect::<String>()
+        };
+        assert_eq!("┌Rename tab────", line(0));
+        assert_eq!("│web|          ", line(1));
     }
 
     #[test]
-    fn test_get_next_idx() {
+    fn test_rotate_state_grid() {
         let mut app: App<TestBackend> = App::default();
-        app.free_idx = [false, true, false, true];
-        let result = app.get_next_idx().unwrap();
-        assert_eq!(1, result);
-        app.free_idx = [false, false, false, false];
-        assert!(app.get_next_idx().is_err());
+        // 0 1 2
+        // 3 4 2
+        for _ in 0..5 {
+            app.tab_mut().event_areas.push(EventArea::default());
+        }
+        app.tab_mut().select_state = SelectState::EventAreas(4);
+        app.rotate_select_state(Action::MoveRight);
+        assert_eq!(SelectState::EventAreas(2), app.tab_mut().select_state);
+        app.rotate_select_state(Action::MoveDown);
+        assert_eq!(SelectState::EventAreas(2), app.tab_mut().select_state);
+        app.rotate_select_state(Action::MoveLeft);
+        assert_eq!(SelectState::EventAreas(1), app.tab_mut().select_state);
+        app.rotate_select_state(Action::MoveDown);
+        assert_eq!(SelectState::EventAreas(4), app.tab_mut().select_state);
+        app.rotate_select_state(Action::MoveLeft);
+        assert_eq!(SelectState::EventAreas(3), app.tab_mut().select_state);
+        app.rotate_select_state(Action::MoveUp);
+        assert_eq!(SelectState::EventAreas(0), app.tab_mut().select_state);
+        app.rotate_select_state(Action::MoveUp);
+        assert_eq!(SelectState::EventAreas(0), app.tab_mut().select_state);
+        app.rotate_select_state(Action::MoveLeft);
+        assert_eq!(SelectState::SideMenu, app.tab_mut().select_state);
     }
 }
@@ -2,13 +2,16 @@
 #![feature(destructuring_assignment)]
 
 use std::{
-    io::Stdout,
+    io::{self, Stdout},
+    path::Path,
     sync::{Arc, Mutex},
-    time::Duration,
 };
 
 use anyhow::Result;
-use clap::{crate_authors, crate_description, crate_name, crate_version, App as ClapApp, Arg};
+use clap::{
+    crate_authors, crate_description, crate_name, crate_version, App as ClapApp, Arg, ArgMatches,
+    SubCommand,
+};
 use log::*;
 use log4rs::{
     append::file::FileAppender,
@@ -20,23 +23,63 @@ use tui::backend::CrosstermBackend;
 
 use megane::{
     app::App,
-    client::LogClient,
-    constant::HELP_INSTRUCTION,
+    cli,
+    client::{local::LocalLogSource, LogClient},
+    config,
+    constant::{HELP_INSTRUCTION, INPUT_TICK_RATE, TAIL_RATE},
     event::LogGroupEvent,
     handler::{
-        input_event_handler::InputEventHandler, logevent_event_handler::LogEventEventHandler,
-        loggroup_event_handler::LogGroupEventHandler, main_event_handler::MainEventHandler,
-        tail_logevent_event_handler::TailLogEventEventHandler, EventHandler,
+        input_event_handler::InputEventHandler, loggroup_event_handler::LogGroupEventHandler,
+        main_event_handler::MainEventHandler, EventHandler,
     },
     state::{
-        logevents_state::LogEventsState, loggroups_state::LogGroupsState,
+        loggroups_state::LogGroupsState,
+        search_history::{self, SearchHistory},
+        search_state::{SearchMode, SearchState},
         status_bar_state::StatusBarState,
     },
     terminal::*,
     ui::{side_menu::SideMenu, status_bar::StatusBar},
-    utils::get_aws_client,
+    utils::get_aws_source,
 };
 
+fn format_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("format")
+        .long("format")
+        .takes_value(true)
+        .possible_values(&["text", "json"])
+        .default_value("text")
+        .help("Output format. 'json' prints one JSON object per line.")
+}
+
+fn log_group_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("log_group")
+        .required(true)
+        .index(1)
+        .help("The log group name.")
+}
+
+fn filter_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("filter")
+        .required(false)
+        .long("filter")
+        .short("f")
+        .takes_value(true)
+        .help("The filter pattern of the log events.")
+}
+
+/// search condition from the arguments of the subcommand
+fn search_state(matches: &ArgMatches<'_>) -> Result<SearchState> {
+    Ok(SearchState::new(
+        matches.value_of("filter").unwrap_or_default().to_string(),
+        SearchMode::parse(
+            matches.value_of("since"),
+            matches.value_of("from"),
+            matches.value_of("to"),
+        )?,
+    ))
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     let clap = ClapApp::new(crate_name!())
@@ -45,6 +88,7 @@ async fn main() -> Result<()> {
         .about(crate_description!())
         .arg(
             Arg::with_name("profile")
+                .global(true)
                 .required(false)
                 .long("profile")
                 .short("p")
@@ -53,6 +97,7 @@ async fn main() -> Result<()> {
         )
         .arg(
             Arg::with_name("region")
+                .global(true)
                 .required(false)
                 .long("region")
                 .short("r")
@@ -61,6 +106,7 @@ async fn main() -> Result<()> {
         )
         .arg(
             Arg::with_name("role_arn")
+                .global(true)
                 .required(false)
                 .long("role_arn")
                 .short("a")
@@ -69,19 +115,95 @@ async fn main() -> Result<()> {
         )
         .arg(
             Arg::with_name("role_name")
+                .global(true)
                 .required(false)
                 .long("role_name")
                 .short("n")
                 .takes_value(true)
                 .help("The role name you want to assume. Ensure that your current credential is allowed to action 'iam:GetRole'"),
         )
+        .arg(
+            Arg::with_name("source")
+                .global(true)
+                .required(false)
+                .long("source")
+                .short("s")
+                .takes_value(true)
+                .help("Directory of exported log files. If provided, logs will be read from the files instead of CloudWatch."),
+        )
+        .arg(
+            Arg::with_name("config")
+                .global(true)
+                .required(false)
+                .long("config")
+                .short("c")
+                .takes_value(true)
+                .help("Path of the config file. If not provided, ~/.config/megane/config.toml will be used."),
+        )
+        .arg(
+            Arg::with_name("workspace")
+                .required(false)
+                .long("workspace")
+                .short("w")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+                .help("Open the log groups of the workspace in the config file. Each workspace is opened in its own tab."),
+        )
         .arg(
             Arg::with_name("debug_mode")
+                .global(true)
                 .required(false)
                 .long("debug")
                 .short("d")
                 .help("Debug mode. Events will be written to ./log/output.log ."),
         )
+        .subcommand(
+            SubCommand::with_name("groups")
+                .about("Print the log groups without the terminal UI")
+                .arg(format_arg()),
+        )
+        .subcommand(
+            SubCommand::with_name("logs")
+                .about("Print the log events in the log group without the terminal UI")
+                .arg(log_group_arg())
+                .arg(filter_arg())
+                .arg(
+                    Arg::with_name("since")
+                        .long("since")
+                        .takes_value(true)
+                        .possible_values(&["1m", "30m", "1h", "12h"])
+                        .conflicts_with_all(&["from", "to"])
+                        .help("Print the log events in the last period."),
+                )
+                .arg(
+                    Arg::with_name("from")
+                        .long("from")
+                        .takes_value(true)
+                        .help("Start of the period, e.g. '2021-01-01 00:00:00' in local time, '2h ago' or 'yesterday 14:00'."),
+                )
+                .arg(
+                    Arg::with_name("to")
+                        .long("to")
+                        .takes_value(true)
+                        .help("End of the period, e.g. '2021-01-01 00:00:00' in local time, '-15m' or 'now'."),
+                )
+                .arg(format_arg())
+                .arg(
+                    Arg::with_name("output")
+                        .long("output")
+                        .short("o")
+                        .takes_value(true)
+                        .help("Export the log events to the file instead of stdout, with the progress on stderr. The format is decided by the extension (.jsonl, .csv or others as text)."),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("tail")
+                .about("Follow the log events in the log group like 'tail -f'")
+                .arg(log_group_arg())
+                .arg(filter_arg())
+                .arg(format_arg()),
+        )
         .get_matches();
 
     // setup logging
@@ -100,56 +222,117 @@ async fn main() -> Result<()> {
         }));
     }
 
+    // load the config before any constant is used
+    let config = match clap.value_of("config") {
+        Some(path) => config::Config::load(Path::new(path))?,
+        None => match config::default_path() {
+            Some(path) => config::Config::load(&path)?,
+            None => config::Config::default(),
+        },
+    };
+    config::init(config.clone());
+    // panes of the workspaces
+    let workspaces = clap
+        .values_of("workspace")
+        .map(|names| names.collect::<Vec<&str>>())
+        .unwrap_or_default()
+        .into_iter()
+        .map(|name| {
+            let panes = config
+                .workspace(name)?
+                .panes
+                .iter()
+                .map(|pane| Ok((pane.log_group.clone(), pane.search_state()?)))
+                .collect::<Result<Vec<(String, SearchState)>>>()?;
+            Ok((name.to_string(), panes))
+        })
+        .collect::<Result<Vec<(String, Vec<(String, SearchState)>)>>>()?;
+
     // setup states and client
     //let aws_client = CloudWatchLogsClient::new(Region::ApNortheast1);
-    let aws_client = get_aws_client(
-        clap.value_of("profile"),
-        clap.value_of("region"),
-        clap.value_of("role_name"),
-        clap.value_of("role_arn"),
-    )
-    .await?;
-    let log_client = LogClient::new(aws_client);
+    let log_client = if let Some(dir) = clap.value_of("source") {
+        LogClient::from_source(LocalLogSource::new(Path::new(dir))?)
+    } else {
+        // arguments take priority over the config
+        let aws_source = get_aws_source(
+            clap.value_of("profile").or(config.profile.as_deref()),
+            clap.value_of("region").or(config.region.as_deref()),
+            clap.value_of("role_name").or(config.role_name.as_deref()),
+            clap.value_of("role_arn").or(config.role_arn.as_deref()),
+        )
+        .await?;
+        LogClient::from_source(aws_source)
+    };
+    // subcommands without the terminal UI
+    match clap.subcommand() {
+        ("groups", Some(matches)) => {
+            let format = cli::output_format(matches.value_of("format").unwrap_or_default())?;
+            return cli::print_log_groups(&log_client, io::stdout(), format).await;
+        }
+        ("logs", Some(matches)) => {
+            if let Some(path) = matches.value_of("output") {
+                cli::export(
+                    &log_client,
+                    Path::new(path),
+                    matches.value_of("log_group").unwrap_or_default(),
+                    &search_state(matches)?,
+                )
+                .await?;
+                return Ok(());
+            }
+            let format = cli::output_format(matches.value_of("format").unwrap_or_default())?;
+            cli::print_logs(
+                &log_client,
+                io::stdout(),
+                matches.value_of("log_group").unwrap_or_default(),
+                &search_state(matches)?,
+                format,
+            )
+            .await?;
+            return Ok(());
+        }
+        ("tail", Some(matches)) => {
+            let format = cli::output_format(matches.value_of("format").unwrap_or_default())?;
+            return cli::tail(
+                log_client,
+                io::stdout(),
+                matches.value_of("log_group").unwrap_or_default(),
+                search_state(matches)?,
+                format,
+                *TAIL_RATE,
+                None,
+            )
+            .await;
+        }
+        _ => {}
+    }
+    // searches used in the previous sessions
+    let search_history = match search_history::default_path() {
+        // a broken history is not worth refusing to start
+        Some(path) => SearchHistory::load(&path).unwrap_or_else(|e| {
+            eprintln!(
+                "invalid search history {}, moved to {}: {}",
+                path.display(),
+                path.with_extension("json.bak").display(),
+                e
+            );
+            // kept in memory only if the file cannot be moved
+            SearchHistory::reset(&path).unwrap_or_default()
+        }),
+        None => SearchHistory::default(),
+    };
     // setup terminal
     let mut terminal = setup_terminal()?;
     let loggroup_state = Arc::new(Mutex::new(LogGroupsState::new()));
     let status_bar_state = Arc::new(Mutex::new(StatusBarState::new(HELP_INSTRUCTION.clone())));
-    let logevent_states = [
-        Arc::new(Mutex::new(LogEventsState::default())),
-        Arc::new(Mutex::new(LogEventsState::default())),
-        Arc::new(Mutex::new(LogEventsState::default())),
-        Arc::new(Mutex::new(LogEventsState::default())),
-    ];
-    let logevent_state_clone_0 = Arc::clone(&logevent_states[0]);
-    let logevent_state_clone_1 = Arc::clone(&logevent_states[1]);
-    let logevent_state_clone_2 = Arc::clone(&logevent_states[2]);
-    let logevent_state_clone_3 = Arc::clone(&logevent_states[3]);
-    // for tail mode
-    let logevent_state_clone_0_clone = Arc::clone(&logevent_states[0]);
-    let logevent_state_clone_1_clone = Arc::clone(&logevent_states[1]);
-    let logevent_state_clone_2_clone = Arc::clone(&logevent_states[2]);
-    let logevent_state_clone_3_clone = Arc::clone(&logevent_states[3]);
 
     // input event handling
-    let (tail_logevent_inst_tx_0, tail_logevent_inst_rx_0) = mpsc::channel(1);
-    let (tail_logevent_inst_tx_1, tail_logevent_inst_rx_1) = mpsc::channel(1);
-    let (tail_logevent_inst_tx_2, tail_logevent_inst_rx_2) = mpsc::channel(1);
-    let (tail_logevent_inst_tx_3, tail_logevent_inst_rx_3) = mpsc::channel(1);
-    let tail_logevent_inst_txs = [
-        tail_logevent_inst_tx_0.clone(),
-        tail_logevent_inst_tx_1.clone(),
-        tail_logevent_inst_tx_2.clone(),
-        tail_logevent_inst_tx_3.clone(),
-    ];
     let (input_tx, input_rx) = tokio::sync::mpsc::channel(1);
     tokio::spawn(async move {
-        let mut input_event_handler = InputEventHandler::new(
-            Duration::from_millis(100),
-            input_tx,
-            tail_logevent_inst_txs,
-            false,
-        );
-        let _ = input_event_handler.run().await;
+        let mut input_event_handler = InputEventHandler::new(*INPUT_TICK_RATE, input_tx, false);
+        if let Err(e) = input_event_handler.run().await {
+            error!("input event handler stopped: {:?}", e);
+        }
     });
 
     // loggroup event handling
@@ -159,111 +342,28 @@ async fn main() -> Result<()> {
     tokio::spawn(async {
         let mut loggroup_event_handler =
             LogGroupEventHandler::new(log_client_clone, loggroup_state_clone, logg_inst_rx);
-        let _ = loggroup_event_handler.run().await;
+        if let Err(e) = loggroup_event_handler.run().await {
+            error!("log group event handler stopped: {:?}", e);
+        }
     });
     // fetch log groups at first
     let _ = logg_inst_tx.send(LogGroupEvent::FetchLogGroups).await;
 
-    // logevent event handling
-    let (logevent_inst_tx_0, logevent_inst_rx_0) = mpsc::channel(1);
-    let (logevent_inst_tx_1, logevent_inst_rx_1) = mpsc::channel(1);
-    let (logevent_inst_tx_2, logevent_inst_rx_2) = mpsc::channel(1);
-    let (logevent_inst_tx_3, logevent_inst_rx_3) = mpsc::channel(1);
-    let log_client_clone = log_client.clone();
-    tokio::spawn(async move {
-        let mut logevent_event_handler = LogEventEventHandler::new(
-            log_client_clone,
-            logevent_state_clone_0,
-            logevent_inst_rx_0,
-            tail_logevent_inst_tx_0,
-        );
-        let _ = logevent_event_handler.run().await;
-    });
-    let log_client_clone = log_client.clone();
-    tokio::spawn(async move {
-        let mut logevent_event_handler = LogEventEventHandler::new(
-            log_client_clone,
-            logevent_state_clone_1,
-            logevent_inst_rx_1,
-            tail_logevent_inst_tx_1,
-        );
-        let _ = logevent_event_handler.run().await;
-    });
-    let log_client_clone = log_client.clone();
-    tokio::spawn(async move {
-        let mut logevent_event_handler = LogEventEventHandler::new(
-            log_client_clone,
-            logevent_state_clone_2,
-            logevent_inst_rx_2,
-            tail_logevent_inst_tx_2,
-        );
-        let _ = logevent_event_handler.run().await;
-    });
-    let log_client_clone = log_client.clone();
-    tokio::spawn(async move {
-        let mut logevent_event_handler = LogEventEventHandler::new(
-            log_client_clone,
-            logevent_state_clone_3,
-            logevent_inst_rx_3,
-            tail_logevent_inst_tx_3,
-        );
-        let _ = logevent_event_handler.run().await;
-    });
-
-    // tail logevent event handling
-    let log_client_clone = log_client.clone();
-    tokio::spawn(async move {
-        let mut tail_logevent_event_handler = TailLogEventEventHandler::new(
-            log_client_clone,
-            logevent_state_clone_0_clone,
-            tail_logevent_inst_rx_0,
-        );
-        let _ = tail_logevent_event_handler.run().await;
-    });
-    let log_client_clone = log_client.clone();
-    tokio::spawn(async move {
-        let mut tail_logevent_event_handler = TailLogEventEventHandler::new(
-            log_client_clone,
-            logevent_state_clone_1_clone,
-            tail_logevent_inst_rx_1,
-        );
-        let _ = tail_logevent_event_handler.run().await;
-    });
-    let log_client_clone = log_client.clone();
-    tokio::spawn(async move {
-        let mut tail_logevent_event_handler = TailLogEventEventHandler::new(
-            log_client_clone,
-            logevent_state_clone_2_clone,
-            tail_logevent_inst_rx_2,
-        );
-        let _ = tail_logevent_event_handler.run().await;
-    });
-    let log_client_clone = log_client.clone();
-    tokio::spawn(async move {
-        let mut tail_logevent_event_handler = TailLogEventEventHandler::new(
-            log_client_clone,
-            logevent_state_clone_3_clone,
-            tail_logevent_inst_rx_3,
-        );
-        let _ = tail_logevent_event_handler.run().await;
-    });
-
     // setup app
-    let app: App<CrosstermBackend<Stdout>> = App::new(
-        SideMenu::new(Arc::clone(&loggroup_state)),
+    let mut app: App<CrosstermBackend<Stdout>> = App::new(
+        SideMenu::new(Arc::clone(&loggroup_state)).with_loggroup_inst_tx(logg_inst_tx),
         vec![],
-        logevent_states,
-        [
-            logevent_inst_tx_0,
-            logevent_inst_tx_1,
-            logevent_inst_tx_2,
-            logevent_inst_tx_3,
-        ],
+        log_client,
         StatusBar::new(status_bar_state),
         false,
         false,
     )
-    .await;
+    .await
+    .with_search_history(search_history);
+    for (name, panes) in workspaces {
+        app.open_workspace(&name, panes).await;
+    }
+    app.switch_tab(0);
 
     terminal.clear()?;
 
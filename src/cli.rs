@@ -0,0 +1,296 @@
+use std::{
+    io::Write,
+    path::Path,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use anyhow::{anyhow, Result};
+use serde_json::json;
+use tokio::sync::mpsc;
+
+use crate::{
+    client::{retry::ErrorKind, LogClient},
+    event::TailLogEventEvent,
+    export::{ExportFormat, ExportProgress, Exporter},
+    handler::{tail_logevent_event_handler::TailLogEventEventHandler, EventHandler},
+    state::{
+        logevents_state::LogEventsState,
+        search_state::{SearchMode, SearchState},
+    },
+};
+
+/// Output format of the subcommands. Log events are written in the same format as the export.
+pub fn output_format(name: &str) -> Result<ExportFormat> {
+    match name {
+        "text" => Ok(ExportFormat::Text),
+        "json" => Ok(ExportFormat::JsonLines),
+        _ => Err(anyhow!("unknown output format: {}", name)),
+    }
+}
+
+/// `megane groups`: print all log groups
+pub async fn print_log_groups<W>(
+    client: &LogClient,
+    mut writer: W,
+    format: ExportFormat,
+) -> Result<()>
+where
+    W: Write,
+{
+    for group in client.fetch_log_groups().await? {
+        match format {
+            ExportFormat::JsonLines => {
+                let value = json!({
+                    "logGroupName": group.log_group_name,
+                    "arn": group.arn,
+                    "creationTime": group.creation_time,
+                    "retentionInDays": group.retention_in_days,
+                    "storedBytes": group.stored_bytes,
+                });
+                writeln!(writer, "{}", value)?;
+            }
+            _ => writeln!(writer, "{}", group.log_group_name.unwrap_or_default())?,
+        }
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// `megane logs`: print all log events matching the condition, page by page
+pub async fn print_logs<W>(
+    client: &LogClient,
+    writer: W,
+    log_group_name: &str,
+    condition: &SearchState,
+    format: ExportFormat,
+) -> Result<usize>
+where
+    W: Write,
+{
+    let mut exporter = Exporter::new(writer, format)?;
+    exporter
+        .write_pages(client, log_group_name, condition, None, |_| {
+            // stdout is the progress itself
+            true
+        })
+        .await?;
+    exporter.finish()
+}
+
+/// `megane tail`: follow the log group like `tail -f`.
+/// The tail handler of the terminal UI fetches the log events, and the new ones are printed and
+/// dropped at every tick. The tail cursor skips the ones fetched again.
+/// The throttling and the transient failures are reported on stderr and fetched again.
+/// Runs until the fetch fails permanently, or `ticks` times if provided.
+pub async fn tail<W>(
+    client: LogClient,
+    writer: W,
+    log_group_name: &str,
+    condition: SearchState,
+    format: ExportFormat,
+    tick_rate: Duration,
+    ticks: Option<usize>,
+) -> Result<()>
+where
+    W: Write,
+{
+    let state = Arc::new(Mutex::new(LogEventsState::new()));
+    let (mut tail_inst_tx, tail_inst_rx) = mpsc::channel(1);
+    let state_clone = Arc::clone(&state);
+    let handle = tokio::spawn(async move {
+        let mut handler = TailLogEventEventHandler::new(client, state_clone, tail_inst_rx);
+        handler.run().await
+    });
+    let condition = SearchState {
+        mode: SearchMode::Tail,
+        ..condition
+    };
+    tail_inst_tx
+        .send(TailLogEventEvent::Start(
+            log_group_name.to_string(),
+            None,
+            Some(condition),
+            true,
+        ))
+        .await?;
+
+    let mut exporter = Exporter::new(writer, format)?;
+    let mut count = 0;
+    let mut interval = None;
+    while ticks != Some(count) {
+        if tail_inst_tx.send(TailLogEventEvent::Tick).await.is_err() {
+            // the handler stopped by an error
+            return handle.await?;
+        }
+        tokio::time::delay_for(tick_rate).await;
+        let (events, error, tail_interval) = {
+            let mut state = state.lock().unwrap();
+            let error = state.error.take().zip(state.error_kind.take());
+            (state.events.take_items(), error, state.tail_interval)
+        };
+        // the handler fetches again at the next tick, and stops when the sender is dropped
+        if let Some((message, kind)) = error {
+            if kind == ErrorKind::Permanent {
+                return Err(anyhow!(message));
+            }
+            eprintln!("{}, retrying", message);
+        }
+        if tail_interval > interval && interval.is_some() {
+            eprintln!("Throttled, polling less often");
+        }
+        interval = tail_interval;
+        exporter.write_events(&events)?;
+        exporter.flush()?;
+        count += 1;
+    }
+    let _ = tail_inst_tx.send(TailLogEventEvent::Abort).await;
+    handle.await??;
+    exporter.finish()?;
+    Ok(())
+}
+
+/// `megane logs --output`: write all log events matching the condition to the file, with the progress on stderr
+pub async fn export(
+    client: &LogClient,
+    path: &Path,
+    log_group_name: &str,
+    condition: &SearchState,
+) -> Result<usize> {
+    let mut exporter = Exporter::create(path)?;
+    exporter
+        .write_pages(client, log_group_name, condition, None, |n| {
+            eprint!("\r{}", ExportProgress::Running(path.to_path_buf(), n));
+            true
+        })
+        .await?;
+    let written = exporter.finish()?;
+    eprintln!("\n{}", ExportProgress::Done(path.to_path_buf(), written));
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use super::*;
+    use crate::{client::memory::MemoryLogSource, test_helper::make_log_events};
+
+    fn get_client(ts: u64) -> LogClient {
+        LogClient::from_source(
+            MemoryLogSource::new()
+                .with_events("group-1", make_log_events(1, 3, ts))
+                .with_events("group-2", vec![]),
+        )
+    }
+
+    #[test]
+    fn test_output_format() {
+        assert_eq!(ExportFormat::Text, output_format("text").unwrap());
+        assert_eq!(ExportFormat::JsonLines, output_format("json").unwrap());
+        assert!(output_format("xml").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_print_log_groups() {
+        let client = get_client(0);
+        let mut out = vec![];
+        print_log_groups(&client, &mut out, ExportFormat::Text)
+            .await
+            .unwrap();
+        assert_eq!("group-1\ngroup-2\n", String::from_utf8(out).unwrap());
+        let mut out = vec![];
+        print_log_groups(&client, &mut out, ExportFormat::JsonLines)
+            .await
+            .unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let first: serde_json::Value = serde_json::from_str(out.lines().next().unwrap()).unwrap();
+        assert_eq!("group-1", first["logGroupName"]);
+    }
+
+    #[tokio::test]
+    async fn test_print_logs() {
+        let client = get_client(0);
+        let mut out = vec![];
+        let written = print_logs(
+            &client,
+            &mut out,
+            "group-1",
+            &SearchState::new(String::default(), SearchMode::FromTo(None, None)),
+            ExportFormat::Text,
+        )
+        .await
+        .unwrap();
+        assert_eq!(3, written);
+        assert_eq!(
+            "1970-01-01T00:00:01.000Z log_event_1\n\
+             1970-01-01T00:00:02.000Z log_event_2\n\
+             1970-01-01T00:00:03.000Z log_event_3\n",
+            String::from_utf8(out).unwrap()
+        );
+        // unknown log group
+        assert!(print_logs(
+            &client,
+            vec![],
+            "group-3",
+            &SearchState::default(),
+            ExportFormat::Text
+        )
+        .await
+        .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_tail() {
+        // tail mode fetches the last 1 minute
+        let client = get_client(Utc::now().timestamp_millis() as u64 - 10_000);
+        let mut out = vec![];
+        tail(
+            client.clone(),
+            &mut out,
+            "group-1",
+            SearchState::default(),
+            ExportFormat::JsonLines,
+            Duration::from_millis(10),
+            Some(3),
+        )
+        .await
+        .unwrap();
+        // printed once even if fetched at every tick
+        assert_eq!(3, String::from_utf8(out).unwrap().lines().count());
+        // the throttled fetch is retried
+        let client = LogClient::from_source(
+            MemoryLogSource::new()
+                .with_events(
+                    "group-1",
+                    make_log_events(1, 3, Utc::now().timestamp_millis() as u64 - 10_000),
+                )
+                .with_failures("ThrottlingException", 1),
+        );
+        let mut out = vec![];
+        tail(
+            client.clone(),
+            &mut out,
+            "group-1",
+            SearchState::default(),
+            ExportFormat::JsonLines,
+            Duration::from_millis(10),
+            Some(4),
+        )
+        .await
+        .unwrap();
+        assert_eq!(3, String::from_utf8(out).unwrap().lines().count());
+        // the permanent error is returned
+        assert!(tail(
+            client,
+            vec![],
+            "group-3",
+            SearchState::default(),
+            ExportFormat::Text,
+            Duration::from_millis(10),
+            Some(3),
+        )
+        .await
+        .is_err());
+    }
+}
@@ -1,43 +1,163 @@
-use anyhow::Result;
-use rusoto_logs::{
-    CloudWatchLogs, CloudWatchLogsClient, DescribeLogGroupsRequest, FilterLogEventsRequest,
-    FilteredLogEvent, LogGroup,
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use rusoto_logs::{CloudWatchLogsClient, FilteredLogEvent, LogGroup, LogStream};
+
+use crate::{
+    constant::REQUESTS_PER_SECOND,
+    insights::{InsightsResults, QueryStatus},
+    state::search_state::SearchState,
 };
 
-use crate::state::search_state::SearchState;
+pub mod cloudwatch;
+pub mod live_tail;
+pub mod local;
+#[cfg(any(test, feature = "testing"))]
+pub mod memory;
+pub mod merged;
+pub mod rate_limit;
+pub mod retry;
+#[cfg(any(test, feature = "testing"))]
+pub mod scripted;
+
+use cloudwatch::CloudWatchLogSource;
+use live_tail::{LiveTailSession, LiveTailUnsupported};
+use merged::{EventSources, MergedLogSource};
+use rate_limit::{RateLimitedLogSource, RateLimiter};
+use retry::RetryPolicy;
+
+/// Backend which log groups, log streams and log events come from.
+#[async_trait]
+pub trait LogSource: Send + Sync {
+    /// Fetch all log groups
+    async fn fetch_log_groups(&self) -> Result<Vec<LogGroup>>;
+
+    /// Fetch log events by query
+    async fn fetch_logs(
+        &self,
+        log_group_name: &str,
+        next_token: &Option<String>,
+        condition: &SearchState,
+    ) -> Result<(Vec<FilteredLogEvent>, Option<String>)>;
+
+    /// Fetch log streams in the log group, the most recently active first
+    async fn fetch_log_streams(&self, log_group_name: &str) -> Result<Vec<LogStream>>;
+
+    /// Fetch the latest log events for tail mode
+    async fn tail_logs(
+        &self,
+        log_group_name: &str,
+        next_token: &Option<String>,
+        condition: &SearchState,
+    ) -> Result<(Vec<FilteredLogEvent>, Option<String>)> {
+        self.fetch_logs(log_group_name, next_token, condition).await
+    }
 
-/// rusoto wrapper
+    /// True if the source implements `start_live_tail`, checked before the token of the rate
+    /// limiter is taken
+    fn supports_live_tail(&self) -> bool {
+        false
+    }
+
+    /// Start the session streaming the new log events for tail mode.
+    /// `LiveTailUnsupported` by default, tail mode polls `tail_logs` instead.
+    async fn start_live_tail(
+        &self,
+        _log_group_name: &str,
+        _condition: &SearchState,
+    ) -> Result<Box<dyn LiveTailSession>> {
+        Err(LiveTailUnsupported.into())
+    }
+
+    /// Fetch `size` log events before the timestamp in the log stream and the events from it,
+    /// `size` after the first one, in order of the timestamp
+    async fn fetch_context(
+        &self,
+        _log_group_name: &str,
+        _log_stream_name: &str,
+        _timestamp: i64,
+        _size: usize,
+    ) -> Result<Vec<FilteredLogEvent>> {
+        Err(anyhow!(
+            "The context of log events is not supported by this log source"
+        ))
+    }
+
+    /// Start a Logs Insights query and return its query id
+    async fn start_query(&self, _log_group_name: &str, _condition: &SearchState) -> Result<String> {
+        Err(anyhow!("Logs Insights is not supported by this log source"))
+    }
+
+    /// Get the status and the results of the Logs Insights query
+    async fn get_query_results(&self, _query_id: &str) -> Result<(QueryStatus, InsightsResults)> {
+        Err(anyhow!("Logs Insights is not supported by this log source"))
+    }
+
+    /// Stop the Logs Insights query which is no longer shown
+    async fn stop_query(&self, _query_id: &str) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Handle to the log source shared by all handlers.
+/// The calls of all clones are limited by the shared rate limiter,
+/// and the throttled or transient failures are retried by the retry policy.
 #[derive(Clone)]
 pub struct LogClient {
-    client: CloudWatchLogsClient,
+    source: Arc<dyn LogSource>,
+    retry: RetryPolicy,
+    limiter: Arc<RateLimiter>,
 }
 
 impl LogClient {
     pub fn new(client: CloudWatchLogsClient) -> Self {
-        LogClient { client }
+        Self::from_source(CloudWatchLogSource::new(client))
+    }
+
+    pub fn from_source<S>(source: S) -> Self
+    where
+        S: LogSource + 'static,
+    {
+        let limiter = Arc::new(RateLimiter::new(*REQUESTS_PER_SECOND));
+        LogClient {
+            source: Arc::new(RateLimitedLogSource::new(
+                Arc::new(source),
+                Arc::clone(&limiter),
+            )),
+            retry: RetryPolicy::default(),
+            limiter,
+        }
+    }
+
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Number of the calls waiting for the rate limiter
+    pub fn queued(&self) -> usize {
+        self.limiter.queued()
+    }
+
+    /// Client fetching the log groups as one merged timeline, recording the log groups of the
+    /// events in `sources`.
+    /// Each log group takes its own token of the rate limiter.
+    pub fn merged(&self, log_group_names: Vec<String>, sources: EventSources) -> Self {
+        LogClient {
+            source: Arc::new(MergedLogSource::new(
+                Arc::clone(&self.source),
+                log_group_names,
+                sources,
+            )),
+            retry: self.retry,
+            limiter: Arc::clone(&self.limiter),
+        }
     }
 
     /// Fetch all log groups
     pub async fn fetch_log_groups(&self) -> Result<Vec<LogGroup>> {
-        let mut log_groups = vec![];
-        let mut next_token = None;
-        loop {
-            let request = DescribeLogGroupsRequest {
-                limit: Some(50),
-                log_group_name_prefix: None,
-                next_token: next_token.clone(),
-            };
-            let mut response = self.client.describe_log_groups(request).await?;
-            if let Some(groups) = &mut response.log_groups {
-                log_groups.append(groups);
-            }
-            next_token = response.next_token.clone();
-            if next_token.is_none() {
-                // All log groups fetched
-                break;
-            }
-        }
-        Ok(log_groups)
+        self.retry.run(|| self.source.fetch_log_groups()).await
     }
 
     /// Fetch log events by query
@@ -47,30 +167,94 @@ impl LogClient {
         next_token: &Option<String>,
         condition: &SearchState,
     ) -> Result<(Vec<FilteredLogEvent>, Option<String>)> {
-        let mut request = FilterLogEventsRequest {
-            log_group_name: log_group_name.to_string(),
-            limit: Some(100),
-            next_token: next_token.clone(),
-            ..Default::default()
-        };
-        request.filter_pattern = Some(condition.query.clone());
-        let (from, to) = condition.mode.get_timestamps();
-        request.start_time = from;
-        request.end_time = to;
-        let response = self.client.filter_log_events(request).await?;
-        let events = if let Some(i) = response.events {
-            i
-        } else {
-            vec![]
-        };
-        Ok((events, response.next_token))
+        self.retry
+            .run(|| {
+                self.source
+                    .fetch_logs(log_group_name, next_token, condition)
+            })
+            .await
+    }
+
+    /// Fetch log streams in the log group
+    pub async fn fetch_log_streams(&self, log_group_name: &str) -> Result<Vec<LogStream>> {
+        self.retry
+            .run(|| self.source.fetch_log_streams(log_group_name))
+            .await
+    }
+
+    /// Fetch the latest log events for tail mode.
+    /// Not retried, the tail handler polls less often when throttled instead.
+    pub async fn tail_logs(
+        &self,
+        log_group_name: &str,
+        next_token: &Option<String>,
+        condition: &SearchState,
+    ) -> Result<(Vec<FilteredLogEvent>, Option<String>)> {
+        self.source
+            .tail_logs(log_group_name, next_token, condition)
+            .await
+    }
+
+    /// Start the live tail session for tail mode.
+    /// Not retried, the tail handler polls until it reconnects instead.
+    pub async fn start_live_tail(
+        &self,
+        log_group_name: &str,
+        condition: &SearchState,
+    ) -> Result<Box<dyn LiveTailSession>> {
+        self.source.start_live_tail(log_group_name, condition).await
+    }
+
+    /// Fetch the log events around the timestamp in the log stream
+    pub async fn fetch_context(
+        &self,
+        log_group_name: &str,
+        log_stream_name: &str,
+        timestamp: i64,
+        size: usize,
+    ) -> Result<Vec<FilteredLogEvent>> {
+        self.retry
+            .run(|| {
+                self.source
+                    .fetch_context(log_group_name, log_stream_name, timestamp, size)
+            })
+            .await
+    }
+
+    /// Start a Logs Insights query and return its query id
+    pub async fn start_query(
+        &self,
+        log_group_name: &str,
+        condition: &SearchState,
+    ) -> Result<String> {
+        self.retry
+            .run(|| self.source.start_query(log_group_name, condition))
+            .await
+    }
+
+    /// Get the status and the results of the Logs Insights query
+    pub async fn get_query_results(
+        &self,
+        query_id: &str,
+    ) -> Result<(QueryStatus, InsightsResults)> {
+        self.retry
+            .run(|| self.source.get_query_results(query_id))
+            .await
+    }
+
+    /// Stop the Logs Insights query. Not retried, the query stops at its timeout anyway.
+    pub async fn stop_query(&self, query_id: &str) -> Result<()> {
+        self.source.stop_query(query_id).await
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::test_helper::{get_mock_client, make_log_events, make_log_groups};
+    use crate::{
+        client::memory::MemoryLogSource,
+        test_helper::{get_mock_client, make_log_events, make_log_groups},
+    };
 
     #[tokio::test]
     async fn test_fetch_log_groups() {
@@ -93,4 +277,99 @@ mod tests {
         assert!(next_token.is_some());
         assert_eq!(expect, result);
     }
+
+    #[tokio::test]
+    async fn test_from_source() {
+        let source = MemoryLogSource::new().with_events("log_group_1", make_log_events(1, 3, 0));
+        let client = LogClient::from_source(source);
+        let result = client.fetch_log_groups().await.unwrap();
+        assert_eq!(
+            Some(String::from("log_group_1")),
+            result[0].log_group_name.clone()
+        );
+        let (result, _) = client
+            .tail_logs(
+                "log_group_1",
+                &None,
+                &SearchState::new(
+                    String::default(),
+                    crate::state::search_state::SearchMode::FromTo(None, None),
+                ),
+            )
+            .await
+            .unwrap();
+        assert_eq!(make_log_events(1, 3, 0), result);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit() {
+        let client = LogClient::from_source(
+            MemoryLogSource::new().with_events("log_group_1", make_log_events(1, 3, 0)),
+        );
+        let burst = REQUESTS_PER_SECOND.max(1.0) as usize;
+        // the clones share the budget
+        let handles = (0..burst + 2)
+            .map(|_| {
+                let client = client.clone();
+                tokio::spawn(async move { client.fetch_log_streams("log_group_1").await })
+            })
+            .collect::<Vec<_>>();
+        tokio::time::delay_for(std::time::Duration::from_millis(10)).await;
+        if *REQUESTS_PER_SECOND > 0.0 {
+            assert_eq!(2, client.queued());
+        }
+        for handle in handles {
+            assert!(handle.await.unwrap().is_ok());
+        }
+        assert_eq!(0, client.queued());
+    }
+
+    #[tokio::test]
+    async fn test_retry() {
+        let condition = SearchState::new(
+            String::default(),
+            crate::state::search_state::SearchMode::FromTo(None, None),
+        );
+        let client = |message: &str, failures: usize| {
+            LogClient::from_source(
+                MemoryLogSource::new()
+                    .with_events("log_group_1", make_log_events(1, 3, 0))
+                    .with_failures(message, failures),
+            )
+            .with_retry(RetryPolicy {
+                max_attempts: 3,
+                base_delay: std::time::Duration::from_millis(1),
+                max_delay: std::time::Duration::from_millis(1),
+            })
+        };
+        // throttled twice and succeeds at the last attempt
+        let throttled = client("ThrottlingException: Rate exceeded", 2);
+        let (result, _) = throttled
+            .fetch_logs("log_group_1", &None, &condition)
+            .await
+            .unwrap();
+        assert_eq!(3, result.len());
+        // throttled more than the attempts
+        let throttled = client("ThrottlingException: Rate exceeded", 3);
+        assert!(throttled
+            .fetch_logs("log_group_1", &None, &condition)
+            .await
+            .is_err());
+        // tail mode is not retried
+        let throttled = client("ThrottlingException: Rate exceeded", 1);
+        assert!(throttled
+            .tail_logs("log_group_1", &None, &condition)
+            .await
+            .is_err());
+        // the permanent failure is not retried
+        let denied = client("AccessDeniedException", 1);
+        assert!(denied
+            .fetch_logs("log_group_1", &None, &condition)
+            .await
+            .is_err());
+        assert!(denied
+            .fetch_logs("log_group_1", &None, &condition)
+            .await
+            .is_ok());
+    }
 }
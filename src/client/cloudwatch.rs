@@ -0,0 +1,418 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use chrono::Utc;
+use rusoto_core::{
+    event_stream::{DeserializeEvent, EventStream},
+    signature::SignedRequest,
+    Client, Region, RusotoError,
+};
+use rusoto_logs::{
+    CloudWatchLogs, CloudWatchLogsClient, DescribeLogGroupsRequest, DescribeLogStreamsRequest,
+    FilterLogEventsRequest, FilteredLogEvent, GetLogEventsRequest, GetQueryResultsRequest,
+    LogGroup, LogStream, StartQueryRequest, StopQueryRequest,
+};
+use serde::Deserialize;
+use serde_json::json;
+use tokio::stream::StreamExt;
+
+use super::{
+    live_tail::{LiveTailSession, LiveTailUnsupported},
+    LogSource,
+};
+use crate::{
+    insights::{InsightsResults, QueryStatus},
+    state::search_state::SearchState,
+};
+
+/// LogSource backed by CloudWatch Logs.
+/// StartLiveTail is not in rusoto_logs 0.45, it is signed by the rusoto client and its event
+/// stream is decoded here.
+#[derive(Clone)]
+pub struct CloudWatchLogSource {
+    client: CloudWatchLogsClient,
+    /// the client signing StartLiveTail and the region, None if tail mode polls FilterLogEvents
+    live_tail: Option<(Client, Region)>,
+}
+
+impl CloudWatchLogSource {
+    pub fn new(client: CloudWatchLogsClient) -> Self {
+        CloudWatchLogSource {
+            client,
+            live_tail: None,
+        }
+    }
+
+    /// Stream tail mode by StartLiveTail, signed and sent by the client of the CloudWatch Logs
+    /// client
+    pub fn with_live_tail(mut self, client: Client, region: Region) -> Self {
+        self.live_tail = Some((client, region));
+        self
+    }
+
+    /// The ARN of the log group without the trailing `:*`, StartLiveTail takes no names
+    async fn log_group_arn(&self, log_group_name: &str) -> Result<String> {
+        let request = DescribeLogGroupsRequest {
+            limit: Some(1),
+            log_group_name_prefix: Some(log_group_name.to_string()),
+            next_token: None,
+        };
+        // in order of the names, the log group comes before the others with the prefix
+        self.client
+            .describe_log_groups(request)
+            .await?
+            .log_groups
+            .unwrap_or_default()
+            .into_iter()
+            .find(|group| group.log_group_name.as_deref() == Some(log_group_name))
+            .and_then(|group| group.arn)
+            .map(|arn| arn.trim_end_matches(":*").to_string())
+            .ok_or_else(|| anyhow!("log group {} does not exist", log_group_name))
+    }
+}
+
+/// The body of StartLiveTail with the filter of the search condition
+fn live_tail_request(log_group_arn: &str, condition: &SearchState) -> serde_json::Value {
+    let mut request = json!({ "logGroupIdentifiers": [log_group_arn] });
+    if !condition.query.is_empty() {
+        request["logEventFilterPattern"] = json!(condition.query);
+    }
+    // names and prefixes can not be specified at the same time
+    if !condition.log_stream_names.is_empty() {
+        request["logStreamNames"] = json!(condition.log_stream_names);
+    } else if let Some(prefix) = &condition.log_stream_name_prefix {
+        request["logStreamNamePrefixes"] = json!([prefix]);
+    }
+    request
+}
+
+/// The events of the response stream of StartLiveTail
+#[derive(Debug, PartialEq)]
+enum LiveTailEvent {
+    /// the new log events, none at the start of the session
+    Update(Vec<FilteredLogEvent>),
+    /// the session is closed at its time limit
+    Timeout,
+    /// the other exceptions, e.g. SessionStreamingException
+    Exception(String),
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SessionUpdate {
+    #[serde(default)]
+    session_results: Vec<LiveTailLogEvent>,
+}
+
+/// The log event of the live tail, without the event id
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LiveTailLogEvent {
+    log_stream_name: Option<String>,
+    message: Option<String>,
+    timestamp: Option<i64>,
+    ingestion_time: Option<i64>,
+}
+
+impl DeserializeEvent for LiveTailEvent {
+    fn deserialize_event(event_type: &str, data: &[u8]) -> Result<Self, RusotoError<()>> {
+        let event = match event_type {
+            "sessionUpdate" => {
+                let update: SessionUpdate = serde_json::from_slice(data)?;
+                LiveTailEvent::Update(
+                    update
+                        .session_results
+                        .into_iter()
+                        .map(|e| FilteredLogEvent {
+                            ingestion_time: e.ingestion_time,
+                            log_stream_name: e.log_stream_name,
+                            message: e.message,
+                            timestamp: e.timestamp,
+                            ..Default::default()
+                        })
+                        .collect(),
+                )
+            }
+            "SessionTimeoutException" => LiveTailEvent::Timeout,
+            _ if event_type.ends_with("Exception") => LiveTailEvent::Exception(format!(
+                "{}: {}",
+                event_type,
+                String::from_utf8_lossy(data)
+            )),
+            // sessionStart and the events added later
+            _ => LiveTailEvent::Update(vec![]),
+        };
+        Ok(event)
+    }
+}
+
+/// The live tail session reading the response stream of StartLiveTail.
+/// The connection is closed when the session is dropped.
+struct CloudWatchLiveTail {
+    stream: EventStream<LiveTailEvent>,
+}
+
+#[async_trait]
+impl LiveTailSession for CloudWatchLiveTail {
+    /// The decoded events are kept in the stream, so dropping the call loses nothing
+    async fn next(&mut self) -> Result<Option<Vec<FilteredLogEvent>>> {
+        while let Some(event) = self.stream.next().await {
+            match event.map_err(|e| anyhow!("live tail stream failed: {:?}", e))? {
+                LiveTailEvent::Update(events) if events.is_empty() => {}
+                LiveTailEvent::Update(events) => return Ok(Some(events)),
+                LiveTailEvent::Timeout => break,
+                LiveTailEvent::Exception(message) => return Err(anyhow!("{}", message)),
+            }
+        }
+        Ok(None)
+    }
+}
+
+#[async_trait]
+impl LogSource for CloudWatchLogSource {
+    async fn fetch_log_groups(&self) -> Result<Vec<LogGroup>> {
+        let mut log_groups = vec![];
+        let mut next_token = None;
+        loop {
+            let request = DescribeLogGroupsRequest {
+                limit: Some(50),
+                log_group_name_prefix: None,
+                next_token: next_token.clone(),
+            };
+            let mut response = self.client.describe_log_groups(request).await?;
+            if let Some(groups) = &mut response.log_groups {
+                log_groups.append(groups);
+            }
+            next_token = response.next_token.clone();
+            if next_token.is_none() {
+                // All log groups fetched
+                break;
+            }
+        }
+        Ok(log_groups)
+    }
+
+    async fn fetch_logs(
+        &self,
+        log_group_name: &str,
+        next_token: &Option<String>,
+        condition: &SearchState,
+    ) -> Result<(Vec<FilteredLogEvent>, Option<String>)> {
+        let mut request = FilterLogEventsRequest {
+            log_group_name: log_group_name.to_string(),
+            limit: Some(100),
+            next_token: next_token.clone(),
+            ..Default::default()
+        };
+        request.filter_pattern = Some(condition.query.clone());
+        // names and prefix can not be specified at the same time
+        if !condition.log_stream_names.is_empty() {
+            request.log_stream_names = Some(condition.log_stream_names.clone());
+        } else {
+            request.log_stream_name_prefix = condition.log_stream_name_prefix.clone();
+        }
+        let (from, to) = condition.mode.get_timestamps();
+        request.start_time = from;
+        request.end_time = to;
+        let response = self.client.filter_log_events(request).await?;
+        Ok((response.events.unwrap_or_default(), response.next_token))
+    }
+
+    fn supports_live_tail(&self) -> bool {
+        self.live_tail.is_some()
+    }
+
+    async fn start_live_tail(
+        &self,
+        log_group_name: &str,
+        condition: &SearchState,
+    ) -> Result<Box<dyn LiveTailSession>> {
+        let (client, region) = self.live_tail.as_ref().ok_or(LiveTailUnsupported)?;
+        let log_group_arn = self.log_group_arn(log_group_name).await?;
+        // served by its own endpoint, signed as CloudWatch Logs
+        let mut request = SignedRequest::new("POST", "logs", region, "/");
+        request.set_endpoint_prefix(String::from("streaming-logs"));
+        request.set_content_type(String::from("application/x-amz-json-1.1"));
+        request.add_header("x-amz-target", "Logs_20140328.StartLiveTail");
+        request.set_payload(Some(
+            live_tail_request(&log_group_arn, condition).to_string(),
+        ));
+        let mut response = client
+            .sign_and_dispatch(request)
+            .await
+            .map_err(|e| anyhow!("failed to start the live tail: {:?}", e))?;
+        if !response.status.is_success() {
+            let response = response.buffer().await?;
+            return Err(anyhow!(
+                "failed to start the live tail: {} {}",
+                response.status,
+                response.body_as_str()
+            ));
+        }
+        Ok(Box::new(CloudWatchLiveTail {
+            stream: EventStream::new(response),
+        }))
+    }
+
+    async fn fetch_log_streams(&self, log_group_name: &str) -> Result<Vec<LogStream>> {
+        // only the latest 50 streams, there can be too many streams to fetch all of them
+        let request = DescribeLogStreamsRequest {
+            log_group_name: log_group_name.to_string(),
+            order_by: Some(String::from("LastEventTime")),
+            descending: Some(true),
+            limit: Some(50),
+            ..Default::default()
+        };
+        let response = self.client.describe_log_streams(request).await?;
+        Ok(response.log_streams.unwrap_or_default())
+    }
+
+    /// GetLogEvents backward from the timestamp and forward from it
+    async fn fetch_context(
+        &self,
+        log_group_name: &str,
+        log_stream_name: &str,
+        timestamp: i64,
+        size: usize,
+    ) -> Result<Vec<FilteredLogEvent>> {
+        let request = |start_from_head, limit| GetLogEventsRequest {
+            log_group_name: log_group_name.to_string(),
+            log_stream_name: log_stream_name.to_string(),
+            // the end time is exclusive
+            end_time: if start_from_head {
+                None
+            } else {
+                Some(timestamp)
+            },
+            start_time: if start_from_head {
+                Some(timestamp)
+            } else {
+                None
+            },
+            start_from_head: Some(start_from_head),
+            limit: Some(limit as i64),
+            ..Default::default()
+        };
+        let before = self.client.get_log_events(request(false, size)).await?;
+        let after = self.client.get_log_events(request(true, size + 1)).await?;
+        Ok(before
+            .events
+            .unwrap_or_default()
+            .into_iter()
+            .chain(after.events.unwrap_or_default())
+            .map(|e| FilteredLogEvent {
+                ingestion_time: e.ingestion_time,
+                log_stream_name: Some(log_stream_name.to_string()),
+                message: e.message,
+                timestamp: e.timestamp,
+                ..Default::default()
+            })
+            .collect())
+    }
+
+    async fn start_query(&self, log_group_name: &str, condition: &SearchState) -> Result<String> {
+        // StartQuery requires both of the start and end time in seconds
+        let (from, to) = condition.mode.get_timestamps();
+        let request = StartQueryRequest {
+            log_group_name: Some(log_group_name.to_string()),
+            query_string: condition.query.clone(),
+            start_time: from.unwrap_or(0) / 1000,
+            end_time: to.unwrap_or_else(|| Utc::now().timestamp_millis()) / 1000,
+            limit: Some(1000),
+            ..Default::default()
+        };
+        let response = self.client.start_query(request).await?;
+        response
+            .query_id
+            .ok_or_else(|| anyhow!("StartQuery returned no query id"))
+    }
+
+    async fn get_query_results(&self, query_id: &str) -> Result<(QueryStatus, InsightsResults)> {
+        let request = GetQueryResultsRequest {
+            query_id: query_id.to_string(),
+        };
+        let response = self.client.get_query_results(request).await?;
+        let status = QueryStatus::from_status(&response.status.unwrap_or_default());
+        let results = response
+            .results
+            .unwrap_or_default()
+            .into_iter()
+            .map(|fields| {
+                fields
+                    .into_iter()
+                    .map(|f| (f.field.unwrap_or_default(), f.value.unwrap_or_default()))
+                    .collect()
+            })
+            .collect();
+        Ok((status, InsightsResults::new(results)))
+    }
+
+    async fn stop_query(&self, query_id: &str) -> Result<()> {
+        let request = StopQueryRequest {
+            query_id: query_id.to_string(),
+        };
+        self.client.stop_query(request).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::search_state::SearchMode;
+
+    #[test]
+    fn test_live_tail_request() {
+        let mut condition = SearchState::new(String::default(), SearchMode::Tail);
+        assert_eq!(
+            json!({ "logGroupIdentifiers": ["arn:group"] }),
+            live_tail_request("arn:group", &condition)
+        );
+        condition.query = String::from("ERROR");
+        condition.log_stream_name_prefix = Some(String::from("app"));
+        assert_eq!(
+            json!({
+                "logGroupIdentifiers": ["arn:group"],
+                "logEventFilterPattern": "ERROR",
+                "logStreamNamePrefixes": ["app"],
+            }),
+            live_tail_request("arn:group", &condition)
+        );
+        condition.log_stream_names = vec![String::from("app-1")];
+        assert_eq!(
+            json!({
+                "logGroupIdentifiers": ["arn:group"],
+                "logEventFilterPattern": "ERROR",
+                "logStreamNames": ["app-1"],
+            }),
+            live_tail_request("arn:group", &condition)
+        );
+    }
+
+    #[test]
+    fn test_deserialize_event() {
+        let data = br#"{"sessionResults": [{"logStreamName": "app-1", "message": "hello", "timestamp": 1000, "ingestionTime": 1001, "logGroupIdentifier": "arn:group"}]}"#;
+        assert_eq!(
+            LiveTailEvent::Update(vec![FilteredLogEvent {
+                ingestion_time: Some(1001),
+                log_stream_name: Some(String::from("app-1")),
+                message: Some(String::from("hello")),
+                timestamp: Some(1000),
+                ..Default::default()
+            }]),
+            LiveTailEvent::deserialize_event("sessionUpdate", data).unwrap()
+        );
+        assert_eq!(
+            LiveTailEvent::Update(vec![]),
+            LiveTailEvent::deserialize_event("sessionStart", b"{}").unwrap()
+        );
+        assert_eq!(
+            LiveTailEvent::Timeout,
+            LiveTailEvent::deserialize_event("SessionTimeoutException", b"{}").unwrap()
+        );
+        assert_eq!(
+            LiveTailEvent::Exception(String::from("SessionStreamingException: {}")),
+            LiveTailEvent::deserialize_event("SessionStreamingException", b"{}").unwrap()
+        );
+        assert!(LiveTailEvent::deserialize_event("sessionUpdate", b"[").is_err());
+    }
+}
@@ -0,0 +1,73 @@
+use std::fmt::{Display, Formatter};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use rusoto_logs::FilteredLogEvent;
+use tokio::sync::mpsc;
+
+/// Error of starting the live tail by the log sources without it, tail mode polls instead
+#[derive(Debug)]
+pub struct LiveTailUnsupported;
+
+impl Display for LiveTailUnsupported {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "The live tail is not supported by this log source")
+    }
+}
+
+impl std::error::Error for LiveTailUnsupported {}
+
+pub fn is_unsupported(e: &anyhow::Error) -> bool {
+    e.downcast_ref::<LiveTailUnsupported>().is_some()
+}
+
+/// Session streaming the new log events of the log group, like StartLiveTail of CloudWatch Logs
+#[async_trait]
+pub trait LiveTailSession: Send {
+    /// The next batch of the log events, None when the session is closed, e.g. at its time limit.
+    /// It must be cancel safe, tail mode drops the call when the other event comes first.
+    async fn next(&mut self) -> Result<Option<Vec<FilteredLogEvent>>>;
+}
+
+/// Session fed through the channel, by the task reading the stream or by tests.
+/// The session is closed when the sender is dropped.
+pub struct ChannelLiveTail {
+    rx: mpsc::Receiver<Result<Vec<FilteredLogEvent>>>,
+}
+
+/// The sender of the batches and the session receiving them
+pub fn channel(buffer: usize) -> (mpsc::Sender<Result<Vec<FilteredLogEvent>>>, ChannelLiveTail) {
+    let (tx, rx) = mpsc::channel(buffer);
+    (tx, ChannelLiveTail { rx })
+}
+
+#[async_trait]
+impl LiveTailSession for ChannelLiveTail {
+    async fn next(&mut self) -> Result<Option<Vec<FilteredLogEvent>>> {
+        self.rx.recv().await.transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::anyhow;
+
+    use super::*;
+    use crate::test_helper::make_log_events;
+
+    #[tokio::test]
+    async fn test_channel() {
+        let (mut tx, mut session) = channel(2);
+        tx.send(Ok(make_log_events(1, 2, 0))).await.unwrap();
+        tx.send(Err(anyhow!("stream reset"))).await.unwrap();
+        assert_eq!(
+            Some(make_log_events(1, 2, 0)),
+            session.next().await.unwrap()
+        );
+        assert!(session.next().await.is_err());
+        drop(tx);
+        assert_eq!(None, session.next().await.unwrap());
+        assert!(is_unsupported(&LiveTailUnsupported.into()));
+        assert!(!is_unsupported(&anyhow!("other")));
+    }
+}
@@ -0,0 +1,427 @@
+use std::{
+    cmp::Reverse,
+    collections::HashMap,
+    fs::{self, File},
+    io::{BufRead, BufReader, Read},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::SystemTime,
+};
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use chrono::DateTime;
+use flate2::read::GzDecoder;
+use rusoto_logs::{FilteredLogEvent, LogGroup, LogStream};
+
+use super::LogSource;
+use crate::{context, state::search_state::SearchState};
+
+/// number of events returned per page, same as the limit used for FilterLogEvents
+const PAGE_SIZE: usize = 100;
+
+/// Events of the files sorted by the timestamp, by the path. Reloaded when the file is modified.
+type EventCache = Arc<Mutex<HashMap<PathBuf, (SystemTime, Arc<Vec<FilteredLogEvent>>)>>>;
+
+/// Serves log groups and log events from files in a local directory.
+/// Each file in the directory is treated as a log group.
+/// The files are read once and cached until they are modified.
+#[derive(Clone)]
+pub struct LocalLogSource {
+    dir: PathBuf,
+    cache: EventCache,
+}
+
+impl LocalLogSource {
+    pub fn new(dir: &Path) -> Result<Self> {
+        if !dir.is_dir() {
+            return Err(anyhow!("{} is not a directory", dir.display()));
+        }
+        Ok(LocalLogSource {
+            dir: dir.to_path_buf(),
+            cache: Arc::new(Mutex::new(HashMap::new())),
+        })
+    }
+
+    /// Events of the log group in order of the timestamp, read off the runtime threads
+    async fn events(&self, log_group_name: &str) -> Result<Arc<Vec<FilteredLogEvent>>> {
+        let path = self.dir.join(log_group_name);
+        let log_group_name = log_group_name.to_string();
+        let cache = Arc::clone(&self.cache);
+        tokio::task::spawn_blocking(move || load_events(&cache, &path, &log_group_name)).await?
+    }
+}
+
+/// The cached events of the file, or read and sort them if the file is new or modified
+fn load_events(
+    cache: &EventCache,
+    path: &Path,
+    log_group_name: &str,
+) -> Result<Arc<Vec<FilteredLogEvent>>> {
+    let modified = fs::metadata(path)?.modified()?;
+    if let Some((cached_at, events)) = cache.lock().unwrap().get(path) {
+        if *cached_at == modified {
+            return Ok(Arc::clone(events));
+        }
+    }
+    let mut events = read_events(path, log_group_name)?;
+    // stable, the events without the timestamp stay in order of the lines
+    events.sort_by_key(|e| e.timestamp);
+    let events = Arc::new(events);
+    cache
+        .lock()
+        .unwrap()
+        .insert(path.to_path_buf(), (modified, Arc::clone(&events)));
+    Ok(events)
+}
+
+/// Files in the directory as log groups, sorted by the name
+fn list_log_groups(dir: &Path) -> Result<Vec<LogGroup>> {
+    let mut log_groups = vec![];
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if !path.is_file() {
+            continue;
+        }
+        if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
+            log_groups.push(LogGroup {
+                arn: Some(path.to_string_lossy().to_string()),
+                log_group_name: Some(name.to_string()),
+                ..Default::default()
+            });
+        }
+    }
+    log_groups.sort_by(|a, b| a.log_group_name.cmp(&b.log_group_name));
+    Ok(log_groups)
+}
+
+#[async_trait]
+impl LogSource for LocalLogSource {
+    /// Fetch all files in the directory as log groups
+    async fn fetch_log_groups(&self) -> Result<Vec<LogGroup>> {
+        let dir = self.dir.clone();
+        tokio::task::spawn_blocking(move || list_log_groups(&dir)).await?
+    }
+
+    /// Fetch log events from the file, applying the query and the time range locally.
+    /// next_token is the offset of the next page in the matched events.
+    async fn fetch_logs(
+        &self,
+        log_group_name: &str,
+        next_token: &Option<String>,
+        condition: &SearchState,
+    ) -> Result<(Vec<FilteredLogEvent>, Option<String>)> {
+        let offset = if let Some(token) = next_token {
+            token.parse::<usize>()?
+        } else {
+            0
+        };
+        let (from, to) = condition.mode.get_timestamps();
+        let terms = parse_query(&condition.query);
+        let mut events = self
+            .events(log_group_name)
+            .await?
+            .iter()
+            .filter(|event| {
+                is_in_range(event, from, to)
+                    && is_matched(event, &terms)
+                    && condition.is_target_log_stream(event.log_stream_name.as_ref())
+            })
+            .skip(offset)
+            .take(PAGE_SIZE + 1)
+            .cloned()
+            .collect::<Vec<FilteredLogEvent>>();
+        let next_token = if events.len() > PAGE_SIZE {
+            events.truncate(PAGE_SIZE);
+            Some((offset + PAGE_SIZE).to_string())
+        } else {
+            None
+        };
+        Ok((events, next_token))
+    }
+
+    /// Log stream names found in the file, ordered by the last event time
+    async fn fetch_log_streams(&self, log_group_name: &str) -> Result<Vec<LogStream>> {
+        let mut streams: Vec<LogStream> = vec![];
+        for event in self.events(log_group_name).await?.iter() {
+            if let Some(name) = &event.log_stream_name {
+                match streams
+                    .iter_mut()
+                    .find(|s| s.log_stream_name.as_ref() == Some(name))
+                {
+                    Some(stream) => {
+                        stream.last_event_timestamp =
+                            stream.last_event_timestamp.max(event.timestamp);
+                    }
+                    None => streams.push(LogStream {
+                        log_stream_name: Some(name.clone()),
+                        last_event_timestamp: event.timestamp,
+                        ..Default::default()
+                    }),
+                }
+            }
+        }
+        streams.sort_by_key(|s| Reverse(s.last_event_timestamp));
+        Ok(streams)
+    }
+
+    async fn fetch_context(
+        &self,
+        log_group_name: &str,
+        log_stream_name: &str,
+        timestamp: i64,
+        size: usize,
+    ) -> Result<Vec<FilteredLogEvent>> {
+        let events = self
+            .events(log_group_name)
+            .await?
+            .iter()
+            .filter(|e| e.log_stream_name.as_deref() == Some(log_stream_name))
+            .cloned()
+            .collect::<Vec<FilteredLogEvent>>();
+        Ok(context::surrounding(&events, timestamp, size))
+    }
+}
+
+/// Read all events in the file. Files with the `.gz` extension are decompressed.
+fn read_events(path: &Path, log_group_name: &str) -> Result<Vec<FilteredLogEvent>> {
+    let file = File::open(path)?;
+    let reader: Box<dyn Read> = if path.extension().and_then(|ext| ext.to_str()) == Some("gz") {
+        Box::new(GzDecoder::new(file))
+    } else {
+        Box::new(file)
+    };
+    let mut events = vec![];
+    for (i, line) in BufReader::new(reader).lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        events.push(parse_line(&line, i, log_group_name));
+    }
+    Ok(events)
+}
+
+/// Parse a line in one of the formats below.
+///  - JSON line in the FilterLogEvents format ({"timestamp": ..., "message": ...})
+///  - S3 export format ("2021-01-01T00:00:00.000Z message")
+///  - plain text (without timestamp)
+fn parse_line(line: &str, line_no: usize, log_group_name: &str) -> FilteredLogEvent {
+    let mut event = match serde_json::from_str::<FilteredLogEvent>(line) {
+        Ok(e) if e.message.is_some() => e,
+        _ => {
+            let (timestamp, message) = match line.split_once(' ') {
+                Some((head, rest)) => match DateTime::parse_from_rfc3339(head) {
+                    Ok(dt) => (Some(dt.timestamp_millis()), rest.to_string()),
+                    Err(_) => (None, line.to_string()),
+                },
+                None => (None, line.to_string()),
+            };
+            FilteredLogEvent {
+                message: Some(message),
+                timestamp,
+                ..Default::default()
+            }
+        }
+    };
+    // event_id is used to skip duplicate events, so it must be unique in the file
+    if event.event_id.is_none() {
+        event.event_id = Some(format!("{}-{}", log_group_name, line_no));
+    }
+    event
+}
+
+/// Split the query into terms. Terms in double quotes are treated as one term.
+fn parse_query(query: &str) -> Vec<String> {
+    let mut terms = vec![];
+    let mut current = String::default();
+    let mut in_quote = false;
+    for c in query.chars() {
+        match c {
+            '"' => {
+                if in_quote && !current.is_empty() {
+                    terms.push(current.clone());
+                    current.clear();
+                }
+                in_quote = !in_quote;
+            }
+            ' ' if !in_quote => {
+                if !current.is_empty() {
+                    terms.push(current.clone());
+                    current.clear();
+                }
+            }
+            _ => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        terms.push(current);
+    }
+    terms
+}
+
+/// All terms must be contained in the message
+fn is_matched(event: &FilteredLogEvent, terms: &[String]) -> bool {
+    if terms.is_empty() {
+        return true;
+    }
+    if let Some(msg) = &event.message {
+        terms.iter().all(|t| msg.contains(t.as_str()))
+    } else {
+        false
+    }
+}
+
+fn is_in_range(event: &FilteredLogEvent, from: Option<i64>, to: Option<i64>) -> bool {
+    match event.timestamp {
+        Some(t) => from.unwrap_or(i64::MIN) <= t && t <= to.unwrap_or(i64::MAX),
+        None => from.is_none() && to.is_none(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::search_state::SearchMode;
+
+    fn get_client() -> LocalLogSource {
+        LocalLogSource::new(Path::new("mock_data/local")).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_fetch_log_groups() {
+        let result = get_client().fetch_log_groups().await.unwrap();
+        let names = result
+            .iter()
+            .map(|g| g.log_group_name.clone().unwrap())
+            .collect::<Vec<String>>();
+        assert_eq!(vec!["app.jsonl", "export.log.gz"], names);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_logs() {
+        let client = get_client();
+        let condition = SearchState::new(String::default(), SearchMode::FromTo(None, None));
+        let (result, next_token) = client
+            .fetch_logs("app.jsonl", &None, &condition)
+            .await
+            .unwrap();
+        assert_eq!(3, result.len());
+        assert_eq!(Some(String::from("1")), result[0].event_id);
+        assert!(next_token.is_none());
+        // query
+        let condition = SearchState::new(
+            String::from("\"request failed\""),
+            SearchMode::FromTo(None, None),
+        );
+        let (result, _) = client
+            .fetch_logs("app.jsonl", &None, &condition)
+            .await
+            .unwrap();
+        assert_eq!(1, result.len());
+        // time range
+        let condition = SearchState::new(
+            String::default(),
+            SearchMode::FromTo(Some(1609426801000), Some(1609426802000)),
+        );
+        let (result, _) = client
+            .fetch_logs("app.jsonl", &None, &condition)
+            .await
+            .unwrap();
+        assert_eq!(2, result.len());
+        // log stream
+        let mut condition = SearchState::new(String::default(), SearchMode::FromTo(None, None));
+        condition.log_stream_names = vec![String::from("stream-2")];
+        let (result, _) = client
+            .fetch_logs("app.jsonl", &None, &condition)
+            .await
+            .unwrap();
+        assert_eq!(1, result.len());
+        assert_eq!(Some(String::from("3")), result[0].event_id);
+        // gzip'd S3 export
+        let condition = SearchState::new(String::default(), SearchMode::FromTo(None, None));
+        let (result, _) = client
+            .fetch_logs("export.log.gz", &None, &condition)
+            .await
+            .unwrap();
+        assert_eq!(2, result.len());
+        assert_eq!(Some(1609426800000), result[0].timestamp);
+        assert_eq!(Some(String::from("export line 1")), result[0].message);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_log_streams() {
+        let result = get_client().fetch_log_streams("app.jsonl").await.unwrap();
+        let names = result
+            .iter()
+            .map(|s| s.log_stream_name.clone().unwrap())
+            .collect::<Vec<String>>();
+        assert_eq!(vec!["stream-2", "stream-1"], names);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_context() {
+        let result = get_client()
+            .fetch_context("app.jsonl", "stream-1", 1609426801000, 5)
+            .await
+            .unwrap();
+        let ids = result
+            .iter()
+            .map(|e| e.event_id.clone().unwrap())
+            .collect::<Vec<String>>();
+        assert_eq!(vec!["1", "2"], ids);
+    }
+
+    #[tokio::test]
+    async fn test_events_cache() {
+        let dir = std::env::temp_dir().join("megane_test_events_cache");
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("group.log");
+        fs::write(
+            &path,
+            "2021-01-01T00:00:02.000Z second\n2021-01-01T00:00:01.000Z first\n",
+        )
+        .unwrap();
+        let client = LocalLogSource::new(&dir).unwrap();
+        // sorted by the timestamp
+        let events = client.events("group.log").await.unwrap();
+        assert_eq!(Some(String::from("first")), events[0].message);
+        assert!(Arc::ptr_eq(
+            &events,
+            &client.events("group.log").await.unwrap()
+        ));
+        // reloaded after modified
+        let modified = fs::metadata(&path).unwrap().modified().unwrap();
+        fs::write(&path, "2021-01-01T00:00:03.000Z third\n").unwrap();
+        // the mtime may not change within the resolution of the filesystem
+        fs::OpenOptions::new()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(modified + std::time::Duration::from_secs(1))
+            .unwrap();
+        let events = client.events("group.log").await.unwrap();
+        assert_eq!(1, events.len());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_parse_line() {
+        let event = parse_line("plain text", 3, "group");
+        assert_eq!(Some(String::from("plain text")), event.message);
+        assert_eq!(Some(String::from("group-3")), event.event_id);
+        assert!(event.timestamp.is_none());
+        let event = parse_line("2021-01-01T00:00:00.000Z hello world", 0, "group");
+        assert_eq!(Some(String::from("hello world")), event.message);
+        assert_eq!(Some(1609459200000), event.timestamp);
+    }
+
+    #[test]
+    fn test_parse_query() {
+        assert!(parse_query("").is_empty());
+        assert_eq!(
+            vec!["ERROR", "request failed", "id"],
+            parse_query("ERROR \"request failed\" id")
+        );
+    }
+}
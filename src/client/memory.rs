@@ -0,0 +1,240 @@
+use std::{
+    collections::BTreeMap,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use rusoto_logs::{FilteredLogEvent, LogGroup, LogStream};
+
+use super::LogSource;
+use crate::{
+    context,
+    insights::{InsightsResults, QueryStatus},
+    state::search_state::SearchState,
+};
+
+/// LogSource holding log events in memory, built for the tests only.
+#[derive(Clone, Default)]
+pub struct MemoryLogSource {
+    groups: BTreeMap<String, Vec<FilteredLogEvent>>,
+    /// the error message and the number of the fetches left to fail, shared by the clones
+    failures: Option<(String, Arc<AtomicUsize>)>,
+}
+
+impl MemoryLogSource {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the log group with its log events
+    pub fn with_events(mut self, log_group_name: &str, events: Vec<FilteredLogEvent>) -> Self {
+        self.groups.insert(log_group_name.to_string(), events);
+        self
+    }
+
+    /// Fail the next `count` fetches of the log events or the log groups with the message
+    pub fn with_failures(mut self, message: &str, count: usize) -> Self {
+        self.failures = Some((message.to_string(), Arc::new(AtomicUsize::new(count))));
+        self
+    }
+
+    fn next_failure(&self) -> Result<()> {
+        match &self.failures {
+            Some((message, count))
+                if count
+                    .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |c| c.checked_sub(1))
+                    .is_ok() =>
+            {
+                Err(anyhow!("{}", message))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[async_trait]
+impl LogSource for MemoryLogSource {
+    async fn fetch_log_groups(&self) -> Result<Vec<LogGroup>> {
+        self.next_failure()?;
+        Ok(self
+            .groups
+            .keys()
+            .map(|name| LogGroup {
+                arn: Some(name.clone()),
+                log_group_name: Some(name.clone()),
+                ..Default::default()
+            })
+            .collect())
+    }
+
+    /// All events in the time range and the log streams are returned at once, the query is ignored.
+    async fn fetch_logs(
+        &self,
+        log_group_name: &str,
+        _next_token: &Option<String>,
+        condition: &SearchState,
+    ) -> Result<(Vec<FilteredLogEvent>, Option<String>)> {
+        self.next_failure()?;
+        let events = self
+            .groups
+            .get(log_group_name)
+            .ok_or_else(|| anyhow!("log group {} does not exist", log_group_name))?;
+        let (from, to) = condition.mode.get_timestamps();
+        let events = events
+            .iter()
+            .filter(|e| {
+                let t = e.timestamp.unwrap_or_default();
+                from.unwrap_or(i64::MIN) <= t
+                    && t <= to.unwrap_or(i64::MAX)
+                    && condition.is_target_log_stream(e.log_stream_name.as_ref())
+            })
+            .cloned()
+            .collect();
+        Ok((events, None))
+    }
+
+    async fn fetch_log_streams(&self, log_group_name: &str) -> Result<Vec<LogStream>> {
+        let mut streams: Vec<LogStream> = vec![];
+        if let Some(events) = self.groups.get(log_group_name) {
+            for event in events {
+                if let Some(name) = &event.log_stream_name {
+                    if !streams
+                        .iter()
+                        .any(|s| s.log_stream_name.as_ref() == Some(name))
+                    {
+                        streams.push(LogStream {
+                            log_stream_name: Some(name.clone()),
+                            ..Default::default()
+                        });
+                    }
+                }
+            }
+        }
+        Ok(streams)
+    }
+
+    async fn fetch_context(
+        &self,
+        log_group_name: &str,
+        log_stream_name: &str,
+        timestamp: i64,
+        size: usize,
+    ) -> Result<Vec<FilteredLogEvent>> {
+        let events = self
+            .groups
+            .get(log_group_name)
+            .ok_or_else(|| anyhow!("log group {} does not exist", log_group_name))?;
+        let mut events = events
+            .iter()
+            .filter(|e| e.log_stream_name.as_deref() == Some(log_stream_name))
+            .cloned()
+            .collect::<Vec<FilteredLogEvent>>();
+        events.sort_by_key(|e| e.timestamp);
+        Ok(context::surrounding(&events, timestamp, size))
+    }
+
+    /// The query id is the log group name, the query itself is ignored.
+    async fn start_query(&self, log_group_name: &str, _condition: &SearchState) -> Result<String> {
+        if !self.groups.contains_key(log_group_name) {
+            return Err(anyhow!("log group {} does not exist", log_group_name));
+        }
+        Ok(log_group_name.to_string())
+    }
+
+    /// All events of the log group as `@timestamp` and `@message` rows
+    async fn get_query_results(&self, query_id: &str) -> Result<(QueryStatus, InsightsResults)> {
+        let events = self
+            .groups
+            .get(query_id)
+            .ok_or_else(|| anyhow!("query {} does not exist", query_id))?;
+        let results = events
+            .iter()
+            .map(|e| {
+                vec![
+                    (
+                        String::from("@timestamp"),
+                        e.timestamp.unwrap_or_default().to_string(),
+                    ),
+                    (
+                        String::from("@message"),
+                        e.message.clone().unwrap_or_default(),
+                    ),
+                ]
+            })
+            .collect();
+        Ok((QueryStatus::Complete, InsightsResults::new(results)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{state::search_state::SearchMode, test_helper::make_log_events};
+
+    #[tokio::test]
+    async fn test_fetch_logs() {
+        let source = MemoryLogSource::new().with_events("group", make_log_events(1, 3, 0));
+        let condition = SearchState::new(String::default(), SearchMode::FromTo(Some(2000), None));
+        let (result, next_token) = source.fetch_logs("group", &None, &condition).await.unwrap();
+        assert_eq!(make_log_events(2, 3, 0), result);
+        assert!(next_token.is_none());
+        assert!(source
+            .fetch_logs("not exist", &None, &condition)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_log_streams() {
+        let mut events = make_log_events(1, 3, 0);
+        events[0].log_stream_name = Some(String::from("stream-1"));
+        events[1].log_stream_name = Some(String::from("stream-2"));
+        events[2].log_stream_name = Some(String::from("stream-1"));
+        let source = MemoryLogSource::new().with_events("group", events);
+        let result = source.fetch_log_streams("group").await.unwrap();
+        assert_eq!(2, result.len());
+        assert_eq!(Some(String::from("stream-2")), result[1].log_stream_name);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_context() {
+        let mut events = make_log_events(0, 6, 0);
+        for (i, event) in events.iter_mut().enumerate() {
+            event.log_stream_name = Some(format!("stream-{}", i % 2));
+        }
+        let source = MemoryLogSource::new().with_events("group", events);
+        let result = source
+            .fetch_context("group", "stream-0", 4000, 1)
+            .await
+            .unwrap();
+        assert_eq!(
+            vec![
+                Some(String::from("2")),
+                Some(String::from("4")),
+                Some(String::from("6"))
+            ],
+            result.into_iter().map(|e| e.event_id).collect::<Vec<_>>()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_insights_query() {
+        let source = MemoryLogSource::new().with_events("group", make_log_events(1, 2, 0));
+        let query_id = source
+            .start_query("group", &SearchState::default())
+            .await
+            .unwrap();
+        let (status, results) = source.get_query_results(&query_id).await.unwrap();
+        assert_eq!(QueryStatus::Complete, status);
+        assert_eq!(&vec!["@timestamp", "@message"], results.columns());
+        assert_eq!(2, results.rows().len());
+        assert!(source
+            .start_query("not exist", &SearchState::default())
+            .await
+            .is_err());
+    }
+}
@@ -0,0 +1,383 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Arc, Mutex},
+};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use rusoto_logs::{FilteredLogEvent, LogGroup, LogStream};
+
+use super::LogSource;
+use crate::state::search_state::SearchState;
+
+/// LogSource showing several log groups as one, for the merged timeline.
+/// The log group name given to each method is ignored, the log groups are fetched concurrently
+/// with their own next tokens and the events are sorted by timestamp.
+/// The pages of `fetch_logs` follow each other in time, the events of a log group can be returned
+/// with a later page until the other log groups catch up with them.
+/// The live tail is not supported, tail mode of the merged timeline polls.
+pub struct MergedLogSource {
+    source: Arc<dyn LogSource>,
+    log_group_names: Vec<String>,
+    sources: EventSources,
+    pending: Mutex<Pending>,
+}
+
+/// The page of a log group and its next token
+type Page = (String, Vec<FilteredLogEvent>, Option<String>);
+
+/// The events fetched by `fetch_logs` but not returned yet
+#[derive(Default)]
+struct Pending {
+    events: Vec<FilteredLogEvent>,
+    /// the timestamp of the latest event fetched from each log group
+    latest: HashMap<String, i64>,
+}
+
+impl MergedLogSource {
+    pub fn new(
+        source: Arc<dyn LogSource>,
+        log_group_names: Vec<String>,
+        sources: EventSources,
+    ) -> Self {
+        MergedLogSource {
+            source,
+            log_group_names,
+            sources,
+            pending: Mutex::new(Pending::default()),
+        }
+    }
+
+    /// All log groups from the first page
+    fn initial_tokens(&self) -> BTreeMap<String, Option<String>> {
+        self.log_group_names
+            .iter()
+            .map(|name| (name.clone(), None))
+            .collect()
+    }
+
+    /// Fetch the log groups with their next tokens at the same time
+    async fn fetch_pages(
+        &self,
+        tokens: BTreeMap<String, Option<String>>,
+        condition: &SearchState,
+        tail: bool,
+    ) -> Result<Vec<Page>> {
+        let handles = tokens
+            .into_iter()
+            .map(|(log_group_name, token)| {
+                let source = Arc::clone(&self.source);
+                let condition = condition.clone();
+                tokio::spawn(async move {
+                    let result = if tail {
+                        source.tail_logs(&log_group_name, &token, &condition).await
+                    } else {
+                        source.fetch_logs(&log_group_name, &token, &condition).await
+                    };
+                    (log_group_name, result)
+                })
+            })
+            .collect::<Vec<_>>();
+        let mut pages = vec![];
+        for handle in handles {
+            let (log_group_name, result) = handle.await?;
+            let (fetched, token) = result?;
+            self.sources.insert(&log_group_name, &fetched);
+            pages.push((log_group_name, fetched, token));
+        }
+        Ok(pages)
+    }
+}
+
+#[async_trait]
+impl LogSource for MergedLogSource {
+    async fn fetch_log_groups(&self) -> Result<Vec<LogGroup>> {
+        self.source.fetch_log_groups().await
+    }
+
+    /// The next page of the log groups which have more events
+    async fn fetch_logs(
+        &self,
+        _log_group_name: &str,
+        next_token: &Option<String>,
+        condition: &SearchState,
+    ) -> Result<(Vec<FilteredLogEvent>, Option<String>)> {
+        let tokens = match next_token {
+            Some(token) => decode_tokens(token)?
+                .into_iter()
+                .filter(|(_, token)| token.is_some())
+                .collect(),
+            None => {
+                self.sources.clear();
+                self.initial_tokens()
+            }
+        };
+        let pages = self.fetch_pages(tokens, condition, false).await?;
+        let mut pending = self.pending.lock().unwrap();
+        if next_token.is_none() {
+            *pending = Pending::default();
+        }
+        let mut next_tokens = BTreeMap::new();
+        for (log_group_name, events, token) in pages {
+            if let Some(latest) = events.iter().map(timestamp).max() {
+                pending.latest.insert(log_group_name.clone(), latest);
+            }
+            pending.events.extend(events);
+            next_tokens.insert(log_group_name, token);
+        }
+        sort_events(&mut pending.events);
+        // the next pages of the log groups can have the events older than the ones fetched from
+        // the others, which are kept until the log groups catch up with them
+        let until = next_tokens
+            .iter()
+            .filter(|(_, token)| token.is_some())
+            .map(|(log_group_name, _)| pending.latest.get(log_group_name).copied())
+            .min();
+        let len = match until {
+            Some(Some(until)) => pending
+                .events
+                .iter()
+                .position(|e| timestamp(e) > until)
+                .unwrap_or(pending.events.len()),
+            // nothing fetched from the log group yet
+            Some(None) => 0,
+            None => pending.events.len(),
+        };
+        let kept = pending.events.split_off(len);
+        let events = std::mem::replace(&mut pending.events, kept);
+        Ok((events, encode_tokens(&next_tokens)))
+    }
+
+    /// Log streams of all log groups
+    async fn fetch_log_streams(&self, _log_group_name: &str) -> Result<Vec<LogStream>> {
+        let mut log_streams = vec![];
+        for log_group_name in &self.log_group_names {
+            log_streams.append(&mut self.source.fetch_log_streams(log_group_name).await?);
+        }
+        Ok(log_streams)
+    }
+
+    /// The log group of the event is given instead of the merged one
+    async fn fetch_context(
+        &self,
+        log_group_name: &str,
+        log_stream_name: &str,
+        timestamp: i64,
+        size: usize,
+    ) -> Result<Vec<FilteredLogEvent>> {
+        self.source
+            .fetch_context(log_group_name, log_stream_name, timestamp, size)
+            .await
+    }
+
+    /// The latest events of all log groups
+    async fn tail_logs(
+        &self,
+        _log_group_name: &str,
+        next_token: &Option<String>,
+        condition: &SearchState,
+    ) -> Result<(Vec<FilteredLogEvent>, Option<String>)> {
+        let mut tokens = self.initial_tokens();
+        if let Some(token) = next_token {
+            tokens.extend(decode_tokens(token)?);
+        }
+        let mut events = vec![];
+        let mut next_tokens = BTreeMap::new();
+        for (log_group_name, fetched, token) in self.fetch_pages(tokens, condition, true).await? {
+            events.extend(fetched);
+            next_tokens.insert(log_group_name, token);
+        }
+        sort_events(&mut events);
+        Ok((events, encode_tokens(&next_tokens)))
+    }
+}
+
+/// The next tokens of the log groups in one token. None if all log groups have no more events.
+fn encode_tokens(tokens: &BTreeMap<String, Option<String>>) -> Option<String> {
+    if tokens.values().all(|t| t.is_none()) {
+        None
+    } else {
+        serde_json::to_string(tokens).ok()
+    }
+}
+
+fn decode_tokens(token: &str) -> Result<BTreeMap<String, Option<String>>> {
+    Ok(serde_json::from_str(token)?)
+}
+
+/// The log groups of the events in the merged timeline by the event id, which is unique across
+/// the log groups like the timeline skipping the pushed events assumes. Recorded by the merged
+/// log source when the events are fetched and read by the event area, the events are left as
+/// they are fetched. The clones share the map.
+#[derive(Debug, Clone, Default)]
+pub struct EventSources {
+    groups: Arc<Mutex<HashMap<String, String>>>,
+}
+
+impl EventSources {
+    /// Record the log group of the events
+    pub fn insert(&self, log_group_name: &str, events: &[FilteredLogEvent]) {
+        let mut groups = self.groups.lock().unwrap();
+        for id in events.iter().filter_map(|e| e.event_id.as_ref()) {
+            groups.insert(id.clone(), log_group_name.to_string());
+        }
+    }
+
+    /// Forget the events of the previous query
+    fn clear(&self) {
+        self.groups.lock().unwrap().clear();
+    }
+
+    /// The log group of the event, None if the event has no id
+    pub fn get(&self, event: &FilteredLogEvent) -> Option<String> {
+        let id = event.event_id.as_ref()?;
+        self.groups.lock().unwrap().get(id).cloned()
+    }
+}
+
+/// Stable sort by timestamp, the events of the same time keep the order of the log groups
+pub fn sort_events(events: &mut [FilteredLogEvent]) {
+    events.sort_by_key(timestamp);
+}
+
+fn timestamp(event: &FilteredLogEvent) -> i64 {
+    event.timestamp.unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        client::memory::MemoryLogSource, state::search_state::SearchMode,
+        test_helper::make_log_events,
+    };
+
+    /// Returns the events one by one with the index as the next token
+    struct PagedLogSource {
+        source: MemoryLogSource,
+    }
+
+    #[async_trait]
+    impl LogSource for PagedLogSource {
+        async fn fetch_log_groups(&self) -> Result<Vec<LogGroup>> {
+            self.source.fetch_log_groups().await
+        }
+
+        async fn fetch_logs(
+            &self,
+            log_group_name: &str,
+            next_token: &Option<String>,
+            condition: &SearchState,
+        ) -> Result<(Vec<FilteredLogEvent>, Option<String>)> {
+            let (events, _) = self
+                .source
+                .fetch_logs(log_group_name, &None, condition)
+                .await?;
+            let idx = next_token
+                .as_ref()
+                .map(|t| t.parse::<usize>().unwrap())
+                .unwrap_or_default();
+            let next_token = if idx + 1 < events.len() {
+                Some((idx + 1).to_string())
+            } else {
+                None
+            };
+            Ok((events.into_iter().skip(idx).take(1).collect(), next_token))
+        }
+
+        async fn fetch_log_streams(&self, log_group_name: &str) -> Result<Vec<LogStream>> {
+            self.source.fetch_log_streams(log_group_name).await
+        }
+    }
+
+    fn condition() -> SearchState {
+        SearchState::new(String::default(), SearchMode::FromTo(None, None))
+    }
+
+    fn merged_source() -> MergedLogSource {
+        let mut api = make_log_events(0, 1, 1000);
+        api[1].timestamp = Some(3000);
+        let mut worker = make_log_events(0, 2, 2000);
+        worker[0].timestamp = Some(500);
+        worker[1].timestamp = Some(900);
+        // the event ids are unique across the log groups
+        for event in worker.iter_mut() {
+            event.event_id = event.event_id.as_ref().map(|id| format!("w{}", id));
+        }
+        let source = PagedLogSource {
+            source: MemoryLogSource::new()
+                .with_events("api", api)
+                .with_events("worker", worker),
+        };
+        MergedLogSource::new(
+            Arc::new(source),
+            vec![String::from("api"), String::from("worker")],
+            EventSources::default(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_fetch_logs() {
+        let timestamps =
+            |events: &[FilteredLogEvent]| events.iter().map(|e| e.timestamp).collect::<Vec<_>>();
+        let source = merged_source();
+        // the api event is kept until the worker catches up with it
+        let (events, next_token) = source.fetch_logs("", &None, &condition()).await.unwrap();
+        assert_eq!(vec![Some(500)], timestamps(&events));
+        // the ids are kept and the log groups are recorded aside
+        assert_eq!(Some(String::from("w0")), events[0].event_id);
+        assert_eq!(Some(String::from("worker")), source.sources.get(&events[0]));
+        // each log group has its own next token
+        let (events, next_token) = source
+            .fetch_logs("", &next_token, &condition())
+            .await
+            .unwrap();
+        assert_eq!(vec![Some(900)], timestamps(&events));
+        // only the worker has more events, the rest is returned with its last page
+        let (events, next_token) = source
+            .fetch_logs("", &next_token, &condition())
+            .await
+            .unwrap();
+        assert_eq!(
+            vec![Some(1000), Some(3000), Some(4000)],
+            timestamps(&events)
+        );
+        assert_eq!(Some(String::from("api")), source.sources.get(&events[0]));
+        assert_eq!(Some(String::from("w2")), events[2].event_id);
+        assert_eq!(None, next_token);
+        // the new query starts over
+        let (events, _) = source.fetch_logs("", &None, &condition()).await.unwrap();
+        assert_eq!(vec![Some(500)], timestamps(&events));
+    }
+
+    #[tokio::test]
+    async fn test_tail_logs() {
+        let source = merged_source();
+        let (events, next_token) = source.tail_logs("", &None, &condition()).await.unwrap();
+        assert_eq!(2, events.len());
+        let (events, _) = source
+            .tail_logs("", &next_token, &condition())
+            .await
+            .unwrap();
+        assert_eq!(2, events.len());
+        // log streams of all log groups
+        assert!(source.fetch_log_streams("").await.is_ok());
+    }
+
+    #[test]
+    fn test_event_sources() {
+        let sources = EventSources::default();
+        let mut events = make_log_events(0, 1, 0);
+        sources.insert("/aws/api", &events[..1]);
+        sources.insert("/aws/api:v2", &events[1..]);
+        assert_eq!(Some(String::from("/aws/api")), sources.get(&events[0]));
+        assert_eq!(
+            Some(String::from("/aws/api:v2")),
+            sources.clone().get(&events[1])
+        );
+        events[0].event_id = None;
+        assert_eq!(None, sources.get(&events[0]));
+        sources.clear();
+        assert_eq!(None, sources.get(&events[1]));
+    }
+}
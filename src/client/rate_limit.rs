@@ -0,0 +1,254 @@
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use rusoto_logs::{FilteredLogEvent, LogGroup, LogStream};
+
+use super::{
+    live_tail::{LiveTailSession, LiveTailUnsupported},
+    LogSource,
+};
+use crate::{
+    insights::{InsightsResults, QueryStatus},
+    state::search_state::SearchState,
+};
+
+/// Token bucket shared by all clones of the client, so that the panes together
+/// call the API at most `rate` times per second. Bursts up to `rate` calls are allowed.
+pub struct RateLimiter {
+    /// tokens per second, no limit if not positive
+    rate: f64,
+    /// tokens left and the time they were counted
+    bucket: Mutex<(f64, Instant)>,
+    queued: AtomicUsize,
+}
+
+impl RateLimiter {
+    pub fn new(rate: f64) -> Self {
+        RateLimiter {
+            rate,
+            bucket: Mutex::new((rate.max(1.0), Instant::now())),
+            queued: AtomicUsize::new(0),
+        }
+    }
+
+    fn is_unlimited(&self) -> bool {
+        self.rate <= 0.0
+    }
+
+    /// Number of the calls waiting for the token
+    pub fn queued(&self) -> usize {
+        self.queued.load(Ordering::SeqCst)
+    }
+
+    /// Take the token, or the time to wait until the next token
+    fn try_acquire(&self, now: Instant) -> Option<Duration> {
+        let mut bucket = self.bucket.lock().unwrap();
+        let (tokens, last) = *bucket;
+        let elapsed = now.saturating_duration_since(last).as_secs_f64();
+        let tokens = (tokens + elapsed * self.rate).min(self.rate.max(1.0));
+        if tokens >= 1.0 {
+            *bucket = (tokens - 1.0, now);
+            None
+        } else {
+            *bucket = (tokens, now);
+            Some(Duration::from_secs_f64((1.0 - tokens) / self.rate))
+        }
+    }
+
+    /// Wait for the token
+    pub async fn acquire(&self) {
+        if self.is_unlimited() {
+            return;
+        }
+        let mut wait = match self.try_acquire(Instant::now()) {
+            Some(wait) => wait,
+            None => return,
+        };
+        self.queued.fetch_add(1, Ordering::SeqCst);
+        // the waiting call may be dropped, e.g. when the pane is closed
+        let _queued = scopeguard::guard((), |_| {
+            self.queued.fetch_sub(1, Ordering::SeqCst);
+        });
+        loop {
+            tokio::time::delay_for(wait).await;
+            match self.try_acquire(Instant::now()) {
+                Some(next) => wait = next,
+                None => return,
+            }
+        }
+    }
+}
+
+/// LogSource calling the API after taking the token of the shared rate limiter
+pub struct RateLimitedLogSource {
+    source: Arc<dyn LogSource>,
+    limiter: Arc<RateLimiter>,
+}
+
+impl RateLimitedLogSource {
+    pub fn new(source: Arc<dyn LogSource>, limiter: Arc<RateLimiter>) -> Self {
+        RateLimitedLogSource { source, limiter }
+    }
+}
+
+#[async_trait]
+impl LogSource for RateLimitedLogSource {
+    async fn fetch_log_groups(&self) -> Result<Vec<LogGroup>> {
+        self.limiter.acquire().await;
+        self.source.fetch_log_groups().await
+    }
+
+    async fn fetch_logs(
+        &self,
+        log_group_name: &str,
+        next_token: &Option<String>,
+        condition: &SearchState,
+    ) -> Result<(Vec<FilteredLogEvent>, Option<String>)> {
+        self.limiter.acquire().await;
+        self.source
+            .fetch_logs(log_group_name, next_token, condition)
+            .await
+    }
+
+    async fn fetch_log_streams(&self, log_group_name: &str) -> Result<Vec<LogStream>> {
+        self.limiter.acquire().await;
+        self.source.fetch_log_streams(log_group_name).await
+    }
+
+    async fn tail_logs(
+        &self,
+        log_group_name: &str,
+        next_token: &Option<String>,
+        condition: &SearchState,
+    ) -> Result<(Vec<FilteredLogEvent>, Option<String>)> {
+        self.limiter.acquire().await;
+        self.source
+            .tail_logs(log_group_name, next_token, condition)
+            .await
+    }
+
+    fn supports_live_tail(&self) -> bool {
+        self.source.supports_live_tail()
+    }
+
+    /// The session itself is not limited, the events are pushed by the server.
+    /// No token is taken if the source does not support it.
+    async fn start_live_tail(
+        &self,
+        log_group_name: &str,
+        condition: &SearchState,
+    ) -> Result<Box<dyn LiveTailSession>> {
+        if !self.source.supports_live_tail() {
+            return Err(LiveTailUnsupported.into());
+        }
+        self.limiter.acquire().await;
+        self.source.start_live_tail(log_group_name, condition).await
+    }
+
+    /// Two calls of GetLogEvents for CloudWatch
+    async fn fetch_context(
+        &self,
+        log_group_name: &str,
+        log_stream_name: &str,
+        timestamp: i64,
+        size: usize,
+    ) -> Result<Vec<FilteredLogEvent>> {
+        self.limiter.acquire().await;
+        self.limiter.acquire().await;
+        self.source
+            .fetch_context(log_group_name, log_stream_name, timestamp, size)
+            .await
+    }
+
+    async fn start_query(&self, log_group_name: &str, condition: &SearchState) -> Result<String> {
+        self.limiter.acquire().await;
+        self.source.start_query(log_group_name, condition).await
+    }
+
+    async fn get_query_results(&self, query_id: &str) -> Result<(QueryStatus, InsightsResults)> {
+        self.limiter.acquire().await;
+        self.source.get_query_results(query_id).await
+    }
+
+    async fn stop_query(&self, query_id: &str) -> Result<()> {
+        self.limiter.acquire().await;
+        self.source.stop_query(query_id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::{live_tail::is_unsupported, memory::MemoryLogSource};
+
+    #[test]
+    fn test_try_acquire() {
+        let limiter = RateLimiter::new(2.0);
+        let now = limiter.bucket.lock().unwrap().1;
+        // the burst
+        assert_eq!(None, limiter.try_acquire(now));
+        assert_eq!(None, limiter.try_acquire(now));
+        assert_eq!(Some(Duration::from_millis(500)), limiter.try_acquire(now));
+        // a token in 0.5 seconds
+        assert_eq!(
+            Some(Duration::from_millis(250)),
+            limiter.try_acquire(now + Duration::from_millis(250))
+        );
+        assert_eq!(None, limiter.try_acquire(now + Duration::from_millis(500)));
+        // the tokens are not saved more than the rate
+        let later = now + Duration::from_secs(10);
+        assert_eq!(None, limiter.try_acquire(later));
+        assert_eq!(None, limiter.try_acquire(later));
+        assert!(limiter.try_acquire(later).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_acquire() {
+        let limiter = Arc::new(RateLimiter::new(20.0));
+        let start = Instant::now();
+        let handles = (0..25)
+            .map(|_| {
+                let limiter = Arc::clone(&limiter);
+                tokio::spawn(async move { limiter.acquire().await })
+            })
+            .collect::<Vec<_>>();
+        tokio::time::delay_for(Duration::from_millis(10)).await;
+        // 20 calls by the burst, the rest are queued
+        assert_eq!(5, limiter.queued());
+        for handle in handles {
+            handle.await.unwrap();
+        }
+        assert_eq!(0, limiter.queued());
+        assert!(start.elapsed() >= Duration::from_millis(200));
+        // no limit
+        let limiter = RateLimiter::new(0.0);
+        for _ in 0..100 {
+            limiter.acquire().await;
+        }
+        assert_eq!(0, limiter.queued());
+    }
+
+    #[tokio::test]
+    async fn test_start_live_tail_unsupported() {
+        let limiter = Arc::new(RateLimiter::new(1.0));
+        let source =
+            RateLimitedLogSource::new(Arc::new(MemoryLogSource::new()), Arc::clone(&limiter));
+        assert!(!source.supports_live_tail());
+        let condition = SearchState::default();
+        for _ in 0..2 {
+            match source.start_live_tail("group", &condition).await {
+                Err(e) => assert!(is_unsupported(&e)),
+                Ok(_) => panic!("the live tail is not supported"),
+            }
+        }
+        // the token is left for the other calls
+        assert_eq!(None, limiter.try_acquire(Instant::now()));
+    }
+}
@@ -0,0 +1,252 @@
+use std::{
+    collections::hash_map::RandomState,
+    future::Future,
+    hash::{BuildHasher, Hasher},
+    time::Duration,
+};
+
+use anyhow::Result;
+use log::warn;
+use rusoto_core::RusotoError;
+use rusoto_logs::{
+    DescribeLogGroupsError, DescribeLogStreamsError, FilterLogEventsError, GetLogEventsError,
+    GetQueryResultsError, StartQueryError,
+};
+
+use crate::constant::{RETRY_BASE_DELAY, RETRY_MAX_ATTEMPTS, RETRY_MAX_DELAY};
+
+/// How the failed call should be handled
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ErrorKind {
+    /// The rate of the calls should be lowered
+    Throttled,
+    /// The same call may succeed later
+    Transient,
+    /// The call fails again, e.g. the log group does not exist or the credentials are expired
+    Permanent,
+}
+
+impl ErrorKind {
+    pub fn is_retryable(self) -> bool {
+        self != ErrorKind::Permanent
+    }
+}
+
+macro_rules! classify_rusoto_errors {
+    ($e:expr, $($t:ident),*) => {
+        $(
+            if let Some(e) = $e.downcast_ref::<RusotoError<$t>>() {
+                return classify_rusoto(e, |s| matches!(s, $t::ServiceUnavailable(_)));
+            }
+        )*
+    };
+}
+
+/// Classify the error of the CloudWatch Logs API.
+/// The other errors are throttled if the message says so, or permanent.
+pub fn classify(e: &anyhow::Error) -> ErrorKind {
+    classify_rusoto_errors!(
+        e,
+        DescribeLogGroupsError,
+        DescribeLogStreamsError,
+        FilterLogEventsError,
+        GetLogEventsError,
+        StartQueryError,
+        GetQueryResultsError
+    );
+    match classify_response(0, &e.to_string()) {
+        ErrorKind::Throttled => ErrorKind::Throttled,
+        _ => ErrorKind::Permanent,
+    }
+}
+
+fn classify_rusoto<E>(e: &RusotoError<E>, is_unavailable: impl Fn(&E) -> bool) -> ErrorKind {
+    match e {
+        RusotoError::Service(s) if is_unavailable(s) => ErrorKind::Transient,
+        RusotoError::HttpDispatch(_) => ErrorKind::Transient,
+        // ThrottlingException is not modeled by rusoto, it comes as the unknown response
+        RusotoError::Unknown(response) => {
+            classify_response(response.status.as_u16(), response.body_as_str())
+        }
+        _ => ErrorKind::Permanent,
+    }
+}
+
+/// Classify the error response by the status code and the error type in the body
+pub fn classify_response(status: u16, body: &str) -> ErrorKind {
+    if status == 429
+        || ["Throttling", "TooManyRequests", "RequestLimitExceeded"]
+            .iter()
+            .any(|t| body.contains(t))
+    {
+        ErrorKind::Throttled
+    } else if status >= 500 {
+        ErrorKind::Transient
+    } else {
+        ErrorKind::Permanent
+    }
+}
+
+/// Retry of the throttled or transient failures with the exponential backoff and the full jitter
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryPolicy {
+    /// attempts including the first call, 1 for no retry
+    pub max_attempts: usize,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: *RETRY_MAX_ATTEMPTS,
+            base_delay: *RETRY_BASE_DELAY,
+            max_delay: *RETRY_MAX_DELAY,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// The upper bound of the delay before the retry, doubled at every attempt (0 origin)
+    pub fn max_backoff(&self, attempt: usize) -> Duration {
+        let factor = 1u32.checked_shl(attempt as u32).unwrap_or(u32::MAX);
+        self.base_delay
+            .checked_mul(factor)
+            .map_or(self.max_delay, |d| d.min(self.max_delay))
+    }
+
+    /// A random delay up to `max_backoff`, so that the panes throttled at once do not retry at once
+    pub fn backoff(&self, attempt: usize) -> Duration {
+        let max = self.max_backoff(attempt).as_millis() as u64;
+        Duration::from_millis(random() % (max + 1))
+    }
+
+    /// Call `f` until it succeeds, fails permanently or `max_attempts` is reached
+    pub async fn run<F, Fut, T>(&self, mut f: F) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let mut attempt = 0;
+        loop {
+            match f().await {
+                Err(e) if attempt + 1 < self.max_attempts && classify(&e).is_retryable() => {
+                    let delay = self.backoff(attempt);
+                    warn!("retry in {:?} (attempt {}): {}", delay, attempt + 1, e);
+                    tokio::time::delay_for(delay).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+/// A random number by the randomly seeded hasher of the standard library
+fn random() -> u64 {
+    RandomState::new().build_hasher().finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use anyhow::anyhow;
+    use rusoto_core::request::HttpDispatchError;
+
+    use super::*;
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(4),
+        }
+    }
+
+    #[test]
+    fn test_classify() {
+        assert_eq!(
+            ErrorKind::Throttled,
+            classify_response(
+                400,
+                r#"{"__type":"ThrottlingException","message":"Rate exceeded"}"#
+            )
+        );
+        assert_eq!(ErrorKind::Throttled, classify_response(429, ""));
+        assert_eq!(ErrorKind::Transient, classify_response(503, ""));
+        assert_eq!(
+            ErrorKind::Permanent,
+            classify_response(400, r#"{"__type":"ExpiredTokenException"}"#)
+        );
+        let e: RusotoError<FilterLogEventsError> =
+            RusotoError::HttpDispatch(HttpDispatchError::new(String::from("timeout")));
+        assert_eq!(ErrorKind::Transient, classify(&e.into()));
+        let e: RusotoError<GetLogEventsError> = RusotoError::Service(
+            GetLogEventsError::ServiceUnavailable(String::from("unavailable")),
+        );
+        assert_eq!(ErrorKind::Transient, classify(&e.into()));
+        let e: RusotoError<FilterLogEventsError> = RusotoError::Service(
+            FilterLogEventsError::ResourceNotFound(String::from("no group")),
+        );
+        assert_eq!(ErrorKind::Permanent, classify(&e.into()));
+        assert_eq!(ErrorKind::Permanent, classify(&anyhow!("other")));
+        assert_eq!(
+            ErrorKind::Throttled,
+            classify(&anyhow!("ThrottlingException: Rate exceeded"))
+        );
+    }
+
+    #[test]
+    fn test_backoff() {
+        let policy = policy();
+        assert_eq!(Duration::from_millis(1), policy.max_backoff(0));
+        assert_eq!(Duration::from_millis(2), policy.max_backoff(1));
+        assert_eq!(Duration::from_millis(4), policy.max_backoff(2));
+        assert_eq!(Duration::from_millis(4), policy.max_backoff(100));
+        assert!((0..100).all(|_| policy.backoff(1) <= Duration::from_millis(2)));
+    }
+
+    #[tokio::test]
+    async fn test_run() {
+        let unavailable = || -> anyhow::Error {
+            RusotoError::<FilterLogEventsError>::HttpDispatch(HttpDispatchError::new(String::from(
+                "timeout",
+            )))
+            .into()
+        };
+        // succeeds at the last attempt
+        let calls = AtomicUsize::new(0);
+        let result = policy()
+            .run(|| async {
+                if calls.fetch_add(1, Ordering::SeqCst) < 2 {
+                    Err(unavailable())
+                } else {
+                    Ok(1)
+                }
+            })
+            .await;
+        assert_eq!(1, result.unwrap());
+        assert_eq!(3, calls.load(Ordering::SeqCst));
+        // gives up after max_attempts
+        let calls = AtomicUsize::new(0);
+        let result: Result<()> = policy()
+            .run(|| async {
+                calls.fetch_add(1, Ordering::SeqCst);
+                Err(unavailable())
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(3, calls.load(Ordering::SeqCst));
+        // the permanent failure is not retried
+        let calls = AtomicUsize::new(0);
+        let result: Result<()> = policy()
+            .run(|| async {
+                calls.fetch_add(1, Ordering::SeqCst);
+                Err(anyhow!("log group does not exist"))
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(1, calls.load(Ordering::SeqCst));
+    }
+}
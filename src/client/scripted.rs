@@ -0,0 +1,153 @@
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+};
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use rusoto_logs::{FilteredLogEvent, LogGroup, LogStream};
+
+use super::{
+    live_tail::{ChannelLiveTail, LiveTailSession, LiveTailUnsupported},
+    LogSource,
+};
+use crate::{
+    insights::{InsightsResults, QueryStatus},
+    state::search_state::SearchState,
+};
+
+/// The next token and the time range of the fetch
+pub type ScriptedCall = (Option<String>, (Option<i64>, Option<i64>));
+/// The events and the next token, or the error message
+type ScriptedResponse = std::result::Result<(Vec<FilteredLogEvent>, Option<String>), String>;
+
+/// LogSource returning the scripted responses of the fetches in order, and recording the calls.
+/// The live tail sessions are also scripted, the live tail is not supported after them.
+/// The Logs Insights queries keep running until they are stopped.
+/// Built for the tests only. The clones share the script and the calls.
+#[derive(Clone, Default)]
+pub struct ScriptedLogSource {
+    responses: Arc<Mutex<VecDeque<ScriptedResponse>>>,
+    calls: Arc<Mutex<Vec<ScriptedCall>>>,
+    /// the sessions, or the error messages of starting them
+    live_tails: Arc<Mutex<VecDeque<std::result::Result<ChannelLiveTail, String>>>>,
+    live_tail_starts: Arc<Mutex<usize>>,
+    stopped_queries: Arc<Mutex<Vec<String>>>,
+}
+
+impl ScriptedLogSource {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Return the events and the next token at the next fetch
+    pub fn then_events(self, events: Vec<FilteredLogEvent>, next_token: Option<&str>) -> Self {
+        self.responses
+            .lock()
+            .unwrap()
+            .push_back(Ok((events, next_token.map(String::from))));
+        self
+    }
+
+    /// Fail the next fetch with the message
+    pub fn then_error(self, message: &str) -> Self {
+        self.responses
+            .lock()
+            .unwrap()
+            .push_back(Err(message.to_string()));
+        self
+    }
+
+    /// Start the session at the next start of the live tail, fed by its sender
+    pub fn then_live_tail(self, session: ChannelLiveTail) -> Self {
+        self.live_tails.lock().unwrap().push_back(Ok(session));
+        self
+    }
+
+    /// Fail the next start of the live tail with the message
+    pub fn then_live_tail_error(self, message: &str) -> Self {
+        self.live_tails
+            .lock()
+            .unwrap()
+            .push_back(Err(message.to_string()));
+        self
+    }
+
+    /// The number of the starts of the live tail so far
+    pub fn live_tail_starts(&self) -> usize {
+        *self.live_tail_starts.lock().unwrap()
+    }
+
+    /// The ids of the Logs Insights queries stopped so far
+    pub fn stopped_queries(&self) -> Vec<String> {
+        self.stopped_queries.lock().unwrap().clone()
+    }
+
+    /// The calls of the fetches so far
+    pub fn calls(&self) -> Vec<ScriptedCall> {
+        self.calls.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl LogSource for ScriptedLogSource {
+    async fn fetch_log_groups(&self) -> Result<Vec<LogGroup>> {
+        Ok(vec![])
+    }
+
+    /// No events after the script ends
+    async fn fetch_logs(
+        &self,
+        _log_group_name: &str,
+        next_token: &Option<String>,
+        condition: &SearchState,
+    ) -> Result<(Vec<FilteredLogEvent>, Option<String>)> {
+        self.calls
+            .lock()
+            .unwrap()
+            .push((next_token.clone(), condition.mode.get_timestamps()));
+        match self.responses.lock().unwrap().pop_front() {
+            Some(Ok(response)) => Ok(response),
+            Some(Err(message)) => Err(anyhow!("{}", message)),
+            None => Ok((vec![], None)),
+        }
+    }
+
+    async fn fetch_log_streams(&self, _log_group_name: &str) -> Result<Vec<LogStream>> {
+        Ok(vec![])
+    }
+
+    fn supports_live_tail(&self) -> bool {
+        true
+    }
+
+    async fn start_live_tail(
+        &self,
+        _log_group_name: &str,
+        _condition: &SearchState,
+    ) -> Result<Box<dyn LiveTailSession>> {
+        *self.live_tail_starts.lock().unwrap() += 1;
+        match self.live_tails.lock().unwrap().pop_front() {
+            Some(Ok(session)) => Ok(Box::new(session)),
+            Some(Err(message)) => Err(anyhow!("{}", message)),
+            None => Err(LiveTailUnsupported.into()),
+        }
+    }
+
+    /// The query id is the log group name
+    async fn start_query(&self, log_group_name: &str, _condition: &SearchState) -> Result<String> {
+        Ok(log_group_name.to_string())
+    }
+
+    async fn get_query_results(&self, _query_id: &str) -> Result<(QueryStatus, InsightsResults)> {
+        Ok((QueryStatus::Running, InsightsResults::new(vec![])))
+    }
+
+    async fn stop_query(&self, query_id: &str) -> Result<()> {
+        self.stopped_queries
+            .lock()
+            .unwrap()
+            .push(query_id.to_string());
+        Ok(())
+    }
+}
@@ -0,0 +1,289 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::RwLock,
+};
+
+use anyhow::{anyhow, Result};
+use lazy_static::lazy_static;
+use serde::Deserialize;
+use tui::style::Color;
+
+use crate::{
+    keymap::KeyMap,
+    state::search_state::{SearchMode, SearchState},
+    stream_column::StreamColumn,
+};
+
+lazy_static! {
+    static ref GLOBAL: RwLock<Config> = RwLock::new(Config::default());
+}
+
+/// Set the config which the constants are read from.
+/// It has to be called before any of the constants is used.
+pub fn init(config: Config) {
+    *GLOBAL.write().unwrap() = config;
+}
+
+/// The config set by `init`, or the default one
+pub fn global() -> Config {
+    GLOBAL.read().unwrap().clone()
+}
+
+/// `~/.config/megane/config.toml`
+pub fn default_path() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(".config").join("megane").join("config.toml"))
+}
+
+/// Contents of the config file. All fields are optional, the defaults are in `constant.rs`.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    pub profile: Option<String>,
+    pub region: Option<String>,
+    pub role_arn: Option<String>,
+    pub role_name: Option<String>,
+    /// milliseconds between the fetches in tail mode
+    pub tail_rate: Option<u64>,
+    /// milliseconds before the latest event fetched again in tail mode
+    pub tail_overlap: Option<u64>,
+    /// milliseconds between the redraws
+    pub input_tick_rate: Option<u64>,
+    /// calls of the CloudWatch Logs API including the retries of the throttled ones
+    pub max_attempts: Option<usize>,
+    /// API calls per second of all panes together, 0 for no limit
+    pub requests_per_second: Option<f64>,
+    pub logevent_step: Option<usize>,
+    /// log events shown before and after the hit in the context view
+    pub context_size: Option<usize>,
+    pub date_format: Option<String>,
+    /// how the log stream is shown at first: full, truncated, hashed or hidden
+    pub stream_column: Option<String>,
+    /// log groups opened at once
+    pub max_panes: Option<usize>,
+    /// fields of the JSON messages shown as the columns, e.g. `["level", "http.status"]`
+    #[serde(default)]
+    pub json_fields: Vec<String>,
+    #[serde(default)]
+    pub colors: Colors,
+    #[serde(default)]
+    pub workspaces: BTreeMap<String, Workspace>,
+    /// default, vim or emacs
+    pub keymap: Option<String>,
+    /// Overrides of the keymap, e.g. `next_log_event = ["j", "ctrl+n"]`
+    #[serde(default)]
+    pub keys: BTreeMap<String, Vec<String>>,
+}
+
+/// Colour names (e.g. "yellow", "lightblue"), 256 colour indexes (e.g. "208") or "#rrggbb"
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Colors {
+    pub selected: Option<String>,
+    pub deselected: Option<String>,
+}
+
+/// Log groups opened at once by `--workspace`
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Workspace {
+    #[serde(default)]
+    pub panes: Vec<Pane>,
+}
+
+/// A log group and its search condition
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Pane {
+    pub log_group: String,
+    #[serde(default)]
+    pub query: String,
+    /// tail, 1m, 30m, 1h or 12h. Tail if neither of range and from/to is provided.
+    pub range: Option<String>,
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+impl Config {
+    /// Read the config file. The default config is returned if the file does not exist.
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Config::default());
+        }
+        let config = Self::parse(&fs::read_to_string(path)?)
+            .map_err(|e| anyhow!("invalid config {}: {}", path.display(), e))?;
+        Ok(config)
+    }
+
+    pub fn parse(input: &str) -> Result<Self> {
+        let config: Config = toml::from_str(input)?;
+        // fail at startup rather than falling back to the default silently
+        for color in [&config.colors.selected, &config.colors.deselected]
+            .iter()
+            .filter_map(|c| c.as_ref())
+        {
+            parse_color(color)?;
+        }
+        if let Some(stream_column) = &config.stream_column {
+            StreamColumn::from_name(stream_column)?;
+        }
+        config.keymap()?;
+        Ok(config)
+    }
+
+    pub fn keymap(&self) -> Result<KeyMap> {
+        KeyMap::from_config(self.keymap.as_deref(), &self.keys)
+    }
+
+    pub fn workspace(&self, name: &str) -> Result<&Workspace> {
+        self.workspaces
+            .get(name)
+            .ok_or_else(|| anyhow!("workspace {} is not found in the config", name))
+    }
+}
+
+impl Pane {
+    /// from/to accept the local time in DATE_FORMAT, so the config has to be set by `init` before calling this
+    pub fn search_state(&self) -> Result<SearchState> {
+        let mode = if self.range.is_none() && self.from.is_none() && self.to.is_none() {
+            SearchMode::Tail
+        } else {
+            SearchMode::parse(
+                self.range.as_deref(),
+                self.from.as_deref(),
+                self.to.as_deref(),
+            )?
+        };
+        Ok(SearchState::new(self.query.clone(), mode))
+    }
+}
+
+pub fn parse_color(input: &str) -> Result<Color> {
+    let color = match input.to_lowercase().as_str() {
+        "reset" => Color::Reset,
+        "black" => Color::Black,
+        "red" => Color::Red,
+        "green" => Color::Green,
+        "yellow" => Color::Yellow,
+        "blue" => Color::Blue,
+        "magenta" => Color::Magenta,
+        "cyan" => Color::Cyan,
+        "gray" => Color::Gray,
+        "darkgray" => Color::DarkGray,
+        "lightred" => Color::LightRed,
+        "lightgreen" => Color::LightGreen,
+        "lightyellow" => Color::LightYellow,
+        "lightblue" => Color::LightBlue,
+        "lightmagenta" => Color::LightMagenta,
+        "lightcyan" => Color::LightCyan,
+        "white" => Color::White,
+        s if s.starts_with('#') && s.len() == 7 => {
+            let rgb = u32::from_str_radix(&s[1..], 16)?;
+            Color::Rgb((rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8)
+        }
+        s => Color::Indexed(
+            s.parse()
+                .map_err(|_| anyhow!("unknown colour: {}", input))?,
+        ),
+    };
+    Ok(color)
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    use super::*;
+    use crate::keymap::Action;
+
+    #[test]
+    fn test_parse() {
+        let config = Config::parse(
+            r##"
+profile = "dev"
+region = "ap-northeast-1"
+tail_rate = 2000
+tail_overlap = 30000
+max_attempts = 2
+requests_per_second = 2.5
+logevent_step = 10
+max_panes = 8
+json_fields = ["level", "http.status"]
+stream_column = "truncated"
+keymap = "vim"
+
+[colors]
+selected = "#ff8800"
+
+[keys]
+next_log_event = ["j", "ctrl+n"]
+
+[workspaces.api]
+panes = [
+  { log_group = "/aws/lambda/api", query = "ERROR" },
+  { log_group = "/aws/lambda/worker", range = "1h" },
+]
+"##,
+        )
+        .unwrap();
+        assert_eq!(Some(String::from("dev")), config.profile);
+        assert_eq!(Some(2000), config.tail_rate);
+        assert_eq!(Some(30000), config.tail_overlap);
+        assert_eq!(None, config.input_tick_rate);
+        assert_eq!(Some(2), config.max_attempts);
+        assert_eq!(Some(2.5), config.requests_per_second);
+        assert_eq!(Some(8), config.max_panes);
+        assert_eq!(Some(String::from("truncated")), config.stream_column);
+        assert_eq!(
+            vec![String::from("level"), String::from("http.status")],
+            config.json_fields
+        );
+        assert_eq!(
+            &[
+                KeyEvent::new(KeyCode::Char('j'), KeyModifiers::NONE),
+                KeyEvent::new(KeyCode::Char('n'), KeyModifiers::CONTROL)
+            ],
+            config.keymap().unwrap().keys(Action::NextLogEvent)
+        );
+        let panes = &config.workspace("api").unwrap().panes;
+        assert_eq!(
+            SearchState::new(String::from("ERROR"), SearchMode::Tail),
+            panes[0].search_state().unwrap()
+        );
+        assert_eq!(
+            SearchState::new(String::default(), SearchMode::OneHour),
+            panes[1].search_state().unwrap()
+        );
+        assert!(config.workspace("web").is_err());
+        // empty
+        assert_eq!(Config::default(), Config::parse("").unwrap());
+        // invalid
+        assert!(Config::parse("unknown_key = 1").is_err());
+        assert!(Config::parse("[colors]\nselected = \"sky\"").is_err());
+        assert!(Config::parse("keymap = \"nano\"").is_err());
+        assert!(Config::parse("stream_column = \"short\"").is_err());
+        assert!(Config::parse("[keys]\nexit = [\"hyper+q\"]").is_err());
+        let pane = Pane {
+            log_group: String::from("api"),
+            range: Some(String::from("2d")),
+            ..Default::default()
+        };
+        assert!(pane.search_state().is_err());
+    }
+
+    #[test]
+    fn test_load() {
+        let config = Config::load(Path::new("not-exist.toml")).unwrap();
+        assert_eq!(Config::default(), config);
+    }
+
+    #[test]
+    fn test_parse_color() {
+        assert_eq!(Color::LightBlue, parse_color("LightBlue").unwrap());
+        assert_eq!(Color::Rgb(255, 136, 0), parse_color("#ff8800").unwrap());
+        assert_eq!(Color::Indexed(208), parse_color("208").unwrap());
+        assert!(parse_color("sky").is_err());
+        assert!(parse_color("#zzzzzz").is_err());
+    }
+}
@@ -3,55 +3,92 @@ use std::time::Duration;
 use lazy_static::lazy_static;
 use tui::style::{Color, Style};
 
+use crate::{
+    config,
+    key_event_wrapper::KeyEventWrapper,
+    keymap::{Action, KeyMap},
+    stream_column::StreamColumn,
+};
+
 lazy_static! {
-    pub static ref TAIL_RATE: Duration = Duration::from_secs(1);
-    pub static ref LOGEVENT_STEP: usize = 5;
-    pub static ref DATE_FORMAT: String = String::from("%Y-%m-%d %H:%M:%S");
+    static ref CONFIG: config::Config = config::global();
+    pub static ref TAIL_RATE: Duration = CONFIG
+        .tail_rate
+        .map(Duration::from_millis)
+        .unwrap_or_else(|| Duration::from_secs(1));
+    /// tail mode fetches this window before the latest event again, for the events ingested late
+    pub static ref TAIL_OVERLAP: Duration = CONFIG
+        .tail_overlap
+        .map(Duration::from_millis)
+        .unwrap_or_else(|| Duration::from_secs(10));
+    pub static ref INPUT_TICK_RATE: Duration = CONFIG
+        .input_tick_rate
+        .map(Duration::from_millis)
+        .unwrap_or_else(|| Duration::from_millis(100));
+    /// budget of the API calls of all panes, no limit if 0
+    pub static ref REQUESTS_PER_SECOND: f64 = CONFIG.requests_per_second.unwrap_or(5.0);
+    pub static ref RETRY_MAX_ATTEMPTS: usize = CONFIG.max_attempts.unwrap_or(4).max(1);
+    pub static ref RETRY_BASE_DELAY: Duration = Duration::from_millis(200);
+    pub static ref RETRY_MAX_DELAY: Duration = Duration::from_secs(5);
+    /// tail mode fetches at every tick at most this times slower when throttled
+    pub static ref TAIL_MAX_SLOWDOWN: u32 = 32;
+    /// the live tail session is closed by the server after 3 hours, it is restarted before that
+    pub static ref LIVE_TAIL_SESSION_LIMIT: Duration = Duration::from_secs(3 * 60 * 60 - 60);
+    pub static ref INSIGHTS_POLL_RATE: Duration = Duration::from_secs(1);
+    /// the Logs Insights query is stopped if it does not finish in this time
+    pub static ref INSIGHTS_TIMEOUT: Duration = Duration::from_secs(15 * 60);
+    pub static ref LOGEVENT_STEP: usize = CONFIG.logevent_step.unwrap_or(5);
+    pub static ref CONTEXT_SIZE: usize = CONFIG.context_size.unwrap_or(20);
+    pub static ref STREAM_COLUMN: StreamColumn = CONFIG
+        .stream_column
+        .as_deref()
+        .and_then(|name| StreamColumn::from_name(name).ok())
+        .unwrap_or(StreamColumn::Hidden);
+    pub static ref JSON_FIELDS: Vec<String> = CONFIG.json_fields.clone();
+    pub static ref DATE_FORMAT: String = CONFIG
+        .date_format
+        .clone()
+        .unwrap_or_else(|| String::from("%Y-%m-%d %H:%M:%S"));
     pub static ref MORE_LOG_GROUP_NAME: String = String::from("More...");
     pub static ref MORE_LOG_GROUP_ARN: String = String::from("more");
     pub static ref MORE_LOG_EVENT_ID: String = String::from("999");
-    pub static ref DESELECTED_COLOR: Color = Color::White;
-    pub static ref SELECTED_COLOR: Color = Color::Yellow;
+    pub static ref DESELECTED_COLOR: Color = CONFIG
+        .colors
+        .deselected
+        .as_ref()
+        .and_then(|c| config::parse_color(c).ok())
+        .unwrap_or(Color::White);
+    pub static ref SELECTED_COLOR: Color = CONFIG
+        .colors
+        .selected
+        .as_ref()
+        .and_then(|c| config::parse_color(c).ok())
+        .unwrap_or(Color::Yellow);
     pub static ref NORMAL_STYLE: Style = Style::default().fg(*DESELECTED_COLOR);
     pub static ref ACTIVE_STYLE: Style = Style::default().fg(*SELECTED_COLOR);
-    pub static ref MAX_LOG_GROUP_SELECTION: usize = 4;
-    pub static ref HELP_INSTRUCTION: String = String::from("'?' to help");
-    pub static ref LOADER: String = String::from("⣾⣽⣻⢿⡿⣟⣯⣷");
-    pub static ref HELP_MESSAGE: String = String::from(
-        r#"
-<Global>
-  [TAB]   - Close/open side menu
-  [Arrow] - Move focus
-  [?]     - Show help
-  [Q]     - Close help
-  [C+Ctrl] - Exit
-
-<Side Menu>
-  [Up/Down] - Move cursor
-  [Enter] - Select log group
-  [WORD] - Incremental filtering (add)
-  [BackSpace] - Incremental filtering (remove)
-
-<Log Event>
-  [Enter] - Copy the selected log event to clipboard
-  [J/K] - Move cursor
-  [J+Shift/K+Shift] - Move cursor by 5
-  [TAB] - Close/open current log event
-  [S+Ctrl] - Open search dialog
-  [G] - Go to the first log event
-  [G+Shift] - Go to the last log event
-
-<Search Dialog>
-  [Esc] - Cancel search dialog
-  [Enter] - Confirm search dialog and start to search with the new conditions
-  [Up/Down] - Move cursor
-  [Space] - Select the period
-  [TAB] - Toggle period input focus
-
-<Text Box>
-  [WORD] - Input text
-  [BackSpace] - Delete text
-  [Left/Right] - Move cursor
-    "#
+    /// matched parts of the local search
+    pub static ref MATCH_STYLE: Style = Style::default().fg(Color::Black).bg(Color::Yellow);
+    /// failures of fetching the log events
+    pub static ref ERROR_STYLE: Style = Style::default().fg(Color::Red);
+    pub static ref MAX_LOG_GROUP_SELECTION: usize = CONFIG.max_panes.unwrap_or(9).max(1);
+    pub static ref SEARCH_HISTORY_SIZE: usize = 50;
+    pub static ref HELP_INSTRUCTION: String = format!(
+        "'{}' to help",
+        KEYMAP
+            .keys(Action::ShowHelp)
+            .first()
+            .map(|key| KeyEventWrapper::new(*key).to_string())
+            .unwrap_or_default()
     );
+    pub static ref RETRY_INSTRUCTION: String = format!(
+        "'{}' to retry",
+        KEYMAP
+            .keys(Action::Retry)
+            .first()
+            .map(|key| KeyEventWrapper::new(*key).to_string())
+            .unwrap_or_default()
+    );
+    pub static ref LOADER: String = String::from("⣾⣽⣻⢿⡿⣟⣯⣷");
+    pub static ref KEYMAP: KeyMap = CONFIG.keymap().unwrap_or_default();
+    pub static ref HELP_MESSAGE: String = KEYMAP.help_message();
 }
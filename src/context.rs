@@ -0,0 +1,90 @@
+use rusoto_logs::FilteredLogEvent;
+
+/// Log events around the hit in its log stream, shown by the context view
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventContext {
+    pub hit: FilteredLogEvent,
+    pub events: Vec<FilteredLogEvent>,
+    pub is_fetching: bool,
+    pub error: Option<String>,
+}
+
+impl EventContext {
+    /// Start fetching the context of the hit
+    pub fn new(hit: FilteredLogEvent) -> Self {
+        EventContext {
+            hit,
+            events: vec![],
+            is_fetching: true,
+            error: None,
+        }
+    }
+
+    pub fn set_result(&mut self, result: anyhow::Result<Vec<FilteredLogEvent>>) {
+        match result {
+            Ok(events) => self.events = events,
+            Err(e) => self.error = Some(e.to_string()),
+        }
+        self.is_fetching = false;
+    }
+
+    /// Index of the hit in the events. The events by GetLogEvents have no event id,
+    /// so the hit is found by the timestamp and the message.
+    pub fn hit_index(&self) -> Option<usize> {
+        self.events
+            .iter()
+            .position(|e| e.timestamp == self.hit.timestamp && e.message == self.hit.message)
+    }
+}
+
+/// `size` events before the timestamp and the events from the timestamp, `size` after the first one.
+/// The events must be sorted by the timestamp.
+pub fn surrounding(
+    events: &[FilteredLogEvent],
+    timestamp: i64,
+    size: usize,
+) -> Vec<FilteredLogEvent> {
+    let pos = events.partition_point(|e| e.timestamp.unwrap_or_default() < timestamp);
+    events[pos.saturating_sub(size)..]
+        .iter()
+        .take(size.min(pos) + size + 1)
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helper::make_log_events;
+
+    #[test]
+    fn test_surrounding() {
+        // timestamps are 0, 1000, ..., 9000
+        let events = make_log_events(0, 9, 0);
+        assert_eq!(make_log_events(3, 7, 0), surrounding(&events, 5000, 2));
+        // the edges
+        assert_eq!(make_log_events(0, 3, 0), surrounding(&events, 1000, 2));
+        assert_eq!(make_log_events(7, 9, 0), surrounding(&events, 9000, 2));
+        // between the events
+        assert_eq!(make_log_events(5, 7, 0), surrounding(&events, 5500, 1));
+    }
+
+    #[test]
+    fn test_hit_index() {
+        let events = make_log_events(0, 4, 0);
+        let mut context = EventContext::new(events[2].clone());
+        assert_eq!(None, context.hit_index());
+        // the events without the event id
+        context.set_result(Ok(events
+            .into_iter()
+            .map(|e| FilteredLogEvent {
+                event_id: None,
+                ..e
+            })
+            .collect()));
+        assert!(!context.is_fetching);
+        assert_eq!(Some(2), context.hit_index());
+        context.set_result(Err(anyhow::anyhow!("throttled")));
+        assert_eq!(Some(String::from("throttled")), context.error);
+    }
+}
@@ -1,3 +1,7 @@
+use std::path::PathBuf;
+
+use rusoto_logs::FilteredLogEvent;
+
 use crate::state::search_state::SearchState;
 
 pub enum LogGroupEvent {
@@ -9,6 +13,12 @@ pub enum LogGroupEvent {
 pub enum LogEventEvent {
     // log_group_name, next_token, search_conditions, need_reset
     FetchLogEvents(String, Option<String>, Option<SearchState>, bool),
+    // log_group_name
+    FetchLogStreams(String),
+    // log_group_name, the hit to fetch the events around
+    FetchContext(String, FilteredLogEvent),
+    // log_group_name, search_conditions, file path, fetch_all_pages
+    Export(String, Option<SearchState>, PathBuf, bool),
     Abort,
 }
 
@@ -25,4 +35,6 @@ pub enum TailLogEventEvent {
 pub enum Event<I> {
     Input(I),
     Tick,
+    // time to fetch the new log events in tail mode
+    TailTick,
 }
@@ -0,0 +1,327 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    fs::File,
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use anyhow::Result;
+use chrono::{DateTime, SecondsFormat, Utc};
+use rusoto_logs::FilteredLogEvent;
+use serde_json::json;
+
+use crate::{client::LogClient, state::search_state::SearchState};
+
+/// File format of the exported log events
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+    /// one JSON object per line, readable by the offline replay mode
+    JsonLines,
+    /// timestamp, stream, message
+    Csv,
+    /// "timestamp message" per line, same as the S3 export format
+    Text,
+}
+
+impl ExportFormat {
+    /// Decide the format by the extension of the file
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("jsonl") | Some("json") | Some("ndjson") => ExportFormat::JsonLines,
+            Some("csv") => ExportFormat::Csv,
+            _ => ExportFormat::Text,
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::JsonLines => "jsonl",
+            ExportFormat::Csv => "csv",
+            ExportFormat::Text => "log",
+        }
+    }
+}
+
+/// Progress of the export shown in the status bar
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExportProgress {
+    Running(PathBuf, usize),
+    Done(PathBuf, usize),
+    Failed(PathBuf, String),
+}
+
+impl Display for ExportProgress {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ExportProgress::Running(path, n) => {
+                write!(f, "exporting {} events to {} ...", n, path.display())
+            }
+            ExportProgress::Done(path, n) => {
+                write!(f, "exported {} events to {}", n, path.display())
+            }
+            ExportProgress::Failed(path, e) => {
+                write!(f, "failed to export to {}: {}", path.display(), e)
+            }
+        }
+    }
+}
+
+/// Writes log events to a file in the format
+pub struct Exporter<W>
+where
+    W: Write,
+{
+    writer: W,
+    format: ExportFormat,
+    written: usize,
+}
+
+impl Exporter<BufWriter<File>> {
+    /// Create the file. The format is decided by the extension.
+    pub fn create(path: &Path) -> Result<Self> {
+        let file = File::create(path)?;
+        Exporter::new(BufWriter::new(file), ExportFormat::from_path(path))
+    }
+}
+
+impl<W> Exporter<W>
+where
+    W: Write,
+{
+    pub fn new(mut writer: W, format: ExportFormat) -> Result<Self> {
+        if let ExportFormat::Csv = format {
+            writeln!(writer, "timestamp,stream,message")?;
+        }
+        Ok(Exporter {
+            writer,
+            format,
+            written: 0,
+        })
+    }
+
+    /// number of events written so far
+    pub fn written(&self) -> usize {
+        self.written
+    }
+
+    pub fn write_events(&mut self, events: &[FilteredLogEvent]) -> Result<()> {
+        for event in events {
+            match self.format {
+                ExportFormat::JsonLines => {
+                    let value = json!({
+                        "eventId": event.event_id,
+                        "ingestionTime": event.ingestion_time,
+                        "logStreamName": event.log_stream_name,
+                        "message": event.message,
+                        "timestamp": event.timestamp,
+                    });
+                    writeln!(self.writer, "{}", value)?;
+                }
+                ExportFormat::Csv => {
+                    writeln!(
+                        self.writer,
+                        "{},{},{}",
+                        format_timestamp(event.timestamp),
+                        escape_csv(event.log_stream_name.as_deref().unwrap_or_default()),
+                        escape_csv(event.message.as_deref().unwrap_or_default())
+                    )?;
+                }
+                ExportFormat::Text => {
+                    let message = event.message.as_deref().unwrap_or_default();
+                    match event.timestamp {
+                        Some(_) => writeln!(
+                            self.writer,
+                            "{} {}",
+                            format_timestamp(event.timestamp),
+                            message.trim_end_matches('\n')
+                        )?,
+                        None => writeln!(self.writer, "{}", message.trim_end_matches('\n'))?,
+                    }
+                }
+            }
+            self.written += 1;
+        }
+        Ok(())
+    }
+
+    /// Fetch log events page by page from the token and write them until the next token is exhausted,
+    /// or `on_progress` returns false to stop. `None` token starts from the first page.
+    pub async fn write_pages<F>(
+        &mut self,
+        client: &LogClient,
+        log_group_name: &str,
+        condition: &SearchState,
+        mut next_token: Option<String>,
+        mut on_progress: F,
+    ) -> Result<()>
+    where
+        F: FnMut(usize) -> bool,
+    {
+        loop {
+            let (events, token) = client
+                .fetch_logs(log_group_name, &next_token, condition)
+                .await?;
+            self.write_events(&events)?;
+            if !on_progress(self.written) || token.is_none() {
+                break;
+            }
+            next_token = token;
+        }
+        Ok(())
+    }
+
+    /// Flush the written events, e.g. to show them on stdout immediately
+    pub fn flush(&mut self) -> Result<()> {
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> Result<usize> {
+        self.writer.flush()?;
+        Ok(self.written)
+    }
+}
+
+/// Default file name of the export, e.g. `aws-lambda-function-20210101000000.jsonl`
+pub fn default_file_name(log_group_name: &str, format: ExportFormat) -> String {
+    let name = log_group_name.trim_start_matches('/').replace(
+        |c: char| !c.is_ascii_alphanumeric() && c != '-' && c != '_',
+        "-",
+    );
+    format!(
+        "{}-{}.{}",
+        name,
+        Utc::now().format("%Y%m%d%H%M%S"),
+        format.extension()
+    )
+}
+
+fn format_timestamp(timestamp: Option<i64>) -> String {
+    timestamp
+        .and_then(DateTime::from_timestamp_millis)
+        .map(|dt| dt.to_rfc3339_opts(SecondsFormat::Millis, true))
+        .unwrap_or_default()
+}
+
+fn escape_csv(value: &str) -> String {
+    if value.contains(&[',', '"', '\n', '\r'][..]) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        client::memory::MemoryLogSource, state::search_state::SearchMode,
+        test_helper::make_log_events,
+    };
+
+    fn export(format: ExportFormat, events: &[FilteredLogEvent]) -> String {
+        let mut exporter = Exporter::new(vec![], format).unwrap();
+        exporter.write_events(events).unwrap();
+        assert_eq!(events.len(), exporter.written());
+        String::from_utf8(exporter.writer).unwrap()
+    }
+
+    fn get_events() -> Vec<FilteredLogEvent> {
+        let mut events = make_log_events(1, 2, 1609459200000);
+        events[0].log_stream_name = Some(String::from("stream-1"));
+        events[1].message = Some(String::from("hello, \"world\"\n"));
+        events
+    }
+
+    #[test]
+    fn test_from_path() {
+        assert_eq!(
+            ExportFormat::JsonLines,
+            ExportFormat::from_path(Path::new("a.jsonl"))
+        );
+        assert_eq!(
+            ExportFormat::Csv,
+            ExportFormat::from_path(Path::new("a.csv"))
+        );
+        assert_eq!(
+            ExportFormat::Text,
+            ExportFormat::from_path(Path::new("a.log"))
+        );
+        assert_eq!(ExportFormat::Text, ExportFormat::from_path(Path::new("a")));
+    }
+
+    #[test]
+    fn test_write_json_lines() {
+        let result = export(ExportFormat::JsonLines, &get_events());
+        let lines = result.lines().collect::<Vec<&str>>();
+        assert_eq!(2, lines.len());
+        // readable as FilteredLogEvent again
+        let event: FilteredLogEvent = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(get_events()[0], event);
+    }
+
+    #[test]
+    fn test_write_csv() {
+        let result = export(ExportFormat::Csv, &get_events());
+        assert_eq!(
+            "timestamp,stream,message\n\
+             2021-01-01T00:00:01.000Z,stream-1,log_event_1\n\
+             2021-01-01T00:00:02.000Z,,\"hello, \"\"world\"\"\n\"\n",
+            result
+        );
+    }
+
+    #[test]
+    fn test_write_text() {
+        let result = export(ExportFormat::Text, &get_events());
+        assert_eq!(
+            "2021-01-01T00:00:01.000Z log_event_1\n\
+             2021-01-01T00:00:02.000Z hello, \"world\"\n",
+            result
+        );
+    }
+
+    #[tokio::test]
+    async fn test_write_pages() {
+        let client = LogClient::from_source(
+            MemoryLogSource::new().with_events("group", make_log_events(1, 3, 0)),
+        );
+        let mut exporter = Exporter::new(vec![], ExportFormat::Text).unwrap();
+        let mut progress = vec![];
+        exporter
+            .write_pages(
+                &client,
+                "group",
+                &SearchState::new(String::default(), SearchMode::FromTo(None, None)),
+                None,
+                |n| {
+                    progress.push(n);
+                    true
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(vec![3], progress);
+        assert_eq!(3, exporter.finish().unwrap());
+    }
+
+    #[test]
+    fn test_default_file_name() {
+        let name = default_file_name("/aws/lambda/func", ExportFormat::Csv);
+        assert!(name.starts_with("aws-lambda-func-"));
+        assert!(name.ends_with(".csv"));
+    }
+
+    #[test]
+    fn test_progress_fmt() {
+        let path = PathBuf::from("out.jsonl");
+        assert_eq!(
+            "exporting 10 events to out.jsonl ...",
+            ExportProgress::Running(path.clone(), 10).to_string()
+        );
+        assert_eq!(
+            "exported 10 events to out.jsonl",
+            ExportProgress::Done(path, 10).to_string()
+        );
+    }
+}
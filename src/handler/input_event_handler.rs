@@ -6,15 +6,11 @@ use crossterm::event::{self, Event as CEvent, KeyEvent};
 use tokio::sync::mpsc;
 
 use super::*;
-use crate::{
-    constant::TAIL_RATE,
-    event::{Event, TailLogEventEvent},
-};
+use crate::{constant::TAIL_RATE, event::Event};
 
 pub struct InputEventHandler {
     tick_rate: Duration,
     input_tx: mpsc::Sender<Event<KeyEvent>>,
-    tail_inst_txs: [mpsc::Sender<TailLogEventEvent>; 4],
     /// if true, run() will return Ok in short time.
     is_debug: bool,
 }
@@ -23,13 +19,11 @@ impl InputEventHandler {
     pub fn new(
         tick_rate: Duration,
         input_tx: mpsc::Sender<Event<KeyEvent>>,
-        tail_inst_txs: [mpsc::Sender<TailLogEventEvent>; 4],
         is_debug: bool,
     ) -> Self {
         InputEventHandler {
             tick_rate,
             input_tx,
-            tail_inst_txs,
             is_debug,
         }
     }
@@ -56,9 +50,7 @@ impl EventHandler for InputEventHandler {
                 last_tick = Instant::now();
             }
             if last_tail_tick.elapsed() >= tail_tick_rate {
-                for tx in self.tail_inst_txs.as_mut().iter_mut() {
-                    tx.send(TailLogEventEvent::Tick).await.unwrap();
-                }
+                self.input_tx.send(Event::TailTick).await?;
                 last_tail_tick = Instant::now();
             }
             if self.is_debug && start_time.elapsed() > end_for {
@@ -1,22 +1,64 @@
-use std::sync::{Arc, Mutex};
+use std::{
+    future::Future,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+};
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use async_trait::async_trait;
-use log::info;
+use log::{error, info, warn};
+use rusoto_logs::FilteredLogEvent;
 use tokio::sync::mpsc;
 
 use super::*;
 use crate::{
     client::LogClient,
+    constant::{CONTEXT_SIZE, INSIGHTS_POLL_RATE, INSIGHTS_TIMEOUT},
+    context::EventContext,
     event::{LogEventEvent, TailLogEventEvent},
-    state::{logevents_state::LogEventsState, search_state::SearchMode},
+    export::{ExportProgress, Exporter},
+    state::{
+        logevents_state::LogEventsState,
+        search_state::{SearchMode, SearchState},
+    },
 };
 
+/// Task of the pane running in the background, so that the handler keeps taking the instructions.
+/// Cancelled when it is dropped, e.g. replaced by the next task, and the task stops at its next step.
+struct BackgroundTask {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl BackgroundTask {
+    fn spawn<F, Fut>(f: F) -> Self
+    where
+        F: FnOnce(Arc<AtomicBool>) -> Fut,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let cancelled = Arc::new(AtomicBool::new(false));
+        tokio::spawn(f(Arc::clone(&cancelled)));
+        BackgroundTask { cancelled }
+    }
+}
+
+impl Drop for BackgroundTask {
+    fn drop(&mut self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+}
+
 pub struct LogEventEventHandler {
     client: LogClient,
     state: Arc<Mutex<LogEventsState>>,
     inst_rx: mpsc::Receiver<LogEventEvent>,
     tail_inst_tx: mpsc::Sender<TailLogEventEvent>,
+    /// the running Logs Insights query, cancelled by the next fetch
+    insights_query: Option<BackgroundTask>,
+    /// the running export, cancelled by the next export
+    export: Option<BackgroundTask>,
 }
 
 impl LogEventEventHandler {
@@ -31,63 +73,274 @@ impl LogEventEventHandler {
             state,
             inst_rx,
             tail_inst_tx,
+            insights_query: None,
+            export: None,
         }
     }
+
+    /// Start the Logs Insights query in the background
+    fn start_insights_query(&mut self, gname: String, condition: SearchState) {
+        self.state.lock().unwrap().reset();
+        self.state.lock().unwrap().is_fetching = true;
+        let client = self.client.clone();
+        let state = Arc::clone(&self.state);
+        self.insights_query = Some(BackgroundTask::spawn(|cancelled| {
+            run_insights_query(client, state, gname, condition, cancelled)
+        }));
+    }
+
+    /// Write the loaded log events to the file, and the rest of them by the next token if fetch_all
+    /// is true. Runs in the background reporting the progress, cancelled by the next export.
+    fn start_export(
+        &mut self,
+        gname: String,
+        condition: SearchState,
+        path: PathBuf,
+        fetch_all: bool,
+    ) {
+        let (events, rest) = {
+            let mut state = self.state.lock().unwrap();
+            state.export_progress = Some(ExportProgress::Running(path.clone(), 0));
+            // next token in tail mode is not for the condition
+            let rest = state
+                .next_token
+                .clone()
+                .filter(|_| fetch_all && condition.mode != SearchMode::Tail);
+            (state.events.items().clone(), rest)
+        };
+        let client = self.client.clone();
+        let state = Arc::clone(&self.state);
+        self.export = Some(BackgroundTask::spawn(move |cancelled| async move {
+            let result = async {
+                let mut exporter = Exporter::create(&path)?;
+                exporter.write_events(&events)?;
+                if rest.is_some() {
+                    exporter
+                        .write_pages(&client, &gname, &condition, rest, |n| {
+                            let mut state = state.lock().unwrap();
+                            if cancelled.load(Ordering::SeqCst) {
+                                return false;
+                            }
+                            state.export_progress = Some(ExportProgress::Running(path.clone(), n));
+                            true
+                        })
+                        .await?;
+                }
+                exporter.finish()
+            }
+            .await;
+            let mut state = state.lock().unwrap();
+            if cancelled.load(Ordering::SeqCst) {
+                return;
+            }
+            state.export_progress = Some(match result {
+                Ok(n) => ExportProgress::Done(path, n),
+                Err(e) => ExportProgress::Failed(path, e.to_string()),
+            });
+        }));
+    }
+
+    /// Fetch the events around the hit in its log stream.
+    /// The error is shown in the context view instead of stopping the handler.
+    async fn fetch_context(&mut self, gname: &str, hit: FilteredLogEvent) {
+        self.state.lock().unwrap().context = Some(EventContext::new(hit.clone()));
+        let result = match (&hit.log_stream_name, hit.timestamp) {
+            (Some(stream), Some(timestamp)) => {
+                self.client
+                    .fetch_context(gname, stream, timestamp, *CONTEXT_SIZE)
+                    .await
+            }
+            _ => Err(anyhow!("The log event has no log stream or timestamp")),
+        };
+        // the context view may be closed while fetching
+        if let Some(context) = self.state.lock().unwrap().context.as_mut() {
+            context.set_result(result);
+        }
+    }
+
+    async fn fetch_log_events(
+        &mut self,
+        gname: &str,
+        token: &Option<String>,
+        condition: &SearchState,
+        need_reset: bool,
+    ) -> Result<()> {
+        {
+            let mut state = self.state.lock().unwrap();
+            state.is_fetching = true;
+            state.error = None;
+            if need_reset {
+                info!("reset all state...");
+                state.reset();
+            }
+        }
+        let (mut fetched_log_events, next_token) =
+            self.client.fetch_logs(gname, token, condition).await?;
+        let mut state = self.state.lock().unwrap();
+        state.events.push_items(&mut fetched_log_events, false);
+        state.next_token = next_token;
+        state.is_fetching = false;
+        Ok(())
+    }
+
+    async fn fetch_log_streams(&mut self, gname: &str) -> Result<()> {
+        self.state.lock().unwrap().is_fetching_log_streams = true;
+        let log_streams = self.client.fetch_log_streams(gname).await?;
+        let mut state = self.state.lock().unwrap();
+        state.log_streams = log_streams
+            .into_iter()
+            .filter_map(|s| s.log_stream_name)
+            .collect();
+        state.is_fetching_log_streams = false;
+        Ok(())
+    }
+}
+
+/// Run the Logs Insights query and poll the results until the query finishes.
+/// Partial results are shown while the query is running.
+/// The query is stopped when it is cancelled or does not finish in INSIGHTS_TIMEOUT.
+async fn run_insights_query(
+    client: LogClient,
+    state: Arc<Mutex<LogEventsState>>,
+    gname: String,
+    condition: SearchState,
+    cancelled: Arc<AtomicBool>,
+) {
+    let result = async {
+        let query_id = client.start_query(&gname, &condition).await?;
+        let polled = tokio::time::timeout(
+            *INSIGHTS_TIMEOUT,
+            poll_query_results(&client, &state, &query_id, &cancelled),
+        )
+        .await;
+        let result = match polled {
+            Ok(Ok(true)) => return Ok(()),
+            Ok(Ok(false)) => Ok(()),
+            Ok(Err(e)) => return Err(e),
+            Err(_) => Err(anyhow!(
+                "The Logs Insights query did not finish in {} minutes",
+                INSIGHTS_TIMEOUT.as_secs() / 60
+            )),
+        };
+        if let Err(e) = client.stop_query(&query_id).await {
+            warn!("failed to stop the insights query: {:?}", e);
+        }
+        result
+    }
+    .await;
+    let mut state = state.lock().unwrap();
+    if cancelled.load(Ordering::SeqCst) {
+        return;
+    }
+    match result {
+        Ok(()) => state.is_fetching = false,
+        Err(e) => {
+            error!("failed to run the insights query: {:?}", e);
+            state.set_error(&e);
+        }
+    }
+}
+
+/// Show the results until the query is done (true) or cancelled (false)
+async fn poll_query_results(
+    client: &LogClient,
+    state: &Mutex<LogEventsState>,
+    query_id: &str,
+    cancelled: &AtomicBool,
+) -> Result<bool> {
+    loop {
+        let (status, results) = client.get_query_results(query_id).await?;
+        info!("insights query status: {:?}", status);
+        {
+            // checked under the lock, the handler sets it before resetting the state
+            let mut state = state.lock().unwrap();
+            if cancelled.load(Ordering::SeqCst) {
+                return Ok(false);
+            }
+            state.insights = Some(results);
+        }
+        if status.is_done() {
+            return Ok(true);
+        }
+        tokio::time::delay_for(*INSIGHTS_POLL_RATE).await;
+    }
 }
 
 #[async_trait]
 impl EventHandler for LogEventEventHandler {
     async fn run(&mut self) -> Result<()> {
         let mut is_tail = false;
-        loop {
-            if let Some(event) = self.inst_rx.recv().await {
-                match event {
-                    LogEventEvent::FetchLogEvents(gname, token, conditions, need_reset) => {
-                        info!("fetch log events - gname: {:?}, token: {:?}, conditions: {:?}, need_reset: {:?}", gname, token, conditions, need_reset);
-                        if let Some(condition) = conditions {
-                            if let SearchMode::Tail = condition.mode {
-                                is_tail = true;
+        while let Some(event) = self.inst_rx.recv().await {
+            match event {
+                LogEventEvent::FetchLogEvents(gname, token, conditions, need_reset) => {
+                    info!("fetch log events - gname: {:?}, token: {:?}, conditions: {:?}, need_reset: {:?}", gname, token, conditions, need_reset);
+                    self.insights_query = None;
+                    if let Some(condition) = conditions {
+                        if condition.is_insights() {
+                            if is_tail {
                                 self.tail_inst_tx
-                                    .send(TailLogEventEvent::Start(
-                                        gname,
-                                        token,
-                                        Some(condition),
-                                        need_reset,
-                                    ))
+                                    .send(TailLogEventEvent::Stop)
                                     .await
                                     .unwrap();
-                            } else {
-                                // TODO: try_lock()?
-                                // TODO: error handling
-                                if is_tail {
-                                    info!("sending TailLogEventEvent::Stop...");
-                                    self.tail_inst_tx
-                                        .send(TailLogEventEvent::Stop)
-                                        .await
-                                        .unwrap();
-                                    is_tail = false;
-                                }
-                                self.state.lock().unwrap().is_fetching = true;
-                                if need_reset {
-                                    info!("reset all state...");
-                                    self.state.lock().unwrap().reset();
-                                }
-                                let (mut fetched_log_events, next_token) =
-                                    self.client.fetch_logs(&gname, &token, &condition).await?;
-                                self.state
-                                    .lock()
-                                    .unwrap()
-                                    .events
-                                    .push_items(&mut fetched_log_events, false);
-                                self.state.lock().unwrap().next_token = next_token;
-                                self.state.lock().unwrap().is_fetching = false;
+                                is_tail = false;
+                            }
+                            self.start_insights_query(gname, condition);
+                        } else if let SearchMode::Tail = condition.mode {
+                            is_tail = true;
+                            self.tail_inst_tx
+                                .send(TailLogEventEvent::Start(
+                                    gname,
+                                    token,
+                                    Some(condition),
+                                    need_reset,
+                                ))
+                                .await
+                                .unwrap();
+                        } else {
+                            if is_tail {
+                                info!("sending TailLogEventEvent::Stop...");
+                                self.tail_inst_tx
+                                    .send(TailLogEventEvent::Stop)
+                                    .await
+                                    .unwrap();
+                                is_tail = false;
+                            }
+                            if let Err(e) = self
+                                .fetch_log_events(&gname, &token, &condition, need_reset)
+                                .await
+                            {
+                                error!("failed to fetch log events: {:?}", e);
+                                self.state.lock().unwrap().set_error(&e);
                             }
                         }
                     }
-                    LogEventEvent::Abort => {
-                        break;
+                }
+                LogEventEvent::FetchLogStreams(gname) => {
+                    info!("fetch log streams - gname: {:?}", gname);
+                    if let Err(e) = self.fetch_log_streams(&gname).await {
+                        error!("failed to fetch log streams: {:?}", e);
+                        self.state.lock().unwrap().set_error(&e);
                     }
                 }
+                LogEventEvent::FetchContext(gname, hit) => {
+                    info!(
+                        "fetch context - gname: {:?}, stream: {:?}, timestamp: {:?}",
+                        gname, hit.log_stream_name, hit.timestamp
+                    );
+                    self.fetch_context(&gname, hit).await;
+                }
+                LogEventEvent::Export(gname, conditions, path, fetch_all) => {
+                    info!(
+                        "export log events - gname: {:?}, path: {:?}, fetch_all: {:?}",
+                        gname, path, fetch_all
+                    );
+                    self.start_export(gname, conditions.unwrap_or_default(), path, fetch_all);
+                }
+                LogEventEvent::Abort => {
+                    self.insights_query = None;
+                    self.export = None;
+                    break;
+                }
             }
         }
         Ok(())
@@ -36,16 +36,21 @@ impl EventHandler for LogGroupEventHandler {
             if let Some(event) = self.inst_rx.recv().await {
                 match event {
                     LogGroupEvent::FetchLogGroups => {
-                        // TODO: try_lock()?
-                        // TODO: error handling
-                        self.state.lock().unwrap().is_fetching = true;
-                        let mut fetched_log_groups = self.client.fetch_log_groups().await?;
-                        self.state
-                            .lock()
-                            .unwrap()
-                            .log_groups
-                            .push_items(&mut fetched_log_groups, false);
-                        self.state.lock().unwrap().is_fetching = false;
+                        {
+                            let mut state = self.state.lock().unwrap();
+                            state.is_fetching = true;
+                            state.error = None;
+                        }
+                        let result = self.client.fetch_log_groups().await;
+                        let mut state = self.state.lock().unwrap();
+                        match result {
+                            Ok(mut fetched_log_groups) => {
+                                state.log_groups.push_items(&mut fetched_log_groups, false)
+                            }
+                            // the side menu stays empty, the log groups can be fetched again
+                            Err(e) => state.error = Some(e.to_string()),
+                        }
+                        state.is_fetching = false;
                     }
                     _ => {
                         break;
@@ -2,12 +2,15 @@ use std::io::Stdout;
 
 use anyhow::Result;
 use async_trait::async_trait;
-use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use crossterm::event::KeyEvent;
 use tokio::sync::mpsc;
 use tui::{backend::CrosstermBackend, Terminal};
 
 use super::*;
-use crate::{app::App, event::Event, terminal::teardown_terminal, ui::Drawable};
+use crate::{
+    app::App, constant::KEYMAP, event::Event, keymap::Action, terminal::teardown_terminal,
+    ui::Drawable,
+};
 
 pub struct MainEventHandler {
     terminal: Terminal<CrosstermBackend<Stdout>>,
@@ -49,20 +52,16 @@ impl EventHandler for MainEventHandler {
             // update app state
             if let Some(event) = self.input_rx.recv().await {
                 match event {
-                    Event::Input(event) => match event.code {
-                        KeyCode::Char('c') => {
-                            if let KeyModifiers::CONTROL = event.modifiers {
-                                teardown_terminal(&mut self.terminal)?;
-                                break;
-                            } else {
-                                middle.app.handle_event(event).await;
-                            }
-                        }
-                        _ => {
+                    Event::Input(event) => {
+                        if KEYMAP.is(Action::Exit, &event) {
+                            teardown_terminal(&mut self.terminal)?;
+                            break;
+                        } else {
                             middle.app.handle_event(event).await;
                         }
-                    },
+                    }
                     Event::Tick => {}
+                    Event::TailTick => middle.app.tail_tick(),
                 }
             }
         }
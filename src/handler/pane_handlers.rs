@@ -0,0 +1,144 @@
+use std::sync::{Arc, Mutex};
+
+use log::error;
+use tokio::sync::mpsc;
+
+use super::*;
+use crate::{
+    client::{merged::EventSources, LogClient},
+    event::{LogEventEvent, TailLogEventEvent},
+    handler::{
+        logevent_event_handler::LogEventEventHandler,
+        tail_logevent_event_handler::TailLogEventEventHandler,
+    },
+    state::logevents_state::LogEventsState,
+};
+
+/// The pair of handlers fetching the log events of an event area.
+/// Spawned when the log group is opened, and stopped when it is closed.
+pub struct PaneHandlers {
+    state: Arc<Mutex<LogEventsState>>,
+    logevent_inst_tx: mpsc::Sender<LogEventEvent>,
+    tail_inst_tx: mpsc::Sender<TailLogEventEvent>,
+}
+
+impl PaneHandlers {
+    pub fn spawn(client: LogClient) -> Self {
+        Self::spawn_with_state(client, LogEventsState::default())
+    }
+
+    /// Handlers of the merged timeline, fetching the log groups at the same time
+    pub fn spawn_merged(
+        client: LogClient,
+        log_group_names: Vec<String>,
+        sources: EventSources,
+    ) -> Self {
+        Self::spawn_with_state(
+            client.merged(log_group_names, sources),
+            LogEventsState::merged(),
+        )
+    }
+
+    fn spawn_with_state(client: LogClient, state: LogEventsState) -> Self {
+        let state = Arc::new(Mutex::new(state));
+        let (logevent_inst_tx, logevent_inst_rx) = mpsc::channel(1);
+        let (tail_inst_tx, tail_inst_rx) = mpsc::channel(1);
+        let mut logevent_event_handler = LogEventEventHandler::new(
+            client.clone(),
+            Arc::clone(&state),
+            logevent_inst_rx,
+            mpsc::Sender::clone(&tail_inst_tx),
+        );
+        tokio::spawn(async move {
+            if let Err(e) = logevent_event_handler.run().await {
+                error!("log event handler stopped: {:?}", e);
+            }
+        });
+        let mut tail_logevent_event_handler =
+            TailLogEventEventHandler::new(client, Arc::clone(&state), tail_inst_rx);
+        tokio::spawn(async move {
+            if let Err(e) = tail_logevent_event_handler.run().await {
+                error!("tail log event handler stopped: {:?}", e);
+            }
+        });
+        PaneHandlers {
+            state,
+            logevent_inst_tx,
+            tail_inst_tx,
+        }
+    }
+
+    pub fn state(&self) -> Arc<Mutex<LogEventsState>> {
+        Arc::clone(&self.state)
+    }
+
+    pub fn logevent_inst_tx(&self) -> mpsc::Sender<LogEventEvent> {
+        mpsc::Sender::clone(&self.logevent_inst_tx)
+    }
+
+    /// Let the tail handler fetch the new log events.
+    /// Skipped if the previous tick is not handled yet, so that the caller is never blocked.
+    pub fn tick(&mut self) {
+        let _ = self.tail_inst_tx.try_send(TailLogEventEvent::Tick);
+    }
+
+    /// Stop the handlers after the running fetch.
+    pub fn close(self) {
+        let PaneHandlers {
+            mut logevent_inst_tx,
+            mut tail_inst_tx,
+            ..
+        } = self;
+        tokio::spawn(async move {
+            let _ = logevent_inst_tx.send(LogEventEvent::Abort).await;
+            let _ = tail_inst_tx.send(TailLogEventEvent::Abort).await;
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        client::memory::MemoryLogSource, state::search_state::SearchState,
+        test_helper::make_log_events,
+    };
+
+    #[tokio::test]
+    async fn test_spawn_and_close() {
+        let client = LogClient::from_source(
+            MemoryLogSource::new().with_events("group", make_log_events(1, 3, 0)),
+        );
+        let pane = PaneHandlers::spawn(client);
+        pane.logevent_inst_tx()
+            .send(LogEventEvent::FetchLogEvents(
+                String::from("group"),
+                None,
+                Some(SearchState::new(
+                    String::default(),
+                    crate::state::search_state::SearchMode::FromTo(None, None),
+                )),
+                true,
+            ))
+            .await
+            .unwrap();
+        let state = pane.state();
+        for _ in 0..50 {
+            if state.lock().unwrap().events.items().len() == 3 {
+                break;
+            }
+            tokio::time::delay_for(std::time::Duration::from_millis(10)).await;
+        }
+        assert_eq!(3, state.lock().unwrap().events.items().len());
+        let mut tx = pane.logevent_inst_tx();
+        pane.close();
+        // the handler stops and the channel is closed
+        for _ in 0..50 {
+            if tx.send(LogEventEvent::Abort).await.is_err() {
+                return;
+            }
+            tokio::time::delay_for(std::time::Duration::from_millis(10)).await;
+        }
+        panic!("the handler is still running");
+    }
+}
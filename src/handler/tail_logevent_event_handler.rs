@@ -1,23 +1,59 @@
-use std::sync::{Arc, Mutex};
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
 
 use anyhow::Result;
 use async_trait::async_trait;
-use log::info;
+use log::{info, warn};
+use rusoto_logs::FilteredLogEvent;
 use tokio::sync::mpsc;
 
 use super::*;
 use crate::{
-    client::LogClient,
+    client::{
+        live_tail::{self, LiveTailSession},
+        retry::{self, ErrorKind, RetryPolicy},
+        LogClient,
+    },
+    constant::{LIVE_TAIL_SESSION_LIMIT, TAIL_MAX_SLOWDOWN, TAIL_OVERLAP, TAIL_RATE},
     event::TailLogEventEvent,
     state::{logevents_state::LogEventsState, search_state::SearchState},
+    tail_cursor::TailCursor,
 };
 
+/// The live tail session and when it started
+struct LiveTail {
+    session: Box<dyn LiveTailSession>,
+    started_at: Instant,
+}
+
+enum Incoming {
+    Inst(Option<TailLogEventEvent>),
+    Live(Result<Option<Vec<FilteredLogEvent>>>),
+}
+
+/// Tail mode fed by the live tail session of the log source, or by polling when the source does
+/// not support it or the session is reconnecting.
+/// The polling from the high-water mark also fills the gap before the session starts.
 pub struct TailLogEventEventHandler {
     client: LogClient,
     state: Arc<Mutex<LogEventsState>>,
     inst_rx: mpsc::Receiver<TailLogEventEvent>,
     tail_mode: bool,
     current_search_condition: SearchState,
+    cursor: TailCursor,
+    /// fetch at every this number of ticks, doubled when throttled and halved after success
+    slowdown: u32,
+    skipped_ticks: u32,
+    live: Option<LiveTail>,
+    /// the log source does not support the live tail, never started again
+    live_unsupported: bool,
+    /// failures of the live tail in a row, the session is started again after the backoff
+    live_failures: usize,
+    reconnect_at: Option<Instant>,
+    /// the session is restarted after this time
+    session_limit: Duration,
 }
 
 impl TailLogEventEventHandler {
@@ -32,49 +68,212 @@ impl TailLogEventEventHandler {
             inst_rx,
             tail_mode: false,
             current_search_condition: SearchState::default(),
+            cursor: TailCursor::new(*TAIL_OVERLAP),
+            slowdown: 1,
+            skipped_ticks: 0,
+            live: None,
+            live_unsupported: false,
+            live_failures: 0,
+            reconnect_at: None,
+            session_limit: *LIVE_TAIL_SESSION_LIMIT,
+        }
+    }
+
+    /// Restart the live tail session after the limit instead of the 3 hours of CloudWatch Logs
+    pub fn with_session_limit(mut self, limit: Duration) -> Self {
+        self.session_limit = limit;
+        self
+    }
+
+    /// Fetch a page from the high-water mark, or the next page of the current query
+    async fn tail(&mut self) -> Result<()> {
+        let gname = self.state.lock().unwrap().current_log_group.clone();
+        let (condition, token) = self.cursor.query(&self.current_search_condition);
+        let (fetched_log_events, next_token) = self
+            .client
+            .tail_logs(&gname.unwrap_or_default(), &token, &condition)
+            .await?;
+        let mut fetched_log_events =
+            self.cursor
+                .advance(&condition, fetched_log_events, next_token);
+        let mut state = self.state.lock().unwrap();
+        state.events.push_items(&mut fetched_log_events, true);
+        state.cursor_last();
+        state.is_fetching = false;
+        state.error = None;
+        Ok(())
+    }
+
+    async fn start_live_tail(&mut self) -> Result<LiveTail> {
+        let gname = self.state.lock().unwrap().current_log_group.clone();
+        let session = self
+            .client
+            .start_live_tail(&gname.unwrap_or_default(), &self.current_search_condition)
+            .await?;
+        Ok(LiveTail {
+            session,
+            started_at: Instant::now(),
+        })
+    }
+
+    /// Start the live tail session unless the source does not support it or the reconnect is
+    /// not due yet
+    async fn connect_live_tail(&mut self) {
+        if self.live_unsupported || matches!(self.reconnect_at, Some(t) if Instant::now() < t) {
+            return;
+        }
+        match self.start_live_tail().await {
+            Ok(live) => {
+                info!("live tail session started");
+                self.live = Some(live);
+                self.live_failures = 0;
+                self.reconnect_at = None;
+                self.state.lock().unwrap().live_tail = true;
+            }
+            Err(e) if live_tail::is_unsupported(&e) => {
+                info!("{}, polling instead", e);
+                self.live_unsupported = true;
+            }
+            Err(e) => self.live_tail_failed(&e),
         }
     }
+
+    /// Poll until the session is started again after the backoff
+    fn live_tail_failed(&mut self, e: &anyhow::Error) {
+        warn!("live tail failed: {:?}", e);
+        self.live = None;
+        self.reconnect_at =
+            Some(Instant::now() + RetryPolicy::default().max_backoff(self.live_failures));
+        self.live_failures += 1;
+        self.state.lock().unwrap().live_tail = false;
+    }
+
+    /// Start the new session before the server closes the current one at its time limit.
+    /// The current one is kept if the new one fails to start. True if refreshed.
+    async fn refresh_live_tail(&mut self) -> bool {
+        if !matches!(&self.live, Some(live) if live.started_at.elapsed() >= self.session_limit) {
+            return false;
+        }
+        match self.start_live_tail().await {
+            Ok(live) => {
+                info!("live tail session refreshed");
+                self.live = Some(live);
+                true
+            }
+            Err(e) => {
+                warn!("failed to refresh the live tail session: {:?}", e);
+                false
+            }
+        }
+    }
+
+    fn receive_live_tail(&mut self, batch: Result<Option<Vec<FilteredLogEvent>>>) {
+        match batch {
+            Ok(Some(events)) => {
+                let mut events = self.cursor.skip_seen(events);
+                let mut state = self.state.lock().unwrap();
+                state.events.push_items(&mut events, true);
+                state.cursor_last();
+                state.error = None;
+            }
+            // closed by the server, started again by the next tick after polling the gap
+            Ok(None) => {
+                info!("live tail session closed");
+                self.live = None;
+                self.state.lock().unwrap().live_tail = false;
+            }
+            Err(e) => self.live_tail_failed(&e),
+        }
+    }
+
+    /// Stop the live tail and poll again from the next start
+    fn stop_live_tail(&mut self) {
+        self.live = None;
+        self.live_failures = 0;
+        self.reconnect_at = None;
+        self.state.lock().unwrap().live_tail = false;
+    }
 }
 
 #[async_trait]
 impl EventHandler for TailLogEventEventHandler {
     async fn run(&mut self) -> Result<()> {
-        while let Some(event) = self.inst_rx.recv().await {
+        loop {
+            // the session call is cancel safe, dropped when the instruction comes first
+            let incoming = match self.live.as_mut() {
+                Some(live) => tokio::select! {
+                    event = self.inst_rx.recv() => Incoming::Inst(event),
+                    batch = live.session.next() => Incoming::Live(batch),
+                },
+                None => Incoming::Inst(self.inst_rx.recv().await),
+            };
+            let event = match incoming {
+                Incoming::Inst(Some(event)) => event,
+                Incoming::Inst(None) => break,
+                Incoming::Live(batch) => {
+                    self.receive_live_tail(batch);
+                    continue;
+                }
+            };
             match event {
-                TailLogEventEvent::Start(gname, token, conditions, _need_reset) => {
+                // the token is not used, tail mode starts from the time range of the conditions
+                TailLogEventEvent::Start(gname, _token, conditions, _need_reset) => {
                     info!("Tail mode start to fetch");
                     if let Some(search_state) = conditions {
                         self.current_search_condition = search_state.clone();
                     }
-                    self.state.lock().unwrap().next_token = token.clone();
                     self.state.lock().unwrap().current_log_group = Some(gname.clone());
                     self.state.lock().unwrap().reset();
+                    self.cursor = TailCursor::new(*TAIL_OVERLAP);
+                    self.stop_live_tail();
                     self.tail_mode = true;
+                    self.slowdown = 1;
+                    self.skipped_ticks = 0;
+                    self.state.lock().unwrap().tail_interval = Some(*TAIL_RATE);
                 }
                 TailLogEventEvent::Stop => {
                     info!("Tail mode stop fetching");
                     self.tail_mode = false;
+                    self.stop_live_tail();
                     self.state.lock().unwrap().reset();
+                    self.state.lock().unwrap().tail_interval = None;
                 }
                 TailLogEventEvent::Tick => {
-                    if self.tail_mode && !self.state.lock().unwrap().is_fetching {
-                        // skip if fetching
-                        self.state.lock().unwrap().is_fetching = true;
-                        let gname = self.state.lock().unwrap().current_log_group.clone();
-                        let token = self.state.lock().unwrap().next_token.clone();
-                        let (mut fetched_log_events, next_token) = self
-                            .client
-                            .fetch_logs(&gname.unwrap(), &token, &self.current_search_condition)
-                            .await?;
-                        self.state
-                            .lock()
-                            .unwrap()
-                            .events
-                            .push_items(&mut fetched_log_events, true);
-                        self.state.lock().unwrap().next_token = next_token;
-                        self.state.lock().unwrap().cursor_last();
-                        self.state.lock().unwrap().is_fetching = false;
+                    // skip if fetching
+                    if !self.tail_mode || self.state.lock().unwrap().is_fetching {
+                        continue;
+                    }
+                    if self.live.is_some() {
+                        // no polling while the session is streaming, except for the events left
+                        // in the replaced session
+                        if !self.refresh_live_tail().await {
+                            continue;
+                        }
+                    } else {
+                        if self.skipped_ticks + 1 < self.slowdown {
+                            self.skipped_ticks += 1;
+                            continue;
+                        }
+                        self.skipped_ticks = 0;
+                        // started before the polling, which fetches the events before the session
+                        self.connect_live_tail().await;
+                    }
+                    self.state.lock().unwrap().is_fetching = true;
+                    match self.tail().await {
+                        Ok(()) => self.slowdown = (self.slowdown / 2).max(1),
+                        // not an error, the polling slows down
+                        Err(e) if retry::classify(&e) == ErrorKind::Throttled => {
+                            warn!("tail is throttled: {:?}", e);
+                            self.slowdown = (self.slowdown * 2).min(*TAIL_MAX_SLOWDOWN);
+                            self.state.lock().unwrap().is_fetching = false;
+                        }
+                        // the failed fetch is retried by the next tick
+                        Err(e) => {
+                            warn!("failed to tail log events: {:?}", e);
+                            self.state.lock().unwrap().set_error(&e);
+                        }
                     }
+                    self.state.lock().unwrap().tail_interval = Some(*TAIL_RATE * self.slowdown);
                 }
                 TailLogEventEvent::Abort => {
                     break;
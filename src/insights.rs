@@ -0,0 +1,121 @@
+/// Status of a Logs Insights query
+#[derive(Debug, Clone, PartialEq)]
+pub enum QueryStatus {
+    Scheduled,
+    Running,
+    Complete,
+    Failed,
+    Cancelled,
+    Timeout,
+    Unknown,
+}
+
+impl QueryStatus {
+    pub fn from_status(status: &str) -> Self {
+        match status {
+            "Scheduled" => QueryStatus::Scheduled,
+            "Running" => QueryStatus::Running,
+            "Complete" => QueryStatus::Complete,
+            "Failed" => QueryStatus::Failed,
+            "Cancelled" => QueryStatus::Cancelled,
+            "Timeout" => QueryStatus::Timeout,
+            _ => QueryStatus::Unknown,
+        }
+    }
+
+    /// true if polling the results is no longer needed
+    pub fn is_done(&self) -> bool {
+        !matches!(self, QueryStatus::Scheduled | QueryStatus::Running)
+    }
+}
+
+/// Results of a Logs Insights query as a table.
+/// Columns are determined by the fields in the results.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct InsightsResults {
+    columns: Vec<String>,
+    rows: Vec<Vec<String>>,
+}
+
+impl InsightsResults {
+    /// Build the table from the results, each of which is a list of (field, value).
+    /// The `@ptr` field is internal and not shown.
+    pub fn new(results: Vec<Vec<(String, String)>>) -> Self {
+        let mut columns: Vec<String> = vec![];
+        for result in results.iter() {
+            for (field, _) in result.iter() {
+                if field != "@ptr" && !columns.contains(field) {
+                    columns.push(field.clone());
+                }
+            }
+        }
+        let rows = results
+            .into_iter()
+            .map(|result| {
+                columns
+                    .iter()
+                    .map(|c| {
+                        result
+                            .iter()
+                            .find(|(field, _)| field == c)
+                            .map(|(_, value)| value.clone())
+                            .unwrap_or_default()
+                    })
+                    .collect()
+            })
+            .collect();
+        InsightsResults { columns, rows }
+    }
+
+    pub fn columns(&self) -> &Vec<String> {
+        &self.columns
+    }
+
+    pub fn rows(&self) -> &Vec<Vec<String>> {
+        &self.rows
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field(f: &str, v: &str) -> (String, String) {
+        (f.to_string(), v.to_string())
+    }
+
+    #[test]
+    fn test_new() {
+        let results = InsightsResults::new(vec![
+            vec![
+                field("bin(5m)", "2021-01-01 00:00:00"),
+                field("count()", "3"),
+            ],
+            vec![
+                field("bin(5m)", "2021-01-01 00:05:00"),
+                field("count()", "1"),
+                field("@ptr", "xxx"),
+            ],
+            vec![field("other", "value")],
+        ]);
+        assert_eq!(&vec!["bin(5m)", "count()", "other"], results.columns());
+        assert_eq!(
+            &vec![
+                vec!["2021-01-01 00:00:00", "3", ""],
+                vec!["2021-01-01 00:05:00", "1", ""],
+                vec!["", "", "value"],
+            ],
+            results.rows()
+        );
+    }
+
+    #[test]
+    fn test_status() {
+        assert_eq!(QueryStatus::Running, QueryStatus::from_status("Running"));
+        assert_eq!(QueryStatus::Unknown, QueryStatus::from_status("foo"));
+        assert!(!QueryStatus::Scheduled.is_done());
+        assert!(!QueryStatus::Running.is_done());
+        assert!(QueryStatus::Complete.is_done());
+        assert!(QueryStatus::Failed.is_done());
+    }
+}
@@ -0,0 +1,167 @@
+use serde_json::{Map, Value};
+use tui::{
+    style::{Color, Style},
+    text::{Span, Spans, Text},
+};
+
+const INDENT: &str = "  ";
+
+/// The message as a JSON object, None if it is not one
+pub fn parse(msg: &str) -> Option<Map<String, Value>> {
+    let msg = msg.trim();
+    if !msg.starts_with('{') {
+        return None;
+    }
+    match serde_json::from_str(msg) {
+        Ok(Value::Object(map)) => Some(map),
+        _ => None,
+    }
+}
+
+/// The value of the field as a cell. Nested fields are written as `http.status`.
+pub fn field(object: &Map<String, Value>, name: &str) -> Option<String> {
+    let mut names = name.split('.');
+    let mut value = object.get(names.next()?)?;
+    for name in names {
+        value = value.get(name)?;
+    }
+    Some(match value {
+        Value::String(s) => s.clone(),
+        value => value.to_string(),
+    })
+}
+
+/// Pretty-printed JSON coloured by the type of the values
+pub fn highlight<'a>(object: &Map<String, Value>) -> Text<'a> {
+    let mut lines = vec![];
+    let mut line = vec![];
+    push_object(object, 0, &mut line, &mut lines);
+    lines.push(Spans::from(line));
+    Text::from(lines)
+}
+
+fn push_object<'a>(
+    object: &Map<String, Value>,
+    depth: usize,
+    line: &mut Vec<Span<'a>>,
+    lines: &mut Vec<Spans<'a>>,
+) {
+    if object.is_empty() {
+        line.push(Span::raw("{}"));
+        return;
+    }
+    line.push(Span::raw("{"));
+    for (i, (key, value)) in object.iter().enumerate() {
+        lines.push(Spans::from(std::mem::take(line)));
+        line.push(Span::raw(INDENT.repeat(depth + 1)));
+        line.push(Span::styled(
+            Value::String(key.clone()).to_string(),
+            Style::default().fg(Color::Cyan),
+        ));
+        line.push(Span::raw(": "));
+        push_value(value, depth + 1, line, lines);
+        if i + 1 < object.len() {
+            line.push(Span::raw(","));
+        }
+    }
+    lines.push(Spans::from(std::mem::take(line)));
+    line.push(Span::raw(format!("{}}}", INDENT.repeat(depth))));
+}
+
+fn push_value<'a>(
+    value: &Value,
+    depth: usize,
+    line: &mut Vec<Span<'a>>,
+    lines: &mut Vec<Spans<'a>>,
+) {
+    match value {
+        Value::Object(object) => push_object(object, depth, line, lines),
+        Value::Array(array) if array.is_empty() => line.push(Span::raw("[]")),
+        Value::Array(array) => {
+            line.push(Span::raw("["));
+            for (i, value) in array.iter().enumerate() {
+                lines.push(Spans::from(std::mem::take(line)));
+                line.push(Span::raw(INDENT.repeat(depth + 1)));
+                push_value(value, depth + 1, line, lines);
+                if i + 1 < array.len() {
+                    line.push(Span::raw(","));
+                }
+            }
+            lines.push(Spans::from(std::mem::take(line)));
+            line.push(Span::raw(format!("{}]", INDENT.repeat(depth))));
+        }
+        Value::String(_) => line.push(Span::styled(
+            value.to_string(),
+            Style::default().fg(Color::Green),
+        )),
+        Value::Number(_) => line.push(Span::styled(
+            value.to_string(),
+            Style::default().fg(Color::Yellow),
+        )),
+        Value::Bool(_) | Value::Null => line.push(Span::styled(
+            value.to_string(),
+            Style::default().fg(Color::Magenta),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert!(parse(r#" {"level": "INFO"} "#).is_some());
+        assert!(parse("[1, 2]").is_none());
+        assert!(parse("{ not json").is_none());
+        assert!(parse("INFO done").is_none());
+    }
+
+    #[test]
+    fn test_field() {
+        let object = parse(r#"{"level": "INFO", "http": {"status": 200}, "tags": ["a"]}"#).unwrap();
+        assert_eq!(Some(String::from("INFO")), field(&object, "level"));
+        assert_eq!(Some(String::from("200")), field(&object, "http.status"));
+        assert_eq!(Some(String::from(r#"["a"]"#)), field(&object, "tags"));
+        assert_eq!(None, field(&object, "http.method"));
+        assert_eq!(None, field(&object, "msg"));
+    }
+
+    #[test]
+    fn test_highlight() {
+        let object = parse(r#"{"a": 1, "b": {"c": [true, null]}, "d": "x", "e": {}}"#).unwrap();
+        let text = highlight(&object);
+        let lines = text
+            .lines
+            .iter()
+            .map(|spans| {
+                spans
+                    .0
+                    .iter()
+                    .map(|span| span.content.to_string())
+                    .collect::<String>()
+            })
+            .collect::<Vec<String>>();
+        assert_eq!(
+            vec![
+                "{",
+                r#"  "a": 1,"#,
+                r#"  "b": {"#,
+                r#"    "c": ["#,
+                "      true,",
+                "      null",
+                "    ]",
+                "  },",
+                r#"  "d": "x","#,
+                r#"  "e": {}"#,
+                "}",
+            ],
+            lines
+        );
+        // coloured by the type
+        let a = &text.lines[1].0;
+        assert_eq!(Some(Color::Cyan), a[1].style.fg);
+        assert_eq!(Some(Color::Yellow), a[3].style.fg);
+        assert_eq!(Some(Color::Green), text.lines[8].0[3].style.fg);
+    }
+}
@@ -89,17 +89,32 @@ impl ToString for KeyEventWrapper {
             KeyCode::Tab => Some("TAB".to_string()),
             KeyCode::Enter => Some("ENTER".to_string()),
             KeyCode::Esc => Some("Esc".to_string()),
+            KeyCode::BackTab => Some("BackTab".to_string()),
+            KeyCode::Delete => Some("Del".to_string()),
+            KeyCode::Insert => Some("Ins".to_string()),
+            KeyCode::Home => Some("Home".to_string()),
+            KeyCode::End => Some("End".to_string()),
+            KeyCode::PageUp => Some("PgUp".to_string()),
+            KeyCode::PageDown => Some("PgDn".to_string()),
+            KeyCode::F(n) => Some(format!("F{}", n)),
             _ => None,
         };
-        let modifier = match self.inner.modifiers {
-            KeyModifiers::CONTROL => Some("Ctrl".to_string()),
-            _ => None,
-        };
+        let mut modifiers = vec![];
+        match self.inner.code {
+            KeyCode::Char(c) if c.is_uppercase() => modifiers.push("Shift"),
+            _ => {}
+        }
+        if self.inner.modifiers.contains(KeyModifiers::CONTROL) {
+            modifiers.push("Ctrl");
+        }
+        if self.inner.modifiers.contains(KeyModifiers::ALT) {
+            modifiers.push("Alt");
+        }
         format!(
             "{}{}{}",
             code.unwrap_or_default(),
-            if modifier.is_some() { "+" } else { "" },
-            modifier.unwrap_or_default(),
+            if modifiers.is_empty() { "" } else { "+" },
+            modifiers.join("+"),
         )
     }
 }
@@ -134,6 +149,15 @@ mod tests {
             "BackSpace",
         );
         test_case_to_string(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE), "Esc");
+        test_case_to_string(
+            KeyEvent::new(KeyCode::Char('G'), KeyModifiers::SHIFT),
+            "G+Shift",
+        );
+        test_case_to_string(
+            KeyEvent::new(KeyCode::Char('<'), KeyModifiers::ALT),
+            "<+Alt",
+        );
+        test_case_to_string(KeyEvent::new(KeyCode::PageDown, KeyModifiers::NONE), "PgDn");
         test_case_to_string(KeyEvent::new(KeyCode::Null, KeyModifiers::NONE), "");
     }
 
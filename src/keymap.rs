@@ -0,0 +1,745 @@
+use std::collections::BTreeMap;
+
+use anyhow::{anyhow, Result};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::key_event_wrapper::KeyEventWrapper;
+
+/// Where the action is looked up. The focused component handles the key before its parent,
+/// so the same key can be bound to actions of different contexts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Context {
+    Global,
+    SideMenu,
+    EventArea,
+    Dialog,
+}
+
+impl Context {
+    fn title(self) -> &'static str {
+        match self {
+            Context::Global => "Global",
+            Context::SideMenu => "Side Menu",
+            Context::EventArea => "Log Event",
+            Context::Dialog => "Dialog (search / log stream picker / export)",
+        }
+    }
+
+    /// Keys for typing, which cannot be rebound
+    fn fixed_keys(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Context::SideMenu => &[
+                ("WORD", "Incremental filtering (add)"),
+                ("BackSpace", "Incremental filtering (remove)"),
+            ],
+            Context::Dialog => &[
+                ("WORD", "Input text"),
+                ("BackSpace", "Delete text"),
+                ("Left/Right", "Move cursor in the text box"),
+            ],
+            _ => &[],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Action {
+    // Global
+    Exit,
+    ShowHelp,
+    CloseHelp,
+    ToggleSideMenu,
+    MoveLeft,
+    MoveRight,
+    MoveUp,
+    MoveDown,
+    NewTab,
+    CloseTab,
+    RenameTab,
+    NextTab,
+    PrevTab,
+    ToggleMergedView,
+    // Side menu
+    NextLogGroup,
+    PrevLogGroup,
+    SelectLogGroup,
+    // Event area
+    CopyLogEvent,
+    ToggleLogEvent,
+    NextLogEvent,
+    PrevLogEvent,
+    NextLogEventByStep,
+    PrevLogEventByStep,
+    FirstLogEvent,
+    LastLogEvent,
+    OpenSearchDialog,
+    OpenStreamPicker,
+    OpenExportDialog,
+    SwitchStreamColumn,
+    OpenLocalSearch,
+    NextMatch,
+    PrevMatch,
+    OpenFilterDialog,
+    ToggleNoiseFilter,
+    CycleMinLevel,
+    TogglePrettyJson,
+    EditJsonFields,
+    OpenEventDetail,
+    ShowContext,
+    Retry,
+    // Dialogs
+    Confirm,
+    Cancel,
+    NextItem,
+    PrevItem,
+    SelectItem,
+    ToggleItem,
+    DeleteItem,
+    ScrollDown,
+    ScrollUp,
+    ToggleQueryMode,
+    SaveSearch,
+    OpenSavedSearches,
+}
+
+/// In the order of the help message
+pub const ACTIONS: [Action; 52] = [
+    Action::Exit,
+    Action::ShowHelp,
+    Action::CloseHelp,
+    Action::ToggleSideMenu,
+    Action::MoveLeft,
+    Action::MoveRight,
+    Action::MoveUp,
+    Action::MoveDown,
+    Action::NewTab,
+    Action::CloseTab,
+    Action::RenameTab,
+    Action::NextTab,
+    Action::PrevTab,
+    Action::ToggleMergedView,
+    Action::NextLogGroup,
+    Action::PrevLogGroup,
+    Action::SelectLogGroup,
+    Action::CopyLogEvent,
+    Action::ToggleLogEvent,
+    Action::NextLogEvent,
+    Action::PrevLogEvent,
+    Action::NextLogEventByStep,
+    Action::PrevLogEventByStep,
+    Action::FirstLogEvent,
+    Action::LastLogEvent,
+    Action::OpenSearchDialog,
+    Action::OpenStreamPicker,
+    Action::OpenExportDialog,
+    Action::SwitchStreamColumn,
+    Action::OpenLocalSearch,
+    Action::NextMatch,
+    Action::PrevMatch,
+    Action::OpenFilterDialog,
+    Action::ToggleNoiseFilter,
+    Action::CycleMinLevel,
+    Action::TogglePrettyJson,
+    Action::EditJsonFields,
+    Action::OpenEventDetail,
+    Action::ShowContext,
+    Action::Retry,
+    Action::Confirm,
+    Action::Cancel,
+    Action::NextItem,
+    Action::PrevItem,
+    Action::SelectItem,
+    Action::ToggleItem,
+    Action::DeleteItem,
+    Action::ScrollDown,
+    Action::ScrollUp,
+    Action::ToggleQueryMode,
+    Action::SaveSearch,
+    Action::OpenSavedSearches,
+];
+
+impl Action {
+    /// The key of `[keys]` in the config file
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::Exit => "exit",
+            Action::ShowHelp => "show_help",
+            Action::CloseHelp => "close_help",
+            Action::ToggleSideMenu => "toggle_side_menu",
+            Action::MoveLeft => "move_left",
+            Action::MoveRight => "move_right",
+            Action::MoveUp => "move_up",
+            Action::MoveDown => "move_down",
+            Action::NewTab => "new_tab",
+            Action::CloseTab => "close_tab",
+            Action::RenameTab => "rename_tab",
+            Action::NextTab => "next_tab",
+            Action::PrevTab => "prev_tab",
+            Action::ToggleMergedView => "toggle_merged_view",
+            Action::NextLogGroup => "next_log_group",
+            Action::PrevLogGroup => "prev_log_group",
+            Action::SelectLogGroup => "select_log_group",
+            Action::CopyLogEvent => "copy_log_event",
+            Action::ToggleLogEvent => "toggle_log_event",
+            Action::NextLogEvent => "next_log_event",
+            Action::PrevLogEvent => "prev_log_event",
+            Action::NextLogEventByStep => "next_log_event_by_step",
+            Action::PrevLogEventByStep => "prev_log_event_by_step",
+            Action::FirstLogEvent => "first_log_event",
+            Action::LastLogEvent => "last_log_event",
+            Action::OpenSearchDialog => "open_search_dialog",
+            Action::OpenStreamPicker => "open_stream_picker",
+            Action::OpenExportDialog => "open_export_dialog",
+            Action::SwitchStreamColumn => "switch_stream_column",
+            Action::OpenLocalSearch => "open_local_search",
+            Action::NextMatch => "next_match",
+            Action::PrevMatch => "prev_match",
+            Action::OpenFilterDialog => "open_filter_dialog",
+            Action::ToggleNoiseFilter => "toggle_noise_filter",
+            Action::CycleMinLevel => "cycle_min_level",
+            Action::TogglePrettyJson => "toggle_pretty_json",
+            Action::EditJsonFields => "edit_json_fields",
+            Action::OpenEventDetail => "open_event_detail",
+            Action::ShowContext => "show_context",
+            Action::Retry => "retry",
+            Action::Confirm => "confirm",
+            Action::Cancel => "cancel",
+            Action::NextItem => "next_item",
+            Action::PrevItem => "prev_item",
+            Action::SelectItem => "select_item",
+            Action::ToggleItem => "toggle_item",
+            Action::DeleteItem => "delete_item",
+            Action::ScrollDown => "scroll_down",
+            Action::ScrollUp => "scroll_up",
+            Action::ToggleQueryMode => "toggle_query_mode",
+            Action::SaveSearch => "save_search",
+            Action::OpenSavedSearches => "open_saved_searches",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Action> {
+        ACTIONS.iter().copied().find(|a| a.name() == name)
+    }
+
+    pub fn context(self) -> Context {
+        match self {
+            Action::Exit
+            | Action::ShowHelp
+            | Action::CloseHelp
+            | Action::ToggleSideMenu
+            | Action::MoveLeft
+            | Action::MoveRight
+            | Action::MoveUp
+            | Action::MoveDown
+            | Action::NewTab
+            | Action::CloseTab
+            | Action::RenameTab
+            | Action::NextTab
+            | Action::PrevTab
+            | Action::ToggleMergedView => Context::Global,
+            Action::NextLogGroup | Action::PrevLogGroup | Action::SelectLogGroup => {
+                Context::SideMenu
+            }
+            Action::Confirm
+            | Action::Cancel
+            | Action::NextItem
+            | Action::PrevItem
+            | Action::SelectItem
+            | Action::ToggleItem
+            | Action::DeleteItem
+            | Action::ScrollDown
+            | Action::ScrollUp
+            | Action::ToggleQueryMode
+            | Action::SaveSearch
+            | Action::OpenSavedSearches => Context::Dialog,
+            _ => Context::EventArea,
+        }
+    }
+
+    /// Shown in the help message
+    pub fn description(self) -> &'static str {
+        match self {
+            Action::Exit => "Exit",
+            Action::ShowHelp => "Show help",
+            Action::CloseHelp => "Close help",
+            Action::ToggleSideMenu => "Close/open side menu",
+            Action::MoveLeft => "Move focus left",
+            Action::MoveRight => "Move focus right",
+            Action::MoveUp => "Move focus up",
+            Action::MoveDown => "Move focus down",
+            Action::NewTab => "Open a new tab",
+            Action::CloseTab => "Close the tab and its log groups",
+            Action::RenameTab => "Rename the tab",
+            Action::NextTab => "Go to the next tab",
+            Action::PrevTab => "Go to the previous tab",
+            Action::ToggleMergedView => {
+                "Merge the log groups of the tab into one timeline / split it"
+            }
+            Action::NextLogGroup => "Move cursor down",
+            Action::PrevLogGroup => "Move cursor up",
+            Action::SelectLogGroup => "Select log group",
+            Action::CopyLogEvent => "Copy the selected log event to clipboard",
+            Action::ToggleLogEvent => "Close/open current log event",
+            Action::NextLogEvent => "Move cursor down",
+            Action::PrevLogEvent => "Move cursor up",
+            Action::NextLogEventByStep => "Move cursor down by logevent_step",
+            Action::PrevLogEventByStep => "Move cursor up by logevent_step",
+            Action::FirstLogEvent => "Go to the first log event",
+            Action::LastLogEvent => "Go to the last log event",
+            Action::OpenSearchDialog => "Open search dialog",
+            Action::OpenStreamPicker => "Open log stream picker",
+            Action::OpenExportDialog => "Export log events to a file",
+            Action::SwitchStreamColumn => {
+                "Switch log stream column (hidden/full/truncated/colour tag)"
+            }
+            Action::OpenLocalSearch => "Search the fetched log events by regex",
+            Action::NextMatch => "Go to the next match of the regex",
+            Action::PrevMatch => "Go to the previous match of the regex",
+            Action::OpenFilterDialog => "Open the include/exclude rules of the log events",
+            Action::ToggleNoiseFilter => "Hide/show Lambda platform lines and health checks",
+            Action::CycleMinLevel => "Hide the log events below the level (all/INFO/WARN/ERROR)",
+            Action::TogglePrettyJson => "Pretty-print the opened JSON log events",
+            Action::EditJsonFields => "Choose the JSON fields shown as the columns",
+            Action::OpenEventDetail => "Show the detail of the log event",
+            Action::ShowContext => "Show the log events around the log event in its log stream",
+            Action::Retry => "Retry the failed fetch of the log events or the log groups",
+            Action::Confirm => "Search / search the checked log streams / export",
+            Action::Cancel => "Close the dialog",
+            Action::NextItem => "Move cursor down (newer search in the query)",
+            Action::PrevItem => "Move cursor up (older search in the query)",
+            Action::SelectItem => "Select the period",
+            Action::ToggleItem => {
+                "Toggle period input focus / check the log stream / toggle fetching all pages"
+            }
+            Action::DeleteItem => "Delete the saved search / the filter rule",
+            Action::ScrollDown => "Scroll down the message of the event detail",
+            Action::ScrollUp => "Scroll up the message of the event detail",
+            Action::ToggleQueryMode => "Toggle Logs Insights query",
+            Action::SaveSearch => "Save the search with a name",
+            Action::OpenSavedSearches => "Open saved searches",
+        }
+    }
+}
+
+/// Bindings from actions to keys. Built from a preset ("default", "vim" or "emacs")
+/// and the `[keys]` table of the config file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyMap {
+    bindings: BTreeMap<Action, Vec<KeyEvent>>,
+}
+
+impl Default for KeyMap {
+    fn default() -> Self {
+        let bindings = ACTIONS
+            .iter()
+            .map(|&action| (action, default_keys(action)))
+            .collect();
+        KeyMap { bindings }
+    }
+}
+
+impl KeyMap {
+    pub fn preset(name: &str) -> Result<Self> {
+        let mut keymap = KeyMap::default();
+        let overrides = match name {
+            "default" => vec![],
+            "vim" => vec![
+                (Action::MoveLeft, vec![key(KeyCode::Left), alt('h')]),
+                (Action::MoveDown, vec![key(KeyCode::Down), alt('j')]),
+                (Action::MoveUp, vec![key(KeyCode::Up), alt('k')]),
+                (Action::MoveRight, vec![key(KeyCode::Right), alt('l')]),
+                (Action::NextLogGroup, vec![key(KeyCode::Down), ctrl('n')]),
+                (Action::PrevLogGroup, vec![key(KeyCode::Up), ctrl('p')]),
+                (Action::CopyLogEvent, vec![key(KeyCode::Enter), chr('y')]),
+                (Action::ToggleLogEvent, vec![key(KeyCode::Tab), chr('o')]),
+                (Action::NextLogEventByStep, vec![chr('J'), ctrl('d')]),
+                (Action::PrevLogEventByStep, vec![chr('K'), ctrl('u')]),
+                (Action::NextItem, vec![key(KeyCode::Down), ctrl('n')]),
+                (Action::PrevItem, vec![key(KeyCode::Up), ctrl('p')]),
+            ],
+            "emacs" => vec![
+                (Action::MoveLeft, vec![key(KeyCode::Left), alt('b')]),
+                (Action::MoveDown, vec![key(KeyCode::Down), alt('n')]),
+                (Action::MoveUp, vec![key(KeyCode::Up), alt('p')]),
+                (Action::MoveRight, vec![key(KeyCode::Right), alt('f')]),
+                (Action::NextLogGroup, vec![ctrl('n'), key(KeyCode::Down)]),
+                (Action::PrevLogGroup, vec![ctrl('p'), key(KeyCode::Up)]),
+                (Action::CopyLogEvent, vec![alt('w'), key(KeyCode::Enter)]),
+                (Action::NextLogEvent, vec![ctrl('n')]),
+                (Action::PrevLogEvent, vec![ctrl('p')]),
+                (Action::NextLogEventByStep, vec![ctrl('v')]),
+                (Action::PrevLogEventByStep, vec![alt('v')]),
+                (Action::FirstLogEvent, vec![alt('<')]),
+                (Action::LastLogEvent, vec![alt('>')]),
+                (Action::Cancel, vec![ctrl('g'), key(KeyCode::Esc)]),
+                (Action::NextItem, vec![ctrl('n'), key(KeyCode::Down)]),
+                (Action::PrevItem, vec![ctrl('p'), key(KeyCode::Up)]),
+            ],
+            _ => return Err(anyhow!("unknown keymap: {}", name)),
+        };
+        for (action, keys) in overrides {
+            keymap.bind(action, keys);
+        }
+        Ok(keymap)
+    }
+
+    /// `keys` is the `[keys]` table of the config file, e.g. `next_log_event = ["j", "ctrl+n"]`
+    pub fn from_config(preset: Option<&str>, keys: &BTreeMap<String, Vec<String>>) -> Result<Self> {
+        let mut keymap = Self::preset(preset.unwrap_or("default"))?;
+        for (name, inputs) in keys {
+            let action =
+                Action::from_name(name).ok_or_else(|| anyhow!("unknown action: {}", name))?;
+            let keys = inputs
+                .iter()
+                .map(|input| parse_key(input))
+                .collect::<Result<Vec<_>>>()?;
+            keymap.bind(action, keys);
+        }
+        Ok(keymap)
+    }
+
+    /// Replace the keys of the action
+    pub fn bind(&mut self, action: Action, keys: Vec<KeyEvent>) {
+        self.bindings.insert(action, keys);
+    }
+
+    pub fn keys(&self, action: Action) -> &[KeyEvent] {
+        self.bindings
+            .get(&action)
+            .map(|keys| keys.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// The action of the context bound to the key
+    pub fn action(&self, context: Context, event: &KeyEvent) -> Option<Action> {
+        self.bindings
+            .iter()
+            .filter(|(action, _)| action.context() == context)
+            .find(|(_, keys)| keys.iter().any(|key| matches(key, event)))
+            .map(|(&action, _)| action)
+    }
+
+    pub fn is(&self, action: Action, event: &KeyEvent) -> bool {
+        self.keys(action).iter().any(|key| matches(key, event))
+    }
+
+    /// Push the first key of the action for the status bar
+    pub fn push_key_map(
+        &self,
+        maps: &mut BTreeMap<KeyEventWrapper, String>,
+        action: Action,
+        description: &str,
+    ) {
+        if let Some(key) = self.keys(action).first() {
+            maps.insert(KeyEventWrapper::new(*key), description.to_string());
+        }
+    }
+
+    pub fn help_message(&self) -> String {
+        let mut msg = String::from("\n");
+        for context in &[
+            Context::Global,
+            Context::SideMenu,
+            Context::EventArea,
+            Context::Dialog,
+        ] {
+            msg.push_str(&format!("<{}>\n", context.title()));
+            for action in ACTIONS.iter().filter(|a| a.context() == *context) {
+                let keys = self
+                    .keys(*action)
+                    .iter()
+                    .map(|key| KeyEventWrapper::new(*key).to_string())
+                    .collect::<Vec<_>>();
+                if keys.is_empty() {
+                    continue;
+                }
+                msg.push_str(&format!(
+                    "  [{}] - {}\n",
+                    keys.join("/"),
+                    action.description()
+                ));
+            }
+            for (keys, description) in context.fixed_keys() {
+                msg.push_str(&format!("  [{}] - {}\n", keys, description));
+            }
+            msg.push('\n');
+        }
+        msg
+    }
+}
+
+/// Parse a key like "j", "J", "ctrl+s", "alt+shift+j", "enter" or "pagedown"
+pub fn parse_key(input: &str) -> Result<KeyEvent> {
+    let err = || anyhow!("invalid key: {}", input);
+    let (mods, name) = match input.rfind('+') {
+        // "+" itself, or "ctrl++"
+        Some(i) if i + 1 == input.len() && input.len() > 1 => {
+            (&input[..input.len() - 2], &input[input.len() - 1..])
+        }
+        Some(i) if i + 1 < input.len() => (&input[..i], &input[i + 1..]),
+        _ => ("", input),
+    };
+    let mut modifiers = KeyModifiers::NONE;
+    for m in mods.split('+').filter(|m| !m.is_empty()) {
+        modifiers |= match m.to_lowercase().as_str() {
+            "ctrl" | "c" => KeyModifiers::CONTROL,
+            "alt" | "meta" | "m" => KeyModifiers::ALT,
+            "shift" | "s" => KeyModifiers::SHIFT,
+            _ => return Err(err()),
+        };
+    }
+    let code = if name.chars().count() == 1 {
+        let c = name.chars().next().ok_or_else(err)?;
+        if modifiers.contains(KeyModifiers::SHIFT) {
+            KeyCode::Char(c.to_ascii_uppercase())
+        } else {
+            KeyCode::Char(c)
+        }
+    } else {
+        match name.to_lowercase().as_str() {
+            "enter" => KeyCode::Enter,
+            "esc" => KeyCode::Esc,
+            "tab" => KeyCode::Tab,
+            "backtab" => KeyCode::BackTab,
+            "space" => KeyCode::Char(' '),
+            "backspace" => KeyCode::Backspace,
+            "delete" => KeyCode::Delete,
+            "insert" => KeyCode::Insert,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" => KeyCode::PageUp,
+            "pagedown" => KeyCode::PageDown,
+            f if f.starts_with('f') => KeyCode::F(f[1..].parse().map_err(|_| err())?),
+            _ => return Err(err()),
+        }
+    };
+    Ok(KeyEvent::new(code, modifiers))
+}
+
+/// Terminals differ in whether SHIFT is reported with an uppercase char, so it is ignored for chars.
+fn matches(key: &KeyEvent, event: &KeyEvent) -> bool {
+    fn normalize(event: &KeyEvent) -> (KeyCode, KeyModifiers) {
+        let mut modifiers = event.modifiers;
+        if let KeyCode::Char(_) | KeyCode::BackTab = event.code {
+            modifiers.remove(KeyModifiers::SHIFT);
+        }
+        (event.code, modifiers)
+    }
+    normalize(key) == normalize(event)
+}
+
+fn default_keys(action: Action) -> Vec<KeyEvent> {
+    let keys = match action {
+        Action::Exit => ctrl('c'),
+        Action::ShowHelp => chr('?'),
+        Action::CloseHelp => chr('q'),
+        Action::ToggleSideMenu => key(KeyCode::Tab),
+        Action::MoveLeft => key(KeyCode::Left),
+        Action::MoveRight => key(KeyCode::Right),
+        Action::MoveUp => key(KeyCode::Up),
+        Action::MoveDown => key(KeyCode::Down),
+        Action::NewTab => ctrl('t'),
+        Action::CloseTab => ctrl('w'),
+        Action::RenameTab => key(KeyCode::F(2)),
+        Action::NextTab => KeyEvent::new(KeyCode::Right, KeyModifiers::CONTROL),
+        Action::PrevTab => KeyEvent::new(KeyCode::Left, KeyModifiers::CONTROL),
+        Action::ToggleMergedView => ctrl('o'),
+        Action::NextLogGroup => key(KeyCode::Down),
+        Action::PrevLogGroup => key(KeyCode::Up),
+        Action::SelectLogGroup => key(KeyCode::Enter),
+        Action::CopyLogEvent => key(KeyCode::Enter),
+        Action::ToggleLogEvent => key(KeyCode::Tab),
+        Action::NextLogEvent => chr('j'),
+        Action::PrevLogEvent => chr('k'),
+        Action::NextLogEventByStep => chr('J'),
+        Action::PrevLogEventByStep => chr('K'),
+        Action::FirstLogEvent => chr('g'),
+        Action::LastLogEvent => chr('G'),
+        Action::OpenSearchDialog => ctrl('s'),
+        Action::OpenStreamPicker => ctrl('l'),
+        Action::OpenExportDialog => ctrl('e'),
+        Action::SwitchStreamColumn => chr('c'),
+        Action::OpenLocalSearch => chr('/'),
+        Action::NextMatch => chr('n'),
+        Action::PrevMatch => chr('N'),
+        Action::OpenFilterDialog => chr('f'),
+        Action::ToggleNoiseFilter => chr('H'),
+        Action::CycleMinLevel => chr('L'),
+        Action::TogglePrettyJson => chr('p'),
+        Action::EditJsonFields => chr('C'),
+        Action::OpenEventDetail => chr('v'),
+        Action::ShowContext => chr('x'),
+        Action::Retry => chr('R'),
+        Action::Confirm => key(KeyCode::Enter),
+        Action::Cancel => key(KeyCode::Esc),
+        Action::NextItem => key(KeyCode::Down),
+        Action::PrevItem => key(KeyCode::Up),
+        Action::SelectItem => chr(' '),
+        Action::ToggleItem => key(KeyCode::Tab),
+        Action::DeleteItem => ctrl('d'),
+        Action::ScrollDown => key(KeyCode::PageDown),
+        Action::ScrollUp => key(KeyCode::PageUp),
+        Action::ToggleQueryMode => ctrl('t'),
+        Action::SaveSearch => ctrl('s'),
+        Action::OpenSavedSearches => ctrl('r'),
+    };
+    vec![keys]
+}
+
+fn key(code: KeyCode) -> KeyEvent {
+    KeyEvent::new(code, KeyModifiers::NONE)
+}
+
+fn chr(c: char) -> KeyEvent {
+    key(KeyCode::Char(c))
+}
+
+fn ctrl(c: char) -> KeyEvent {
+    KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL)
+}
+
+fn alt(c: char) -> KeyEvent {
+    KeyEvent::new(KeyCode::Char(c), KeyModifiers::ALT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_key() {
+        assert_eq!(chr('j'), parse_key("j").unwrap());
+        assert_eq!(chr('J'), parse_key("J").unwrap());
+        assert_eq!(
+            KeyEvent::new(KeyCode::Char('J'), KeyModifiers::SHIFT),
+            parse_key("shift+j").unwrap()
+        );
+        assert_eq!(ctrl('s'), parse_key("ctrl+s").unwrap());
+        assert_eq!(
+            KeyEvent::new(
+                KeyCode::Char('x'),
+                KeyModifiers::CONTROL | KeyModifiers::ALT
+            ),
+            parse_key("Ctrl+Alt+x").unwrap()
+        );
+        assert_eq!(chr('+'), parse_key("+").unwrap());
+        assert_eq!(
+            KeyEvent::new(KeyCode::Char('+'), KeyModifiers::CONTROL),
+            parse_key("ctrl++").unwrap()
+        );
+        assert_eq!(key(KeyCode::PageDown), parse_key("pagedown").unwrap());
+        assert_eq!(chr(' '), parse_key("space").unwrap());
+        assert_eq!(key(KeyCode::F(5)), parse_key("f5").unwrap());
+        assert!(parse_key("hyper+j").is_err());
+        assert!(parse_key("pgdn").is_err());
+        assert!(parse_key("").is_err());
+    }
+
+    #[test]
+    fn test_action() {
+        let keymap = KeyMap::default();
+        assert_eq!(
+            Some(Action::NextLogEvent),
+            keymap.action(Context::EventArea, &chr('j'))
+        );
+        // shift is ignored for chars
+        assert_eq!(
+            Some(Action::LastLogEvent),
+            keymap.action(
+                Context::EventArea,
+                &KeyEvent::new(KeyCode::Char('G'), KeyModifiers::SHIFT)
+            )
+        );
+        // the same key in the other contexts
+        assert_eq!(
+            Some(Action::ToggleSideMenu),
+            keymap.action(Context::Global, &key(KeyCode::Tab))
+        );
+        assert_eq!(
+            Some(Action::ToggleLogEvent),
+            keymap.action(Context::EventArea, &key(KeyCode::Tab))
+        );
+        assert_eq!(None, keymap.action(Context::SideMenu, &chr('j')));
+        assert!(keymap.is(Action::Exit, &ctrl('c')));
+        assert!(!keymap.is(Action::Exit, &chr('c')));
+    }
+
+    #[test]
+    fn test_preset() {
+        let vim = KeyMap::preset("vim").unwrap();
+        assert_eq!(
+            Some(Action::NextLogEventByStep),
+            vim.action(Context::EventArea, &ctrl('d'))
+        );
+        assert_eq!(
+            Some(Action::MoveLeft),
+            vim.action(Context::Global, &alt('h'))
+        );
+        // the defaults are kept
+        assert_eq!(
+            Some(Action::NextLogEvent),
+            vim.action(Context::EventArea, &chr('j'))
+        );
+        let emacs = KeyMap::preset("emacs").unwrap();
+        assert_eq!(
+            Some(Action::NextLogEvent),
+            emacs.action(Context::EventArea, &ctrl('n'))
+        );
+        assert_eq!(None, emacs.action(Context::EventArea, &chr('j')));
+        assert_eq!(
+            Some(Action::Cancel),
+            emacs.action(Context::Dialog, &ctrl('g'))
+        );
+        assert!(KeyMap::preset("nano").is_err());
+    }
+
+    #[test]
+    fn test_from_config() {
+        let mut keys = BTreeMap::new();
+        keys.insert(
+            String::from("next_log_event"),
+            vec![String::from("down"), String::from("ctrl+j")],
+        );
+        let keymap = KeyMap::from_config(Some("vim"), &keys).unwrap();
+        assert_eq!(
+            &[key(KeyCode::Down), ctrl('j')],
+            keymap.keys(Action::NextLogEvent)
+        );
+        assert_eq!(None, keymap.action(Context::EventArea, &chr('j')));
+        assert_eq!(
+            KeyMap::default(),
+            KeyMap::from_config(None, &BTreeMap::new()).unwrap()
+        );
+        // invalid
+        keys.insert(String::from("jump"), vec![String::from("j")]);
+        assert!(KeyMap::from_config(None, &keys).is_err());
+        let mut keys = BTreeMap::new();
+        keys.insert(String::from("exit"), vec![String::from("ctrl+")]);
+        assert!(KeyMap::from_config(None, &keys).is_err());
+    }
+
+    #[test]
+    fn test_push_key_map() {
+        let keymap = KeyMap::preset("emacs").unwrap();
+        let mut maps = BTreeMap::new();
+        keymap.push_key_map(&mut maps, Action::NextLogEvent, "Next log event");
+        assert_eq!(
+            Some(&String::from("Next log event")),
+            maps.get(&KeyEventWrapper::new(ctrl('n')))
+        );
+        assert_eq!(1, maps.len());
+    }
+
+    #[test]
+    fn test_help_message() {
+        let msg = KeyMap::preset("vim").unwrap().help_message();
+        assert!(msg.contains("<Global>\n  [C+Ctrl] - Exit\n"));
+        assert!(msg.contains("  [J+Shift/D+Ctrl] - Move cursor down by logevent_step\n"));
+        assert!(msg.contains("  [←/H+Alt] - Move focus left\n"));
+        assert!(msg.contains("  [BackSpace] - Incremental filtering (remove)\n"));
+    }
+}
@@ -5,15 +5,28 @@
 pub mod test_helper;
 
 pub mod app;
+pub mod cli;
 pub mod client;
+pub mod config;
 pub mod constant;
+pub mod context;
 pub mod event;
+pub mod export;
 pub mod handler;
+pub mod insights;
+pub mod json_message;
 pub mod key_event_wrapper;
+pub mod keymap;
 pub mod loader;
+pub mod local_filter;
+pub mod local_search;
+pub mod log_level;
 pub mod logevents;
 pub mod loggroups;
 pub mod state;
+pub mod stream_column;
+pub mod tail_cursor;
 pub mod terminal;
+pub mod time_input;
 pub mod ui;
 pub mod utils;
@@ -0,0 +1,251 @@
+use std::fmt;
+
+use anyhow::{anyhow, Result};
+use regex::Regex;
+use rusoto_logs::FilteredLogEvent;
+
+use crate::log_level::LogLevel;
+
+/// Built-in rules hiding the common noise lines
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Preset {
+    /// START/END/REPORT RequestId lines written by the Lambda runtime
+    LambdaPlatform,
+    /// requests of the load balancer and Kubernetes probes
+    HealthCheck,
+}
+
+impl Preset {
+    pub const ALL: [Preset; 2] = [Preset::LambdaPlatform, Preset::HealthCheck];
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Preset::LambdaPlatform => "Hide Lambda platform lines (START/END/REPORT)",
+            Preset::HealthCheck => "Hide health checks",
+        }
+    }
+
+    pub fn is_match(self, msg: &str) -> bool {
+        match self {
+            Preset::LambdaPlatform => [
+                "START RequestId:",
+                "END RequestId:",
+                "REPORT RequestId:",
+                "INIT_START ",
+            ]
+            .iter()
+            .any(|prefix| msg.starts_with(prefix)),
+            Preset::HealthCheck => ["ELB-HealthChecker", "kube-probe", "GET /health"]
+                .iter()
+                .any(|pattern| msg.contains(pattern)),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Pattern {
+    Substring(String),
+    Regex(Regex),
+}
+
+impl Pattern {
+    fn is_match(&self, msg: &str) -> bool {
+        match self {
+            Pattern::Substring(s) => msg.contains(s.as_str()),
+            Pattern::Regex(regex) => regex.is_match(msg),
+        }
+    }
+}
+
+/// Rule matching the messages, written as `+ERROR` (include), `-health` (exclude)
+/// or `-/GET \/(health|ping)/` with a regex. Rules without the sign exclude the messages.
+#[derive(Debug, Clone)]
+pub struct FilterRule {
+    pub include: bool,
+    pub pattern: Pattern,
+}
+
+impl FilterRule {
+    pub fn parse(input: &str) -> Result<Self> {
+        let (include, rest) = if let Some(rest) = input.strip_prefix('+') {
+            (true, rest)
+        } else if let Some(rest) = input.strip_prefix('-') {
+            (false, rest)
+        } else {
+            (false, input)
+        };
+        if rest.is_empty() {
+            return Err(anyhow!("empty rule"));
+        }
+        let pattern = if rest.len() > 1 && rest.starts_with('/') && rest.ends_with('/') {
+            Pattern::Regex(Regex::new(&rest[1..rest.len() - 1])?)
+        } else {
+            Pattern::Substring(rest.to_string())
+        };
+        Ok(FilterRule { include, pattern })
+    }
+}
+
+impl fmt::Display for FilterRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.include { '+' } else { '-' };
+        match &self.pattern {
+            Pattern::Substring(s) => write!(f, "{}{}", sign, s),
+            Pattern::Regex(regex) => write!(f, "{}/{}/", sign, regex.as_str()),
+        }
+    }
+}
+
+/// Rules applied to the fetched log events of a pane on top of the filter pattern of the query.
+/// An event is shown if it matches one of the include rules (or there are none),
+/// none of the exclude rules and the enabled presets, and its level is not below the minimum.
+#[derive(Debug, Clone, Default)]
+pub struct LocalFilter {
+    rules: Vec<FilterRule>,
+    presets: Vec<Preset>,
+    /// events without the detected level are shown
+    min_level: Option<LogLevel>,
+}
+
+impl LocalFilter {
+    pub fn rules(&self) -> &[FilterRule] {
+        &self.rules
+    }
+
+    pub fn presets(&self) -> &[Preset] {
+        &self.presets
+    }
+
+    pub fn min_level(&self) -> Option<LogLevel> {
+        self.min_level
+    }
+
+    pub fn set_min_level(&mut self, min_level: Option<LogLevel>) {
+        self.min_level = min_level;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty() && self.presets.is_empty() && self.min_level.is_none()
+    }
+
+    pub fn push_rule(&mut self, rule: FilterRule) {
+        self.rules.push(rule);
+    }
+
+    pub fn remove_rule(&mut self, idx: usize) {
+        if idx < self.rules.len() {
+            self.rules.remove(idx);
+        }
+    }
+
+    /// Switch the rule between include and exclude
+    pub fn toggle_rule(&mut self, idx: usize) {
+        if let Some(rule) = self.rules.get_mut(idx) {
+            rule.include = !rule.include;
+        }
+    }
+
+    pub fn toggle_preset(&mut self, preset: Preset) {
+        if let Some(pos) = self.presets.iter().position(|p| *p == preset) {
+            self.presets.remove(pos);
+        } else {
+            self.presets.push(preset);
+        }
+    }
+
+    /// Disable the presets if any is enabled, otherwise enable all of them
+    pub fn toggle_presets(&mut self) {
+        if self.presets.is_empty() {
+            self.presets = Preset::ALL.to_vec();
+        } else {
+            self.presets.clear();
+        }
+    }
+
+    /// `level` is the one detected when the event is pushed, not detected again here
+    pub fn is_hidden(&self, event: &FilteredLogEvent, level: Option<LogLevel>) -> bool {
+        let msg = event.message.as_deref().unwrap_or_default();
+        if let Some(min_level) = self.min_level {
+            if matches!(level, Some(level) if level < min_level) {
+                return true;
+            }
+        }
+        let mut includes = self.rules.iter().filter(|r| r.include).peekable();
+        if includes.peek().is_some() && !includes.any(|r| r.pattern.is_match(msg)) {
+            return true;
+        }
+        self.rules
+            .iter()
+            .filter(|r| !r.include)
+            .any(|r| r.pattern.is_match(msg))
+            || self.presets.iter().any(|p| p.is_match(msg))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::log_level;
+
+    fn is_hidden(filter: &LocalFilter, msg: &str) -> bool {
+        let event = FilteredLogEvent {
+            message: Some(msg.to_string()),
+            ..Default::default()
+        };
+        filter.is_hidden(&event, log_level::detect(msg))
+    }
+
+    #[test]
+    fn test_parse() {
+        let rule = FilterRule::parse("+ERROR").unwrap();
+        assert!(rule.include);
+        assert_eq!("+ERROR", rule.to_string());
+        assert_eq!("-health", FilterRule::parse("health").unwrap().to_string());
+        let rule = FilterRule::parse("-/GET \\/(health|ping)/").unwrap();
+        assert!(matches!(rule.pattern, Pattern::Regex(_)));
+        assert_eq!("-/GET \\/(health|ping)/", rule.to_string());
+        // a single slash is a substring
+        assert!(matches!(
+            FilterRule::parse("/").unwrap().pattern,
+            Pattern::Substring(_)
+        ));
+        assert!(FilterRule::parse("-").is_err());
+        assert!(FilterRule::parse("-/(/").is_err());
+    }
+
+    #[test]
+    fn test_is_hidden() {
+        let mut filter = LocalFilter::default();
+        assert!(filter.is_empty());
+        assert!(!is_hidden(&filter, "START RequestId: 1 Version: $LATEST"));
+        filter.toggle_presets();
+        assert_eq!(&Preset::ALL, filter.presets());
+        assert!(is_hidden(&filter, "START RequestId: 1 Version: $LATEST"));
+        assert!(is_hidden(
+            &filter,
+            "10.0.0.1 GET /health 200 ELB-HealthChecker/2.0"
+        ));
+        assert!(!is_hidden(&filter, "ERROR timeout"));
+        filter.toggle_preset(Preset::HealthCheck);
+        assert!(!is_hidden(&filter, "GET /health 200"));
+        // include and exclude rules
+        filter.push_rule(FilterRule::parse("+/ERROR|WARN/").unwrap());
+        filter.push_rule(FilterRule::parse("-retry").unwrap());
+        assert!(is_hidden(&filter, "INFO done"));
+        assert!(!is_hidden(&filter, "WARN slow"));
+        assert!(is_hidden(&filter, "ERROR timeout, retry"));
+        filter.toggle_rule(0);
+        assert!(is_hidden(&filter, "WARN slow"));
+        assert!(!is_hidden(&filter, "INFO done"));
+        filter.remove_rule(0);
+        filter.remove_rule(0);
+        filter.toggle_presets();
+        assert!(filter.is_empty());
+        // the events without the level are shown
+        filter.set_min_level(Some(LogLevel::Warn));
+        assert!(!filter.is_empty());
+        assert!(is_hidden(&filter, "INFO done"));
+        assert!(!is_hidden(&filter, "ERROR timeout"));
+        assert!(!is_hidden(&filter, "done"));
+    }
+}
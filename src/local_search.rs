@@ -0,0 +1,221 @@
+use anyhow::Result;
+use regex::Regex;
+use rusoto_logs::FilteredLogEvent;
+use tui::text::{Span, Spans, Text};
+
+use crate::{constant::MATCH_STYLE, logevents::LogEvents};
+
+/// Regex search over the fetched log events, without querying the API.
+/// The matches are kept until the log events change.
+#[derive(Debug, Clone)]
+pub struct LocalSearch {
+    regex: Regex,
+    /// indexes of the matched events
+    matches: Vec<usize>,
+    /// the version of the log events searched for the matches
+    version: Option<u64>,
+}
+
+impl LocalSearch {
+    pub fn new(pattern: &str) -> Result<Self> {
+        Ok(LocalSearch {
+            regex: Regex::new(pattern)?,
+            matches: vec![],
+            version: None,
+        })
+    }
+
+    pub fn pattern(&self) -> &str {
+        self.regex.as_str()
+    }
+
+    pub fn is_match(&self, event: &FilteredLogEvent) -> bool {
+        event
+            .message
+            .as_ref()
+            .map(|msg| self.regex.is_match(msg))
+            .unwrap_or(false)
+    }
+
+    /// Search the log events again if they have changed since the last search
+    pub fn update(&mut self, events: &LogEvents) {
+        if self.version == Some(events.version()) {
+            return;
+        }
+        self.matches = events
+            .items()
+            .iter()
+            .enumerate()
+            .filter(|(_, e)| self.is_match(e))
+            .map(|(i, _)| i)
+            .collect();
+        self.version = Some(events.version());
+    }
+
+    /// Indexes of the matched events at the last update
+    pub fn matches(&self) -> &[usize] {
+        &self.matches
+    }
+
+    /// The first match after the index, from the top if none is after it
+    pub fn next_match(&self, from: Option<usize>) -> Option<usize> {
+        match from {
+            Some(from) => self
+                .matches
+                .iter()
+                .find(|&&i| i > from)
+                .or_else(|| self.matches.first()),
+            None => self.matches.first(),
+        }
+        .copied()
+    }
+
+    /// The last match before the index, from the bottom if none is before it
+    pub fn prev_match(&self, from: Option<usize>) -> Option<usize> {
+        match from {
+            Some(from) => self
+                .matches
+                .iter()
+                .rev()
+                .find(|&&i| i < from)
+                .or_else(|| self.matches.last()),
+            None => self.matches.last(),
+        }
+        .copied()
+    }
+
+    /// The message with the matched parts highlighted, split into the lines at the newlines and
+    /// at `breaks` (byte offsets where the message is wrapped).
+    /// The regex runs over the whole message, so the matches across the breaks are kept.
+    pub fn highlight<'a>(&self, msg: &str, breaks: &[usize]) -> Text<'a> {
+        let mut matched = vec![false; msg.len()];
+        for m in self.regex.find_iter(msg) {
+            matched[m.start()..m.end()]
+                .iter_mut()
+                .for_each(|b| *b = true);
+        }
+        let mut ends = breaks
+            .iter()
+            .copied()
+            .filter(|&i| i < msg.len() && msg.is_char_boundary(i))
+            .chain(msg.match_indices('\n').map(|(i, _)| i))
+            .collect::<Vec<usize>>();
+        ends.sort_unstable();
+        ends.dedup();
+        ends.push(msg.len());
+        let mut lines = vec![];
+        let mut start = 0;
+        for end in ends {
+            lines.push(highlight_line(&msg[start..end], &matched[start..end]));
+            // the newline itself is not shown
+            start = if msg[end..].starts_with('\n') {
+                end + 1
+            } else {
+                end
+            };
+        }
+        Text::from(lines)
+    }
+}
+
+/// The line split into the spans of the matched and the other parts
+fn highlight_line<'a>(line: &str, matched: &[bool]) -> Spans<'a> {
+    let mut spans = vec![];
+    let mut last = 0;
+    for i in 1..=line.len() {
+        if i < line.len() && matched[i] == matched[last] {
+            continue;
+        }
+        let text = line[last..i].to_string();
+        spans.push(if matched[last] {
+            Span::styled(text, *MATCH_STYLE)
+        } else {
+            Span::raw(text)
+        });
+        last = i;
+    }
+    if spans.is_empty() {
+        spans.push(Span::raw(String::default()));
+    }
+    Spans::from(spans)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helper::make_log_events;
+
+    #[test]
+    fn test_new() {
+        assert_eq!("ERR(OR)?", LocalSearch::new("ERR(OR)?").unwrap().pattern());
+        assert!(LocalSearch::new("ERR(OR").is_err());
+    }
+
+    #[test]
+    fn test_next_and_prev_match() {
+        // log_event_0 .. log_event_5
+        let events = LogEvents::new(make_log_events(0, 5, 0));
+        let mut search = LocalSearch::new("event_[135]").unwrap();
+        search.update(&events);
+        assert_eq!(&[1, 3, 5], search.matches());
+        assert_eq!(Some(1), search.next_match(None));
+        assert_eq!(Some(3), search.next_match(Some(1)));
+        assert_eq!(Some(3), search.next_match(Some(2)));
+        // wrapped
+        assert_eq!(Some(1), search.next_match(Some(5)));
+        assert_eq!(Some(5), search.prev_match(None));
+        assert_eq!(Some(1), search.prev_match(Some(3)));
+        assert_eq!(Some(5), search.prev_match(Some(1)));
+        let mut search = LocalSearch::new("nothing").unwrap();
+        search.update(&events);
+        assert_eq!(None, search.next_match(Some(1)));
+        assert_eq!(None, search.prev_match(Some(1)));
+    }
+
+    #[test]
+    fn test_update() {
+        let mut events = LogEvents::new(make_log_events(0, 1, 0));
+        let mut search = LocalSearch::new("event_[13]").unwrap();
+        search.update(&events);
+        assert_eq!(&[1], search.matches());
+        // searched again only when the events change
+        events.push_items(&mut make_log_events(2, 3, 0), false);
+        assert_eq!(&[1], search.matches());
+        search.update(&events);
+        assert_eq!(&[1, 3], search.matches());
+        events.clear_items();
+        search.update(&events);
+        assert!(search.matches().is_empty());
+    }
+
+    #[test]
+    fn test_highlight() {
+        let search = LocalSearch::new("[0-9]+").unwrap();
+        let text = search.highlight("a 12 b 3\nc", &[]);
+        assert_eq!(
+            Text::from(vec![
+                Spans::from(vec![
+                    Span::raw("a "),
+                    Span::styled("12", *MATCH_STYLE),
+                    Span::raw(" b "),
+                    Span::styled("3", *MATCH_STYLE),
+                ]),
+                Spans::from(vec![Span::raw("c")]),
+            ]),
+            text
+        );
+        // the match across the break
+        let text = search.highlight("ab123cd", &[3]);
+        assert_eq!(
+            Text::from(vec![
+                Spans::from(vec![Span::raw("ab"), Span::styled("1", *MATCH_STYLE)]),
+                Spans::from(vec![Span::styled("23", *MATCH_STYLE), Span::raw("cd")]),
+            ]),
+            text
+        );
+        // empty matches are not highlighted
+        let search = LocalSearch::new("x*").unwrap();
+        assert_eq!(Text::from("abc"), search.highlight("abc", &[]));
+        assert_eq!(Text::from("ab\nc"), search.highlight("abc", &[2]));
+    }
+}
@@ -0,0 +1,174 @@
+use std::fmt;
+
+use serde_json::{Map, Value};
+use tui::style::{Color, Style};
+
+use crate::json_message;
+
+/// fields of the JSON messages holding the level
+const JSON_LEVEL_FIELDS: [&str; 4] = ["level", "severity", "log.level", "lvl"];
+/// words at the head of the message looked for the level, e.g. the timestamp, the request id and the level of Lambda
+const HEAD_WORDS: usize = 4;
+
+/// Severity of the log event, detected from the message
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LogLevel {
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+    Fatal,
+}
+
+impl LogLevel {
+    fn from_name(name: &str) -> Option<Self> {
+        match name.to_uppercase().as_str() {
+            "TRACE" => Some(LogLevel::Trace),
+            "DEBUG" => Some(LogLevel::Debug),
+            "INFO" | "NOTICE" => Some(LogLevel::Info),
+            "WARN" | "WARNING" => Some(LogLevel::Warn),
+            "ERROR" | "ERR" => Some(LogLevel::Error),
+            "FATAL" | "CRITICAL" | "CRIT" | "PANIC" => Some(LogLevel::Fatal),
+            _ => None,
+        }
+    }
+
+    /// Style of the rows of the level
+    pub fn style(self) -> Style {
+        match self {
+            LogLevel::Trace | LogLevel::Debug => Style::default().fg(Color::DarkGray),
+            LogLevel::Info => Style::default(),
+            LogLevel::Warn => Style::default().fg(Color::Yellow),
+            LogLevel::Error => Style::default().fg(Color::Red),
+            LogLevel::Fatal => Style::default().fg(Color::LightRed),
+        }
+    }
+
+    /// The next minimum level of the filter: all -> INFO -> WARN -> ERROR -> all
+    pub fn next_min_level(level: Option<Self>) -> Option<Self> {
+        match level {
+            None => Some(LogLevel::Info),
+            Some(LogLevel::Trace) | Some(LogLevel::Debug) => Some(LogLevel::Info),
+            Some(LogLevel::Info) => Some(LogLevel::Warn),
+            Some(LogLevel::Warn) => Some(LogLevel::Error),
+            Some(LogLevel::Error) | Some(LogLevel::Fatal) => None,
+        }
+    }
+}
+
+impl fmt::Display for LogLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            LogLevel::Trace => "TRACE",
+            LogLevel::Debug => "DEBUG",
+            LogLevel::Info => "INFO",
+            LogLevel::Warn => "WARN",
+            LogLevel::Error => "ERROR",
+            LogLevel::Fatal => "FATAL",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// The level of the message from the level field of JSON, the Lambda REPORT line,
+/// or the level at the head of the message like `ERROR`, `[info]` or `WARN:`.
+pub fn detect(msg: &str) -> Option<LogLevel> {
+    match json_message::parse(msg) {
+        Some(object) => detect_json(&object),
+        None => detect_text(msg),
+    }
+}
+
+/// The level of the JSON message parsed already
+pub fn detect_json(object: &Map<String, Value>) -> Option<LogLevel> {
+    JSON_LEVEL_FIELDS
+        .iter()
+        .filter_map(|name| json_message::field(object, name))
+        .find_map(|level| LogLevel::from_name(&level))
+}
+
+/// The level of the message which is not JSON
+pub fn detect_text(msg: &str) -> Option<LogLevel> {
+    if msg.starts_with("REPORT RequestId:") {
+        return Some(
+            if msg.contains("Status: timeout") || msg.contains("Status: error") {
+                LogLevel::Error
+            } else {
+                LogLevel::Info
+            },
+        );
+    }
+    msg.split_whitespace().take(HEAD_WORDS).find_map(|word| {
+        let bracketed = word.starts_with('[') && word.ends_with(']');
+        let name = word.trim_matches(|c: char| !c.is_ascii_alphabetic());
+        // lower case words are often a part of the sentence, e.g. "no error"
+        if bracketed || name.chars().all(|c| c.is_ascii_uppercase()) {
+            LogLevel::from_name(name)
+        } else {
+            None
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect() {
+        assert_eq!(Some(LogLevel::Error), detect("ERROR failed to connect"));
+        assert_eq!(Some(LogLevel::Warn), detect("WARN: slow query"));
+        assert_eq!(Some(LogLevel::Info), detect("[info] started"));
+        assert_eq!(
+            Some(LogLevel::Error),
+            detect("2021-01-01T00:00:00.000Z\t8f5c1b2a\tERROR\tInvoke Error")
+        );
+        assert_eq!(
+            Some(LogLevel::Debug),
+            detect("2021-01-01 00:00:00,000 [main] DEBUG App - start")
+        );
+        assert_eq!(None, detect("there is no error"));
+        assert_eq!(None, detect("done"));
+        // JSON
+        assert_eq!(
+            Some(LogLevel::Warn),
+            detect(r#"{"level": "warning", "msg": "ERROR in the message"}"#)
+        );
+        assert_eq!(
+            Some(LogLevel::Fatal),
+            detect(r#"{"log": {"level": "critical"}}"#)
+        );
+        assert_eq!(None, detect(r#"{"msg": "ERROR"}"#));
+        // Lambda
+        assert_eq!(
+            Some(LogLevel::Info),
+            detect("REPORT RequestId: 1 Duration: 1.00 ms")
+        );
+        assert_eq!(
+            Some(LogLevel::Error),
+            detect("REPORT RequestId: 1 Duration: 3000.00 ms Status: timeout")
+        );
+    }
+
+    #[test]
+    fn test_next_min_level() {
+        let mut level = None;
+        let mut levels = vec![];
+        for _ in 0..4 {
+            level = LogLevel::next_min_level(level);
+            levels.push(level);
+        }
+        assert_eq!(
+            vec![
+                Some(LogLevel::Info),
+                Some(LogLevel::Warn),
+                Some(LogLevel::Error),
+                None
+            ],
+            levels
+        );
+        assert!(LogLevel::Warn < LogLevel::Error);
+        assert_eq!("WARN", LogLevel::Warn.to_string());
+    }
+}
@@ -1,30 +1,94 @@
+use std::collections::HashSet;
+
 use log::info;
 use rusoto_logs::FilteredLogEvent;
 
 use super::constant::*;
+use crate::{
+    json_message,
+    local_filter::LocalFilter,
+    log_level::{self, LogLevel},
+};
 
 #[derive(Debug)]
 pub struct LogEvents {
     items: Vec<FilteredLogEvent>,
+    /// read from the messages of the items, at the same index as the items
+    infos: Vec<ItemInfo>,
     opened_idx: Vec<usize>,
+    /// keep the items in order of the timestamp, for the merged timeline
+    sorted: bool,
+    /// rules hiding the pushed items
+    filter: LocalFilter,
+    /// items hidden by the filter with their infos, after the number of the shown items before
+    /// them. Shown again when the filter changes.
+    hidden: Vec<(usize, FilteredLogEvent, ItemInfo)>,
+    /// fields of the JSON messages shown as the columns
+    json_fields: Vec<String>,
+    /// ids of the items, to skip the ones fetched again
+    ids: HashSet<String>,
+    /// bumped at every change of the items, e.g. to refresh the matches of the local search
+    version: u64,
+}
+
+/// What is read from the message of an item once when it is pushed
+#[derive(Debug, Clone, Default)]
+struct ItemInfo {
+    /// the level detected from the message
+    level: Option<LogLevel>,
+    /// values of the JSON fields shown as the columns, None if the message is not JSON
+    field_values: Option<Vec<String>>,
 }
 
+/// The item and its info
+type Item = (FilteredLogEvent, ItemInfo);
+
 impl LogEvents {
     pub fn new(items: Vec<FilteredLogEvent>) -> Self {
-        Self {
+        let mut log_events = Self {
+            ids: items
+                .iter()
+                .filter_map(|item| item.event_id.clone())
+                .collect(),
             items,
+            infos: vec![],
             opened_idx: vec![],
+            sorted: false,
+            filter: LocalFilter::default(),
+            hidden: vec![],
+            json_fields: JSON_FIELDS.clone(),
+            version: 0,
+        };
+        log_events.infos = log_events.parse_items(&log_events.items);
+        log_events
+    }
+
+    /// Items pushed later are merged by the timestamp instead of appended
+    pub fn sorted(items: Vec<FilteredLogEvent>) -> Self {
+        Self {
+            sorted: true,
+            ..Self::new(items)
         }
     }
 
     pub fn set_items(&mut self, items: Vec<FilteredLogEvent>) {
+        self.infos = self.parse_items(&items);
+        self.ids = items
+            .iter()
+            .filter_map(|item| item.event_id.clone())
+            .collect();
         self.items = items;
+        self.version += 1;
     }
 
     pub fn items(&self) -> &Vec<FilteredLogEvent> {
         &self.items
     }
 
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
     pub fn opened_idx(&self) -> &Vec<usize> {
         &self.opened_idx
     }
@@ -39,7 +103,135 @@ impl LogEvents {
 
     pub fn clear_items(&mut self) {
         self.items = vec![];
+        self.infos.clear();
         self.opened_idx = vec![];
+        self.hidden.clear();
+        self.ids.clear();
+        self.version += 1;
+    }
+
+    /// Take out the items and forget them, e.g. after printing them.
+    /// The items fetched again are not skipped any more.
+    pub fn take_items(&mut self) -> Vec<FilteredLogEvent> {
+        let items = std::mem::take(&mut self.items);
+        self.clear_items();
+        items
+    }
+
+    /// The level of the item detected when it is pushed
+    pub fn level(&self, idx: usize) -> Option<LogLevel> {
+        self.infos.get(idx).and_then(|info| info.level)
+    }
+
+    pub fn json_fields(&self) -> &[String] {
+        &self.json_fields
+    }
+
+    /// Read the fields of the pushed items again if the fields are changed
+    pub fn set_json_fields(&mut self, json_fields: Vec<String>) {
+        if self.json_fields == json_fields {
+            return;
+        }
+        self.json_fields = json_fields;
+        self.infos = self.parse_items(&self.items);
+        let infos = self.parse_items(self.hidden.iter().map(|(_, item, _)| item));
+        for ((_, _, info), new_info) in self.hidden.iter_mut().zip(infos) {
+            *info = new_info;
+        }
+    }
+
+    /// Values of the JSON fields of the item, in the order of the fields.
+    /// None if the message is not JSON.
+    pub fn field_values(&self, idx: usize) -> Option<&[String]> {
+        self.infos
+            .get(idx)
+            .and_then(|info| info.field_values.as_deref())
+    }
+
+    /// Detect the levels and read the JSON fields of the items, parsing each message once
+    fn parse_items<'a>(
+        &self,
+        items: impl IntoIterator<Item = &'a FilteredLogEvent>,
+    ) -> Vec<ItemInfo> {
+        let mut infos = vec![];
+        for item in items {
+            let msg = item.message.as_deref().unwrap_or_default();
+            let object = json_message::parse(msg);
+            let level = match &object {
+                Some(object) => log_level::detect_json(object),
+                None => log_level::detect_text(msg),
+            };
+            let field_values = object
+                .filter(|_| !self.json_fields.is_empty())
+                .map(|object| {
+                    self.json_fields
+                        .iter()
+                        .map(|name| json_message::field(&object, name).unwrap_or_default())
+                        .collect()
+                });
+            infos.push(ItemInfo {
+                level,
+                field_values,
+            });
+        }
+        infos
+    }
+
+    pub fn filter(&self) -> &LocalFilter {
+        &self.filter
+    }
+
+    /// Filter the pushed items again, without fetching them again.
+    /// The opened items stay opened unless they are hidden.
+    pub fn set_filter(&mut self, filter: LocalFilter) {
+        self.filter = filter;
+        let opened_ids = self.opened_ids();
+        // all the items in the order they were pushed
+        let mut items = Vec::with_capacity(self.items.len() + self.hidden.len());
+        let mut hidden = std::mem::take(&mut self.hidden).into_iter().peekable();
+        let shown = std::mem::take(&mut self.items)
+            .into_iter()
+            .zip(std::mem::take(&mut self.infos));
+        for (i, item) in shown.enumerate() {
+            while let Some((_, hidden_item, info)) = hidden.next_if(|(pos, _, _)| *pos <= i) {
+                items.push((hidden_item, info));
+            }
+            items.push(item);
+        }
+        items.extend(hidden.map(|(_, hidden_item, info)| (hidden_item, info)));
+        if self.sorted {
+            sort_items(&mut items);
+        }
+        let items = self.hide_items(items);
+        self.append_items(items);
+        self.reopen(&opened_ids);
+        self.version += 1;
+    }
+
+    /// The number of the items hidden by the filter
+    pub fn hidden_count(&self) -> usize {
+        self.hidden.len()
+    }
+
+    /// Move the items hidden by the filter aside, and return the shown ones
+    fn hide_items(&mut self, items: Vec<Item>) -> Vec<Item> {
+        let mut shown = vec![];
+        for (item, info) in items {
+            if self.filter.is_hidden(&item, info.level) {
+                self.hidden
+                    .push((self.items.len() + shown.len(), item, info));
+            } else {
+                shown.push((item, info));
+            }
+        }
+        shown
+    }
+
+    fn append_items(&mut self, items: Vec<Item>) {
+        for (item, info) in items {
+            self.items.push(item);
+            self.infos.push(info);
+        }
     }
 
     /// This method is used when pushing fetched items which possibly contains duplicate items.
@@ -77,43 +269,80 @@ impl LogEvents {
         }
     }
 
+    /// Append the items which are not pushed yet. The pages fetched again, e.g. the overlapping
+    /// windows of tail mode, can have the pushed items anywhere.
     pub fn push_items(&mut self, items: &mut Vec<FilteredLogEvent>, open_all: bool) {
+        self.skip_pushed_items(items);
+        // the infos of the hidden items are kept for the other filters
+        let infos = self.parse_items(items.iter());
+        let items = self.hide_items(items.drain(..).zip(infos).collect());
+        if items.is_empty() {
+            return;
+        }
+        self.version += 1;
+        if self.sorted {
+            self.merge_items(items, open_all);
+            return;
+        }
         info!("push items --");
         info!("before: {:?}", self.items);
         info!("items to push: {:?}", items);
-        let mut idx: Option<usize> = None;
-        // Skip the duplicate items.
-        for (i, val) in items.iter().enumerate() {
-            let mut found = false;
-            for v in self.items.iter() {
-                if val.event_id == v.event_id {
-                    found = true;
-                    break;
-                }
-            }
-            if !found {
-                idx = Some(i);
-                break;
-            }
-        }
-        if self.items.is_empty() {
-            idx = Some(0);
-        }
-        if let Some(idx) = idx {
-            let idx = idx;
-            let current_len = self.items.len();
-            let mut items_to_push = items.split_off(idx);
-            let push_len = items_to_push.len();
-            self.items.append(&mut items_to_push);
-            if open_all {
-                for j in current_len..current_len + push_len {
-                    self.toggle_select(j);
-                }
+        let current_len = self.items.len();
+        self.append_items(items);
+        if open_all {
+            for j in current_len..self.items.len() {
+                self.toggle_select(j);
             }
         }
         info!("after: {:?}", self.items);
     }
 
+    /// Drop the items already pushed or repeated in the items, and remember the rest.
+    /// The items without the id cannot be told apart, they are always kept.
+    fn skip_pushed_items(&mut self, items: &mut Vec<FilteredLogEvent>) {
+        let ids = &mut self.ids;
+        items.retain(|item| match &item.event_id {
+            Some(id) => ids.insert(id.clone()),
+            None => true,
+        });
+    }
+
+    /// Insert the items in order of the timestamp, skipping the ones already pushed.
+    /// The pages of tail mode in the merged timeline overlap in time, the next pages of the other
+    /// fetches follow the pushed items.
+    fn merge_items(&mut self, items: Vec<Item>, open_all: bool) {
+        let mut opened_ids = self.opened_ids();
+        if open_all {
+            opened_ids.extend(items.iter().map(|(item, _)| item.event_id.clone()));
+        }
+        let mut merged: Vec<Item> = std::mem::take(&mut self.items)
+            .into_iter()
+            .zip(std::mem::take(&mut self.infos))
+            .chain(items)
+            .collect();
+        sort_items(&mut merged);
+        self.append_items(merged);
+        self.reopen(&opened_ids);
+    }
+
+    fn opened_ids(&self) -> Vec<Option<String>> {
+        self.opened_idx
+            .iter()
+            .filter_map(|i| self.items.get(*i).map(|e| e.event_id.clone()))
+            .collect()
+    }
+
+    /// Open the items by the ids after the items are moved
+    fn reopen(&mut self, opened_ids: &[Option<String>]) {
+        self.opened_idx = self
+            .items
+            .iter()
+            .enumerate()
+            .filter(|(_, e)| opened_ids.contains(&e.event_id))
+            .map(|(i, _)| i)
+            .collect();
+    }
+
     pub fn has_items(&self) -> bool {
         !self.items.is_empty()
     }
@@ -129,10 +358,18 @@ impl LogEvents {
     }
 }
 
+/// Stable sort by timestamp like `sort_events` of the merged timeline, with the infos
+fn sort_items(items: &mut [Item]) {
+    items.sort_by_key(|(item, _)| item.timestamp.unwrap_or_default());
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::test_helper::get_events;
+    use crate::{
+        local_filter::FilterRule,
+        test_helper::{get_events, make_log_events},
+    };
 
     #[test]
     fn test_set_items() {
@@ -161,6 +398,16 @@ mod tests {
         assert_eq!(expected, log_events.items);
     }
 
+    #[test]
+    fn test_take_items() {
+        let mut log_events = LogEvents::new(vec![]);
+        log_events.push_items(&mut make_log_events(1, 2, 0), true);
+        assert_eq!(make_log_events(1, 2, 0), log_events.take_items());
+        assert!(!log_events.has_items());
+        assert!(log_events.opened_idx().is_empty());
+        assert!(log_events.take_items().is_empty());
+    }
+
     #[test]
     fn test_is_same() {
         // same length
@@ -203,5 +450,192 @@ mod tests {
         for (i, val) in log_events.items.iter().enumerate() {
             assert_eq!(expected.items.get(i).unwrap(), val);
         }
+
+        // the pushed items anywhere in the overlapping page, and repeated in the page
+        let mut log_events = LogEvents::new(vec![]);
+        log_events.push_items(&mut get_events(1, 4), true);
+        let mut events = get_events(2, 6);
+        events.swap(0, 3);
+        events.push(events[0].clone());
+        log_events.push_items(&mut events, true);
+        assert_eq!(
+            vec!["1", "2", "3", "5", "4"],
+            log_events
+                .items()
+                .iter()
+                .map(|e| e.event_id.clone().unwrap())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(5, log_events.opened_idx().len());
+        // nothing changed by the pushed items only
+        let version = log_events.version();
+        log_events.push_items(&mut get_events(1, 4), true);
+        assert_eq!(version, log_events.version());
+        // pushed again after cleared
+        log_events.clear_items();
+        log_events.push_items(&mut get_events(1, 2), false);
+        assert_eq!(1, log_events.items().len());
+        // the items without the id are all kept
+        log_events.push_items(&mut vec![FilteredLogEvent::default(); 2], false);
+        log_events.push_items(&mut vec![FilteredLogEvent::default()], false);
+        assert_eq!(4, log_events.items().len());
+    }
+
+    #[test]
+    fn test_push_items_filtered() {
+        let mut log_events = LogEvents::new(vec![]);
+        let mut filter = LocalFilter::default();
+        filter.push_rule(FilterRule::parse("-/event_[13]$/").unwrap());
+        log_events.set_filter(filter);
+        // log_event_0 .. log_event_3
+        log_events.push_items(&mut make_log_events(0, 3, 0), false);
+        assert_eq!(2, log_events.items().len());
+        assert_eq!(2, log_events.hidden_count());
+        // fetched again in tail mode
+        log_events.push_items(&mut make_log_events(0, 4, 0), false);
+        assert_eq!(
+            vec![Some(0), Some(2000), Some(4000)],
+            log_events
+                .items()
+                .iter()
+                .map(|e| e.timestamp)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(2, log_events.hidden_count());
+        log_events.clear_items();
+        assert_eq!(0, log_events.hidden_count());
+        assert_eq!(1, log_events.filter().rules().len());
+    }
+
+    #[test]
+    fn test_set_filter() {
+        let timestamps = |log_events: &LogEvents| {
+            log_events
+                .items()
+                .iter()
+                .map(|e| e.timestamp.unwrap_or_default() / 1000)
+                .collect::<Vec<_>>()
+        };
+        let mut log_events = LogEvents::new(vec![]);
+        let mut filter = LocalFilter::default();
+        filter.push_rule(FilterRule::parse("-/event_[013]$/").unwrap());
+        log_events.set_filter(filter);
+        // log_event_0 .. log_event_4
+        log_events.push_items(&mut make_log_events(0, 2, 0), false);
+        log_events.push_items(&mut make_log_events(3, 4, 0), false);
+        log_events.toggle_select(1);
+        assert_eq!(vec![2, 4], timestamps(&log_events));
+        // the hidden items are shown again in the pushed order
+        let version = log_events.version();
+        log_events.set_filter(LocalFilter::default());
+        assert_eq!(vec![0, 1, 2, 3, 4], timestamps(&log_events));
+        assert_eq!(0, log_events.hidden_count());
+        assert_eq!(&vec![4], log_events.opened_idx());
+        assert_ne!(version, log_events.version());
+        let mut filter = LocalFilter::default();
+        filter.push_rule(FilterRule::parse("-/event_[24]$/").unwrap());
+        log_events.set_filter(filter);
+        assert_eq!(vec![0, 1, 3], timestamps(&log_events));
+        assert_eq!(2, log_events.hidden_count());
+        assert!(log_events.opened_idx().is_empty());
+        // closed while hidden
+        log_events.set_filter(LocalFilter::default());
+        assert_eq!(vec![0, 1, 2, 3, 4], timestamps(&log_events));
+        assert!(log_events.opened_idx().is_empty());
+    }
+
+    #[test]
+    fn test_level() {
+        let mut log_events = LogEvents::new(vec![]);
+        let mut items = make_log_events(0, 2, 0);
+        items[1].message = Some(String::from("ERROR timeout"));
+        log_events.push_items(&mut items, false);
+        assert_eq!(None, log_events.level(0));
+        assert_eq!(Some(LogLevel::Error), log_events.level(1));
+        assert_eq!(None, log_events.level(3));
+        log_events.clear_items();
+        assert_eq!(None, log_events.level(1));
+
+        // the events of the live tail have no ids
+        let mut items = vec![
+            FilteredLogEvent {
+                message: Some(String::from("ERROR timeout")),
+                ..Default::default()
+            },
+            FilteredLogEvent {
+                message: Some(String::from("INFO started")),
+                ..Default::default()
+            },
+        ];
+        log_events.push_items(&mut items, false);
+        assert_eq!(Some(LogLevel::Error), log_events.level(0));
+        assert_eq!(Some(LogLevel::Info), log_events.level(1));
+    }
+
+    #[test]
+    fn test_field_values() {
+        let mut log_events = LogEvents::new(vec![]);
+        let mut items = make_log_events(0, 1, 0);
+        items[1].message = Some(String::from(
+            r#"{"level": "error", "http": {"status": 500}}"#,
+        ));
+        log_events.push_items(&mut items, false);
+        assert_eq!(None, log_events.field_values(1));
+        log_events.set_json_fields(vec![String::from("http.status"), String::from("msg")]);
+        assert_eq!(None, log_events.field_values(0));
+        assert_eq!(
+            Some(&[String::from("500"), String::default()][..]),
+            log_events.field_values(1)
+        );
+        assert_eq!(Some(LogLevel::Error), log_events.level(1));
+        log_events.clear_items();
+        assert_eq!(None, log_events.field_values(1));
+
+        // the events of the live tail have no ids
+        let mut items = vec![
+            FilteredLogEvent {
+                message: Some(String::from(r#"{"http": {"status": 500}}"#)),
+                ..Default::default()
+            },
+            FilteredLogEvent {
+                message: Some(String::from(r#"{"http": {"status": 200}}"#)),
+                ..Default::default()
+            },
+        ];
+        log_events.push_items(&mut items, false);
+        assert_eq!(
+            Some(&[String::from("500"), String::default()][..]),
+            log_events.field_values(0)
+        );
+        assert_eq!(
+            Some(&[String::from("200"), String::default()][..]),
+            log_events.field_values(1)
+        );
+    }
+
+    #[test]
+    fn test_push_items_sorted() {
+        let mut log_events = LogEvents::sorted(vec![]);
+        let mut events = make_log_events(0, 1, 0);
+        log_events.push_items(&mut events, true);
+        log_events.toggle_select(0);
+        // 0: 0s, 1: 1s, 2: 2s
+        let mut events = make_log_events(1, 2, 0);
+        events[0].event_id = Some(String::from("other"));
+        events[0].timestamp = Some(500);
+        log_events.push_items(&mut events, false);
+        assert_eq!(
+            vec![Some(0), Some(500), Some(1000), Some(2000)],
+            log_events
+                .items()
+                .iter()
+                .map(|e| e.timestamp)
+                .collect::<Vec<_>>()
+        );
+        // the opened event is kept opened
+        assert_eq!(&vec![2], log_events.opened_idx());
+        // duplicate
+        log_events.push_items(&mut make_log_events(0, 2, 0), false);
+        assert_eq!(4, log_events.items().len());
     }
 }
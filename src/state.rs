@@ -1,4 +1,5 @@
 pub mod logevents_state;
 pub mod loggroups_state;
+pub mod search_history;
 pub mod search_state;
 pub mod status_bar_state;
@@ -1,4 +1,13 @@
-use crate::logevents::*;
+use std::time::Duration;
+
+use crate::{
+    client::retry::{self, ErrorKind},
+    context::EventContext,
+    export::ExportProgress,
+    insights::InsightsResults,
+    local_filter::LocalFilter,
+    logevents::*,
+};
 
 use tui::widgets::TableState;
 
@@ -9,6 +18,22 @@ pub struct LogEventsState {
     pub is_fetching: bool,
     pub current_log_group: Option<String>,
     pub state: TableState,
+    /// results of the Logs Insights query, shown instead of the events if exists
+    pub insights: Option<InsightsResults>,
+    /// log stream names of the log group, the most recently active first
+    pub log_streams: Vec<String>,
+    pub is_fetching_log_streams: bool,
+    pub export_progress: Option<ExportProgress>,
+    /// events around the hit shown by the context view
+    pub context: Option<EventContext>,
+    /// the last failure of fetching the log events, cleared by the next fetch
+    pub error: Option<String>,
+    /// the kind of the last failure, `megane tail` stops only at the permanent ones
+    pub error_kind: Option<ErrorKind>,
+    /// interval of the fetches in tail mode, longer while throttled
+    pub tail_interval: Option<Duration>,
+    /// tail mode is fed by the live tail session instead of polling
+    pub live_tail: bool,
 }
 
 impl LogEventsState {
@@ -19,20 +44,90 @@ impl LogEventsState {
             is_fetching: false,
             current_log_group: None,
             state: TableState::default(),
+            insights: None,
+            log_streams: vec![],
+            is_fetching_log_streams: false,
+            export_progress: None,
+            context: None,
+            error: None,
+            error_kind: None,
+            tail_interval: None,
+            live_tail: false,
+        }
+    }
+
+    /// State of the merged timeline, whose events are kept in order of the timestamp
+    pub fn merged() -> Self {
+        LogEventsState {
+            events: LogEvents::sorted(vec![]),
+            ..Self::new()
         }
     }
 
+    /// Record the failure of the fetch, so that the pane can show it and the user can retry
+    pub fn set_error(&mut self, e: &anyhow::Error) {
+        self.error = Some(e.to_string());
+        self.error_kind = Some(retry::classify(e));
+        self.is_fetching = false;
+        self.is_fetching_log_streams = false;
+    }
+
     pub fn reset(&mut self) {
         self.events.clear_items();
         self.state = TableState::default();
         self.next_token = None;
+        self.insights = None;
+        self.error = None;
+        self.error_kind = None;
+    }
+
+    /// Filter the fetched events again. The cursor stays on the selected event if it is still
+    /// shown, otherwise it is kept in the rows.
+    pub fn set_filter(&mut self, filter: LocalFilter) {
+        let selected = self.state.selected();
+        let selected_id = selected
+            .and_then(|i| self.events.items().get(i))
+            .map(|e| e.event_id.clone());
+        self.events.set_filter(filter);
+        let idx = match selected_id {
+            Some(id) => self
+                .events
+                .items()
+                .iter()
+                .position(|e| e.event_id == id)
+                .or_else(|| selected.map(|i| i.min(self.events.items().len()))),
+            None => selected,
+        };
+        self.state.select(idx);
+    }
+
+    /// number of rows shown in the table
+    fn rows_len(&self) -> usize {
+        if let Some(insights) = &self.insights {
+            insights.rows().len()
+        } else {
+            self.events.items().len()
+        }
+    }
+
+    fn has_rows(&self) -> bool {
+        self.rows_len() > 0
+    }
+
+    /// The message of the event, or the tab separated values of the Logs Insights result.
+    pub fn get_message(&self, idx: usize) -> Option<String> {
+        if let Some(insights) = &self.insights {
+            insights.rows().get(idx).map(|row| row.join("\t"))
+        } else {
+            self.events.get_message(idx)
+        }
     }
 
     pub fn next(&mut self) {
         match self.state.selected() {
             Some(s) => {
-                if self.events.has_items() {
-                    if s <= self.events.items().len() {
+                if self.has_rows() {
+                    if s <= self.rows_len() {
                         self.state.select(Some(s.saturating_add(1)));
                     }
                 } else {
@@ -40,7 +135,7 @@ impl LogEventsState {
                 }
             }
             None => {
-                if self.events.has_items() {
+                if self.has_rows() {
                     self.state.select(Some(0));
                 } else {
                     self.state.select(None);
@@ -52,10 +147,10 @@ impl LogEventsState {
     pub fn next_by(&mut self, step: usize) {
         match self.state.selected() {
             Some(s) => {
-                if self.events.has_items() {
+                if self.has_rows() {
                     let mut next_p = s + step;
-                    if next_p > self.events.items().len() + 1 {
-                        next_p = self.events.items().len() + 1;
+                    if next_p > self.rows_len() + 1 {
+                        next_p = self.rows_len() + 1;
                     }
                     self.state.select(Some(next_p));
                 } else {
@@ -63,10 +158,10 @@ impl LogEventsState {
                 }
             }
             None => {
-                if self.events.has_items() {
+                if self.has_rows() {
                     let mut next_p = step - 1;
-                    if next_p > self.events.items().len() {
-                        next_p = self.events.items().len();
+                    if next_p > self.rows_len() {
+                        next_p = self.rows_len();
                     }
                     self.state.select(Some(next_p));
                 } else {
@@ -103,7 +198,7 @@ impl LogEventsState {
     }
 
     pub fn cursor_last(&mut self) {
-        self.state.select(Some(self.events.items().len()));
+        self.state.select(Some(self.rows_len()));
     }
 
     pub fn need_more_fetching(&self) -> bool {
@@ -127,7 +222,7 @@ mod tests {
     use super::*;
     use rusoto_logs::FilteredLogEvent;
 
-    use crate::test_helper::*;
+    use crate::{local_filter::FilterRule, test_helper::*};
 
     #[test]
     fn test_new() {
@@ -147,6 +242,45 @@ mod tests {
         assert!(state.events.is_same(&expected.events));
     }
 
+    #[test]
+    fn test_set_error() {
+        let mut state = LogEventsState {
+            is_fetching: true,
+            is_fetching_log_streams: true,
+            ..Default::default()
+        };
+        state.set_error(&anyhow::anyhow!("ThrottlingException"));
+        assert_eq!(Some(String::from("ThrottlingException")), state.error);
+        assert_eq!(Some(ErrorKind::Throttled), state.error_kind);
+        assert!(!state.is_fetching);
+        assert!(!state.is_fetching_log_streams);
+        state.reset();
+        assert_eq!(None, state.error);
+        assert_eq!(None, state.error_kind);
+    }
+
+    #[test]
+    fn test_set_filter() {
+        let mut state = LogEventsState::default();
+        state
+            .events
+            .push_items(&mut make_log_events(0, 3, 0), false);
+        state.state.select(Some(2));
+        let mut filter = LocalFilter::default();
+        filter.push_rule(FilterRule::parse("-/event_[01]$/").unwrap());
+        state.set_filter(filter);
+        // on the same event
+        assert_eq!(Some(0), state.state.selected());
+        // the selected event is hidden, kept at the same row
+        state.state.select(Some(1));
+        let mut filter = LocalFilter::default();
+        filter.push_rule(FilterRule::parse("-/event_[23]$/").unwrap());
+        state.set_filter(filter);
+        assert_eq!(Some(1), state.state.selected());
+        state.set_filter(LocalFilter::default());
+        assert_eq!(Some(1), state.state.selected());
+    }
+
     #[test]
     fn test_next() {
         let mut state = LogEventsState::default();
@@ -221,6 +355,27 @@ mod tests {
         assert_eq!(Some(0), state.state.selected());
     }
 
+    #[test]
+    fn test_insights_rows() {
+        let mut state = LogEventsState {
+            events: LogEvents::new(make_log_events(0, 5, 0)),
+            insights: Some(InsightsResults::new(vec![
+                vec![
+                    (String::from("a"), String::from("1")),
+                    (String::from("b"), String::from("2")),
+                ],
+                vec![(String::from("a"), String::from("3"))],
+            ])),
+            ..Default::default()
+        };
+        state.cursor_last();
+        assert_eq!(Some(2), state.state.selected());
+        assert_eq!(Some(String::from("1\t2")), state.get_message(0));
+        state.reset();
+        assert!(state.insights.is_none());
+        assert_eq!(None, state.get_message(0));
+    }
+
     #[test]
     fn test_need_more_fetching() {
         let mut state = LogEventsState::default();
@@ -8,8 +8,12 @@ pub struct LogGroupsState {
     pub log_groups: LogGroups,
     filtered_log_groups: LogGroups,
     pub is_fetching: bool,
+    /// the failure of the last fetch, shown in the status bar until the log groups are fetched again
+    pub error: Option<String>,
     pub selection: Vec<usize>,
     pub state: ListState,
+    /// log group names to select after the log groups are fetched
+    pending_selection: Vec<String>,
 }
 
 impl LogGroupsState {
@@ -18,8 +22,10 @@ impl LogGroupsState {
             log_groups: LogGroups::new(vec![]),
             filtered_log_groups: LogGroups::new(vec![]),
             is_fetching: false,
+            error: None,
             selection: vec![],
             state: ListState::default(),
+            pending_selection: vec![],
         }
     }
 
@@ -46,7 +52,10 @@ impl LogGroupsState {
         query: &str,
         exc: &[String],
     ) -> (Vec<ListItem<'_>>, ListState) {
-        let selected_gnames = self.get_selected_log_group_names();
+        let mut selected_gnames = self.get_selected_log_group_names();
+        if self.log_groups.has_items() {
+            selected_gnames.append(&mut self.pending_selection);
+        }
         self.query_log_groups(query, exc);
         self.update_selections(&selected_gnames);
         let items = self
@@ -95,6 +104,13 @@ impl LogGroupsState {
         }
     }
 
+    /// Replace the selection with the log groups of the names, e.g. by the workspace.
+    /// They are selected when they are fetched.
+    pub fn select_later(&mut self, gnames: Vec<String>) {
+        self.selection.clear();
+        self.pending_selection = gnames;
+    }
+
     pub fn get_selected_log_group_names(&self) -> Vec<String> {
         let mut result = vec![];
         self.selection.iter().for_each(|item| {
@@ -198,6 +214,29 @@ mod tests {
         assert_eq!(Some(3), state.state.selected());
     }
 
+    #[test]
+    fn test_select_later() {
+        let mut state = LogGroupsState::default();
+        state.select_later(vec!["log_group_1".to_string()]);
+        // not fetched yet
+        let _ = state.get_list_items("", &[]);
+        assert!(state.selection.is_empty());
+        state.log_groups = LogGroups::new(make_log_groups(0, 3));
+        let (res_item, _) = state.get_list_items("", &[]);
+        assert_eq!(ListItem::new("[X]log_group_1"), res_item[1]);
+        assert_eq!(
+            vec!["log_group_1".to_string()],
+            state.get_selected_log_group_names()
+        );
+        // replaced, e.g. by switching the tab
+        state.select_later(vec!["log_group_2".to_string()]);
+        let _ = state.get_list_items("", &[]);
+        assert_eq!(
+            vec!["log_group_2".to_string()],
+            state.get_selected_log_group_names()
+        );
+    }
+
     #[test]
     fn test_select() {
         let mut state = LogGroupsState::default();
@@ -208,10 +247,14 @@ mod tests {
         state.select(2);
         state.select(3);
         state.select(4);
-        // don't add idx 5 because MAX_LOG_SELECTION is 4
-        state.select(5);
         let expect = vec![0, 1, 3, 4];
         assert_eq!(expect, state.selection);
+        // don't add idx over MAX_LOG_GROUP_SELECTION
+        for i in 5..(*MAX_LOG_GROUP_SELECTION + 2) {
+            state.select(i);
+        }
+        assert_eq!(*MAX_LOG_GROUP_SELECTION, state.selection.len());
+        assert!(!state.selection.contains(&(*MAX_LOG_GROUP_SELECTION + 1)));
     }
 
     #[test]
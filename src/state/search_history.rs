@@ -0,0 +1,195 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::{constant, state::search_state::SearchState};
+
+/// `~/.local/share/megane/history.json`
+pub fn default_path() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("megane").join("history.json"))
+}
+
+/// Search conditions used in each log group, and the ones saved with a name.
+/// Written to the file at every change if the path is provided.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SearchHistory {
+    /// log group name -> conditions, the newest last
+    #[serde(default)]
+    history: BTreeMap<String, Vec<SearchState>>,
+    /// log group name -> name -> condition
+    #[serde(default)]
+    saved: BTreeMap<String, BTreeMap<String, SearchState>>,
+    #[serde(skip)]
+    path: Option<PathBuf>,
+}
+
+impl SearchHistory {
+    /// Read the history file. The empty history is returned if the file does not exist.
+    pub fn load(path: &Path) -> Result<Self> {
+        let mut history = if path.exists() {
+            serde_json::from_str(&fs::read_to_string(path)?)?
+        } else {
+            SearchHistory::default()
+        };
+        history.path = Some(path.to_path_buf());
+        Ok(history)
+    }
+
+    /// Start a new history at the path, e.g. when the file is broken or written by an old version.
+    /// The file is moved aside to `history.json.bak` rather than overwritten.
+    pub fn reset(path: &Path) -> Result<Self> {
+        if path.exists() {
+            fs::rename(path, path.with_extension("json.bak"))?;
+        }
+        Ok(SearchHistory {
+            path: Some(path.to_path_buf()),
+            ..Default::default()
+        })
+    }
+
+    pub fn history(&self, log_group_name: &str) -> &[SearchState] {
+        self.history
+            .get(log_group_name)
+            .map(|h| h.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Add the condition as the newest one. The same condition used before is moved to the end.
+    pub fn push(&mut self, log_group_name: &str, state: SearchState) {
+        let history = self.history.entry(log_group_name.to_string()).or_default();
+        history.retain(|s| s != &state);
+        history.push(state);
+        let overflow = history.len().saturating_sub(*constant::SEARCH_HISTORY_SIZE);
+        history.drain(..overflow);
+        self.save();
+    }
+
+    pub fn saved(&self, log_group_name: &str) -> Vec<(&String, &SearchState)> {
+        self.saved
+            .get(log_group_name)
+            .map(|saved| saved.iter().collect())
+            .unwrap_or_default()
+    }
+
+    /// Save the condition with the name. The condition of the same name is replaced.
+    pub fn save_as(&mut self, log_group_name: &str, name: &str, state: SearchState) {
+        self.saved
+            .entry(log_group_name.to_string())
+            .or_default()
+            .insert(name.to_string(), state);
+        self.save();
+    }
+
+    pub fn remove_saved(&mut self, log_group_name: &str, name: &str) {
+        if let Some(saved) = self.saved.get_mut(log_group_name) {
+            saved.remove(name);
+            if saved.is_empty() {
+                self.saved.remove(log_group_name);
+            }
+        }
+        self.save();
+    }
+
+    fn save(&self) {
+        if let Some(path) = &self.path {
+            if let Err(e) = self.write(path) {
+                log::warn!("Failed to write search history {}: {}", path.display(), e);
+            }
+        }
+    }
+
+    fn write(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::search_state::SearchMode;
+
+    fn state(query: &str) -> SearchState {
+        SearchState::new(query.to_string(), SearchMode::OneHour)
+    }
+
+    #[test]
+    fn test_push() {
+        let mut history = SearchHistory::default();
+        history.push("group", state("a"));
+        history.push("group", state("b"));
+        history.push("group", state("a"));
+        history.push("other", state("c"));
+        assert_eq!(&[state("b"), state("a")], history.history("group"));
+        assert_eq!(&[state("c")], history.history("other"));
+        assert!(history.history("unknown").is_empty());
+        // the oldest ones are dropped
+        for i in 0..*constant::SEARCH_HISTORY_SIZE {
+            history.push("group", state(&i.to_string()));
+        }
+        assert_eq!(
+            *constant::SEARCH_HISTORY_SIZE,
+            history.history("group").len()
+        );
+        assert_eq!(state("0"), history.history("group")[0]);
+    }
+
+    #[test]
+    fn test_saved() {
+        let mut history = SearchHistory::default();
+        history.save_as("group", "errors", state("ERROR"));
+        history.save_as("group", "5xx", state("500"));
+        history.save_as("group", "errors", state("error"));
+        assert_eq!(
+            vec![
+                (&String::from("5xx"), &state("500")),
+                (&String::from("errors"), &state("error"))
+            ],
+            history.saved("group")
+        );
+        history.remove_saved("group", "5xx");
+        history.remove_saved("group", "errors");
+        assert!(history.saved("group").is_empty());
+        assert_eq!(SearchHistory::default(), history);
+    }
+
+    #[test]
+    fn test_load() {
+        let path = std::env::temp_dir()
+            .join("megane_test_search_history")
+            .join("history.json");
+        let _ = fs::remove_file(&path);
+        let mut history = SearchHistory::load(&path).unwrap();
+        history.push("group", state("a"));
+        history.save_as("group", "errors", state("ERROR"));
+        let loaded = SearchHistory::load(&path).unwrap();
+        assert_eq!(history, loaded);
+        assert_eq!(&[state("a")], loaded.history("group"));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_reset() {
+        let dir = std::env::temp_dir().join("megane_test_search_history_reset");
+        let path = dir.join("history.json");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(&path, "{\"history\": 1}").unwrap();
+        assert!(SearchHistory::load(&path).is_err());
+        let mut history = SearchHistory::reset(&path).unwrap();
+        assert_eq!(
+            "{\"history\": 1}",
+            fs::read_to_string(dir.join("history.json.bak")).unwrap()
+        );
+        history.push("group", state("a"));
+        assert_eq!(history, SearchHistory::load(&path).unwrap());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,8 +1,12 @@
 use std::fmt::{Display, Formatter, Result};
 
-use chrono::{Duration, NaiveDateTime, Utc};
+use anyhow::anyhow;
+use chrono::{Duration, Local, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, PartialEq)]
+use crate::{constant, time_input};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum SearchMode {
     Tail,
     OneMinute,
@@ -38,6 +42,39 @@ impl SearchMode {
             SearchMode::FromTo(from, to) => (*from, *to),
         }
     }
+
+    /// Parse the relative range (tail, 1m, 30m, 1h, 12h) or the absolute range, whose times are
+    /// parsed like the custom term of the search dialog (`time_input::parse`).
+    /// The relative range takes priority.
+    pub fn parse(
+        range: Option<&str>,
+        from: Option<&str>,
+        to: Option<&str>,
+    ) -> anyhow::Result<SearchMode> {
+        match range {
+            Some("tail") => Ok(SearchMode::Tail),
+            Some("1m") => Ok(SearchMode::OneMinute),
+            Some("30m") => Ok(SearchMode::ThirtyMinutes),
+            Some("1h") => Ok(SearchMode::OneHour),
+            Some("12h") => Ok(SearchMode::TwelveHours),
+            Some(r) => Err(anyhow!("unknown range: {}", r)),
+            None => {
+                let now = Local::now();
+                let parse = |label: &str, input: &str| {
+                    time_input::parse(input, now, &constant::DATE_FORMAT)
+                        .map_err(|e| anyhow!("{}: {}", label, e))
+                };
+                let from = from.map(|input| parse("from", input)).transpose()?;
+                let to = to.map(|input| parse("to", input)).transpose()?;
+                if let (Some(f), Some(t)) = (from, to) {
+                    if f > t {
+                        return Err(anyhow!("from is after to"));
+                    }
+                }
+                Ok(SearchMode::FromTo(from, to))
+            }
+        }
+    }
 }
 
 impl Display for SearchMode {
@@ -75,15 +112,58 @@ impl Display for SearchMode {
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+/// how the query is interpreted
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum QueryMode {
+    /// filter pattern for FilterLogEvents
+    Filter,
+    /// Logs Insights query
+    Insights,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SearchState {
     pub query: String,
     pub mode: SearchMode,
+    pub query_mode: QueryMode,
+    /// log streams to search, all log streams if empty
+    #[serde(default)]
+    pub log_stream_names: Vec<String>,
+    /// prefix of the log streams to search, ignored if log_stream_names is not empty
+    #[serde(default)]
+    pub log_stream_name_prefix: Option<String>,
 }
 
 impl SearchState {
     pub fn new(query: String, mode: SearchMode) -> Self {
-        SearchState { query, mode }
+        SearchState {
+            query,
+            mode,
+            query_mode: QueryMode::Filter,
+            log_stream_names: vec![],
+            log_stream_name_prefix: None,
+        }
+    }
+
+    pub fn is_insights(&self) -> bool {
+        self.query_mode == QueryMode::Insights
+    }
+
+    /// true if the search is narrowed down by log streams
+    pub fn has_log_stream_filter(&self) -> bool {
+        !self.log_stream_names.is_empty() || self.log_stream_name_prefix.is_some()
+    }
+
+    /// Check if the log stream is a target of the search.
+    /// FilterLogEvents does not accept both of names and prefix, so names take priority.
+    pub fn is_target_log_stream(&self, log_stream_name: Option<&String>) -> bool {
+        if !self.log_stream_names.is_empty() {
+            matches!(log_stream_name, Some(name) if self.log_stream_names.contains(name))
+        } else if let Some(prefix) = &self.log_stream_name_prefix {
+            matches!(log_stream_name, Some(name) if name.starts_with(prefix.as_str()))
+        } else {
+            true
+        }
     }
 }
 
@@ -140,6 +220,64 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            SearchMode::OneHour,
+            SearchMode::parse(Some("1h"), Some("2021-01-01 00:00:00"), None).unwrap()
+        );
+        assert!(SearchMode::parse(Some("2d"), None, None).is_err());
+        assert_eq!(
+            SearchMode::Tail,
+            SearchMode::parse(Some("tail"), None, None).unwrap()
+        );
+        assert_eq!(
+            SearchMode::FromTo(None, None),
+            SearchMode::parse(None, None, None).unwrap()
+        );
+        match SearchMode::parse(
+            None,
+            Some("2021-01-01 00:00:00"),
+            Some("2021-01-02 00:00:00"),
+        )
+        .unwrap()
+        {
+            SearchMode::FromTo(Some(from), Some(to)) => assert_eq!(24 * 60 * 60 * 1000, to - from),
+            mode => panic!("unexpected mode: {:?}", mode),
+        }
+        // the same inputs as the custom term
+        assert!(matches!(
+            SearchMode::parse(None, Some("yesterday"), Some("-15m")).unwrap(),
+            SearchMode::FromTo(Some(_), Some(_))
+        ));
+        assert_eq!(
+            SearchMode::FromTo(Some(1609459200000), None),
+            SearchMode::parse(None, Some("2021-01-01T00:00:00Z"), None).unwrap()
+        );
+        assert!(SearchMode::parse(None, Some("someday"), None).is_err());
+        assert!(SearchMode::parse(None, Some("now"), Some("1h ago")).is_err());
+    }
+
+    #[test]
+    fn test_is_target_log_stream() {
+        let stream_1 = String::from("2021/01/01/[$LATEST]aaa");
+        let stream_2 = String::from("2021/01/02/[$LATEST]bbb");
+        let mut state = SearchState::default();
+        assert!(!state.has_log_stream_filter());
+        assert!(state.is_target_log_stream(Some(&stream_1)));
+        assert!(state.is_target_log_stream(None));
+        // prefix
+        state.log_stream_name_prefix = Some(String::from("2021/01/02"));
+        assert!(state.has_log_stream_filter());
+        assert!(!state.is_target_log_stream(Some(&stream_1)));
+        assert!(state.is_target_log_stream(Some(&stream_2)));
+        assert!(!state.is_target_log_stream(None));
+        // names take priority over prefix
+        state.log_stream_names = vec![stream_1.clone()];
+        assert!(state.is_target_log_stream(Some(&stream_1)));
+        assert!(!state.is_target_log_stream(Some(&stream_2)));
+    }
+
     #[test]
     fn test_fmt() {
         test_mode_format(SearchMode::Tail, "Tail");
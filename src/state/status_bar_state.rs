@@ -1,11 +1,19 @@
 #[derive(PartialEq, Debug)]
 pub struct StatusBarState {
     pub message: String,
+    /// progress of the long running task (e.g. export), shown before the message
+    pub progress: Option<String>,
+    /// failures of fetching the log events, shown in red before the progress
+    pub error: Option<String>,
 }
 
 impl StatusBarState {
     pub fn new(message: String) -> Self {
-        StatusBarState { message }
+        StatusBarState {
+            message,
+            progress: None,
+            error: None,
+        }
     }
 }
 
@@ -18,6 +26,8 @@ mod tests {
         let result = StatusBarState::new(String::from("test message"));
         let expect = StatusBarState {
             message: String::from("test message"),
+            progress: None,
+            error: None,
         };
         assert_eq!(expect, result);
     }
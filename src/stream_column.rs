@@ -0,0 +1,214 @@
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+};
+
+use anyhow::{anyhow, Result};
+
+use tui::{
+    layout::Constraint,
+    style::{Color, Style},
+    widgets::Cell,
+};
+
+/// width of the truncated log stream name
+const TRUNCATED_WIDTH: usize = 16;
+/// width of the colour tag ("■" and 6 hex digits)
+const TAG_WIDTH: u16 = 7;
+const TAG_COLORS: [Color; 12] = [
+    Color::Red,
+    Color::Green,
+    Color::Yellow,
+    Color::Blue,
+    Color::Magenta,
+    Color::Cyan,
+    Color::LightRed,
+    Color::LightGreen,
+    Color::LightYellow,
+    Color::LightBlue,
+    Color::LightMagenta,
+    Color::LightCyan,
+];
+
+/// How the log stream name is shown in the event table
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StreamColumn {
+    Hidden,
+    /// the whole log stream name
+    Full,
+    /// the end of the log stream name, which is unique in most cases (e.g. Lambda, ECS)
+    Truncated,
+    /// short tag coloured by the hash of the log stream name
+    Tag,
+}
+
+impl StreamColumn {
+    /// full, truncated, hashed or hidden, as in the config
+    pub fn from_name(name: &str) -> Result<Self> {
+        match name {
+            "full" => Ok(StreamColumn::Full),
+            "truncated" => Ok(StreamColumn::Truncated),
+            "hashed" => Ok(StreamColumn::Tag),
+            "hidden" => Ok(StreamColumn::Hidden),
+            _ => Err(anyhow!("unknown stream column: {}", name)),
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            StreamColumn::Hidden => StreamColumn::Full,
+            StreamColumn::Full => StreamColumn::Truncated,
+            StreamColumn::Truncated => StreamColumn::Tag,
+            StreamColumn::Tag => StreamColumn::Hidden,
+        }
+    }
+
+    pub fn is_hidden(self) -> bool {
+        self == StreamColumn::Hidden
+    }
+
+    /// Widths of the columns of the event table: open marker, timestamp, (log stream,) event.
+    pub fn table_constraint(self) -> Vec<Constraint> {
+        match self {
+            StreamColumn::Hidden => vec![
+                Constraint::Length(2),
+                Constraint::Percentage(20),
+                Constraint::Percentage(80),
+            ],
+            StreamColumn::Full => vec![
+                Constraint::Length(2),
+                Constraint::Percentage(20),
+                Constraint::Percentage(25),
+                Constraint::Percentage(55),
+            ],
+            StreamColumn::Truncated => vec![
+                Constraint::Length(2),
+                Constraint::Percentage(20),
+                Constraint::Length(TRUNCATED_WIDTH as u16),
+                Constraint::Percentage(80),
+            ],
+            StreamColumn::Tag => vec![
+                Constraint::Length(2),
+                Constraint::Percentage(20),
+                Constraint::Length(TAG_WIDTH),
+                Constraint::Percentage(80),
+            ],
+        }
+    }
+
+    pub fn text(self, log_stream_name: &str) -> String {
+        match self {
+            StreamColumn::Hidden => String::default(),
+            StreamColumn::Full => log_stream_name.to_string(),
+            StreamColumn::Truncated => truncate(log_stream_name),
+            StreamColumn::Tag => format!("■{:06x}", hash(log_stream_name) & 0xff_ffff),
+        }
+    }
+
+    pub fn cell<'a>(self, log_stream_name: Option<&String>) -> Cell<'a> {
+        let name = match log_stream_name {
+            Some(name) => name,
+            None => return Cell::from(""),
+        };
+        let cell = Cell::from(self.text(name));
+        if let StreamColumn::Tag = self {
+            cell.style(Style::default().fg(tag_color(name)))
+        } else {
+            cell
+        }
+    }
+}
+
+/// Column of the log group in the merged timeline, coloured by the log group name
+pub fn group_cell<'a>(log_group_name: Option<&str>) -> Cell<'a> {
+    match log_group_name {
+        Some(name) => Cell::from(truncate(name)).style(Style::default().fg(tag_color(name))),
+        None => Cell::from(""),
+    }
+}
+
+/// Width of the log group column in the merged timeline
+pub fn group_constraint() -> Constraint {
+    Constraint::Length(TRUNCATED_WIDTH as u16)
+}
+
+/// Keep the end of the name with the ellipsis
+fn truncate(name: &str) -> String {
+    let len = name.chars().count();
+    if len <= TRUNCATED_WIDTH {
+        name.to_string()
+    } else {
+        let tail = name
+            .chars()
+            .skip(len - (TRUNCATED_WIDTH - 1))
+            .collect::<String>();
+        format!("…{}", tail)
+    }
+}
+
+fn hash(name: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    name.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn tag_color(name: &str) -> Color {
+    TAG_COLORS[(hash(name) % TAG_COLORS.len() as u64) as usize]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_name() {
+        assert_eq!(StreamColumn::Full, StreamColumn::from_name("full").unwrap());
+        assert_eq!(
+            StreamColumn::Truncated,
+            StreamColumn::from_name("truncated").unwrap()
+        );
+        assert_eq!(
+            StreamColumn::Tag,
+            StreamColumn::from_name("hashed").unwrap()
+        );
+        assert_eq!(
+            StreamColumn::Hidden,
+            StreamColumn::from_name("hidden").unwrap()
+        );
+        assert!(StreamColumn::from_name("short").is_err());
+    }
+
+    #[test]
+    fn test_next() {
+        assert_eq!(StreamColumn::Full, StreamColumn::Hidden.next());
+        assert_eq!(StreamColumn::Truncated, StreamColumn::Full.next());
+        assert_eq!(StreamColumn::Tag, StreamColumn::Truncated.next());
+        assert_eq!(StreamColumn::Hidden, StreamColumn::Tag.next());
+    }
+
+    #[test]
+    fn test_table_constraint() {
+        assert_eq!(3, StreamColumn::Hidden.table_constraint().len());
+        assert_eq!(4, StreamColumn::Full.table_constraint().len());
+        assert_eq!(4, StreamColumn::Truncated.table_constraint().len());
+        assert_eq!(4, StreamColumn::Tag.table_constraint().len());
+    }
+
+    #[test]
+    fn test_text() {
+        let name = "2021/01/01/[$LATEST]0123456789abcdef";
+        assert_eq!(name, StreamColumn::Full.text(name));
+        assert_eq!("…123456789abcdef", StreamColumn::Truncated.text(name));
+        assert_eq!(
+            TRUNCATED_WIDTH,
+            StreamColumn::Truncated.text(name).chars().count()
+        );
+        assert_eq!("short", StreamColumn::Truncated.text("short"));
+        // the same name is always the same tag
+        let tag = StreamColumn::Tag.text(name);
+        assert_eq!(TAG_WIDTH as usize, tag.chars().count());
+        assert_eq!(tag, StreamColumn::Tag.text(name));
+        assert_ne!(tag, StreamColumn::Tag.text("other stream"));
+        assert_eq!(tag_color(name), tag_color(name));
+    }
+}
@@ -0,0 +1,214 @@
+use std::{collections::HashMap, time::Duration};
+
+use rusoto_logs::FilteredLogEvent;
+
+use crate::state::search_state::{SearchMode, SearchState};
+
+/// Position of tail mode in the log group: the latest timestamp seen (the high-water mark) and
+/// the ids of the events seen near it.
+/// Each query starts from the high-water mark minus the overlap, so that the events ingested late
+/// are still fetched, and the events fetched again are skipped by their ids.
+/// The events of the live tail have no ids, they are matched with the polled ones by the log
+/// stream, the timestamp and the message.
+#[derive(Debug)]
+pub struct TailCursor {
+    /// milliseconds fetched again before the high-water mark
+    overlap: i64,
+    high_water: Option<i64>,
+    /// timestamps of the events seen in the overlap, by the event id
+    recent_ids: HashMap<String, i64>,
+    /// the events seen in the overlap by the content key, their timestamps and whether they have
+    /// the ids, i.e. polled or streamed by the live tail
+    recent_events: Vec<(String, i64, bool)>,
+    /// the start time of the current query and its next page
+    page: Option<(Option<i64>, String)>,
+}
+
+impl TailCursor {
+    pub fn new(overlap: Duration) -> Self {
+        TailCursor {
+            overlap: overlap.as_millis() as i64,
+            high_water: None,
+            recent_ids: HashMap::new(),
+            recent_events: vec![],
+            page: None,
+        }
+    }
+
+    pub fn high_water(&self) -> Option<i64> {
+        self.high_water
+    }
+
+    /// The condition and the next token of the next fetch.
+    /// The next page of the current query, or a new query from the high-water mark.
+    /// The first query starts from the time range of `base`.
+    pub fn query(&self, base: &SearchState) -> (SearchState, Option<String>) {
+        let (from, token) = match (&self.page, self.high_water) {
+            (Some((from, token)), _) => (*from, Some(token.clone())),
+            (None, Some(high_water)) => (Some(high_water - self.overlap), None),
+            (None, None) => (base.mode.get_timestamps().0, None),
+        };
+        let condition = SearchState {
+            mode: SearchMode::FromTo(from, None),
+            ..base.clone()
+        };
+        (condition, token)
+    }
+
+    /// Keep the events not seen yet and move the high-water mark to the latest of them.
+    /// `condition` is the one returned by `query` for the fetch.
+    pub fn advance(
+        &mut self,
+        condition: &SearchState,
+        events: Vec<FilteredLogEvent>,
+        next_token: Option<String>,
+    ) -> Vec<FilteredLogEvent> {
+        let from = condition.mode.get_timestamps().0;
+        self.page = next_token.map(|token| (from, token));
+        self.skip_seen(events)
+    }
+
+    /// Keep the events not seen yet and move the high-water mark, for the events streamed by the
+    /// live tail
+    pub fn skip_seen(&mut self, events: Vec<FilteredLogEvent>) -> Vec<FilteredLogEvent> {
+        let mut new_events = vec![];
+        for event in events {
+            let timestamp = event.timestamp.unwrap_or_default();
+            if let Some(id) = &event.event_id {
+                if self.recent_ids.insert(id.clone(), timestamp).is_some() {
+                    continue;
+                }
+            }
+            let key = content_key(&event);
+            let has_id = event.event_id.is_some();
+            // the same event seen by the other of the polling and the live tail
+            if let Some(i) = self
+                .recent_events
+                .iter()
+                .position(|(k, _, id)| *k == key && *id != has_id)
+            {
+                self.recent_events.swap_remove(i);
+                continue;
+            }
+            self.recent_events.push((key, timestamp, has_id));
+            self.high_water = Some(self.high_water.map_or(timestamp, |t| t.max(timestamp)));
+            new_events.push(event);
+        }
+        // the events before the overlap are not fetched again
+        if let Some(high_water) = self.high_water {
+            let oldest = high_water - self.overlap;
+            self.recent_ids.retain(|_, t| *t >= oldest);
+            self.recent_events.retain(|(_, t, _)| *t >= oldest);
+        }
+        new_events
+    }
+}
+
+fn content_key(event: &FilteredLogEvent) -> String {
+    format!(
+        "{}\t{}\t{}",
+        event.log_stream_name.as_deref().unwrap_or_default(),
+        event.timestamp.unwrap_or_default(),
+        event.message.as_deref().unwrap_or_default()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helper::make_log_events;
+
+    fn ids(events: &[FilteredLogEvent]) -> Vec<String> {
+        events
+            .iter()
+            .map(|e| e.event_id.clone().unwrap_or_default())
+            .collect()
+    }
+
+    #[test]
+    fn test_query() {
+        let base = SearchState::new(String::from("ERROR"), SearchMode::FromTo(Some(500), None));
+        let mut cursor = TailCursor::new(Duration::from_secs(2));
+        // from the time range of the condition
+        let (condition, token) = cursor.query(&base);
+        assert_eq!(SearchMode::FromTo(Some(500), None), condition.mode);
+        assert_eq!("ERROR", condition.query);
+        assert_eq!(None, token);
+        // the next page of the same query
+        cursor.advance(
+            &condition,
+            make_log_events(1, 3, 0),
+            Some(String::from("next")),
+        );
+        let (condition, token) = cursor.query(&base);
+        assert_eq!(SearchMode::FromTo(Some(500), None), condition.mode);
+        assert_eq!(Some(String::from("next")), token);
+        // a new query from the high-water mark minus the overlap
+        cursor.advance(&condition, make_log_events(4, 5, 0), None);
+        assert_eq!(Some(5000), cursor.high_water());
+        let (condition, token) = cursor.query(&base);
+        assert_eq!(SearchMode::FromTo(Some(3000), None), condition.mode);
+        assert_eq!(None, token);
+    }
+
+    #[test]
+    fn test_advance() {
+        let base = SearchState::new(String::default(), SearchMode::FromTo(Some(0), None));
+        let mut cursor = TailCursor::new(Duration::from_secs(2));
+        let (condition, _) = cursor.query(&base);
+        assert_eq!(
+            vec!["1", "2", "3"],
+            ids(&cursor.advance(&condition, make_log_events(1, 3, 0), None))
+        );
+        // the overlap is fetched again with the event ingested late
+        let (condition, _) = cursor.query(&base);
+        let mut events = make_log_events(1, 4, 0);
+        events[0].event_id = Some(String::from("late"));
+        assert_eq!(
+            vec!["late", "4"],
+            ids(&cursor.advance(&condition, events, None))
+        );
+        assert_eq!(Some(4000), cursor.high_water());
+        // the ids before the overlap are forgotten
+        assert!(!cursor.recent_ids.contains_key("1"));
+        assert!(cursor.recent_ids.contains_key("2"));
+        // no events, the high-water mark stays
+        assert!(cursor.advance(&condition, vec![], None).is_empty());
+        assert_eq!(Some(4000), cursor.high_water());
+    }
+
+    #[test]
+    fn test_skip_seen_live_tail() {
+        let base = SearchState::new(String::default(), SearchMode::FromTo(Some(0), None));
+        let mut cursor = TailCursor::new(Duration::from_secs(2));
+        let without_ids = |events: Vec<FilteredLogEvent>| {
+            events
+                .into_iter()
+                .map(|e| FilteredLogEvent {
+                    event_id: None,
+                    ..e
+                })
+                .collect::<Vec<FilteredLogEvent>>()
+        };
+        // streamed by the live tail
+        assert_eq!(
+            2,
+            cursor
+                .skip_seen(without_ids(make_log_events(1, 2, 0)))
+                .len()
+        );
+        // the overlap is polled after the session, only the new event is kept
+        let (condition, _) = cursor.query(&base);
+        assert_eq!(
+            vec!["3"],
+            ids(&cursor.advance(&condition, make_log_events(1, 3, 0), None))
+        );
+        // polled, then streamed by the new session
+        let live = cursor.skip_seen(without_ids(make_log_events(3, 4, 0)));
+        assert_eq!(
+            vec![Some(String::from("log_event_4"))],
+            live.into_iter().map(|e| e.message).collect::<Vec<_>>()
+        );
+        assert_eq!(Some(4000), cursor.high_water());
+    }
+}
@@ -0,0 +1,199 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone};
+use lazy_static::lazy_static;
+use regex::Regex;
+
+lazy_static! {
+    /// `-15m`, `2h ago`, `-3 days`
+    static ref RELATIVE: Regex =
+        Regex::new(r"^(-)?\s*(\d+)\s*([a-z]+)(\s+ago)?$").unwrap();
+    /// `now`, `today`, `yesterday 14:00`
+    static ref DAY: Regex =
+        Regex::new(r"^(now|today|yesterday)(?:\s+(\d{1,2}):(\d{2})(?::(\d{2}))?)?$").unwrap();
+}
+
+/// formats with the timezone, in addition to RFC 3339
+const ZONED_FORMATS: [&str; 2] = ["%Y-%m-%d %H:%M:%S%.f%:z", "%Y-%m-%dT%H:%M:%S%.f%z"];
+/// formats in the local time, in addition to DATE_FORMAT
+const LOCAL_FORMATS: [&str; 4] = [
+    "%Y-%m-%dT%H:%M:%S%.f",
+    "%Y-%m-%d %H:%M:%S%.f",
+    "%Y-%m-%dT%H:%M",
+    "%Y-%m-%d %H:%M",
+];
+/// epoch milliseconds have at least 10 digits since 1970-04-26
+const EPOCH_MILLIS_MIN_DIGITS: usize = 10;
+
+/// Parse the time of the custom term into the epoch milliseconds.
+/// Accepts the relative time (`-15m`, `2h ago`), `now`/`today`/`yesterday` with the optional time
+/// (`yesterday 14:00`), ISO 8601 with the timezone, the local time in `date_format` or ISO 8601,
+/// and the epoch milliseconds.
+pub fn parse(input: &str, now: DateTime<Local>, date_format: &str) -> Result<i64> {
+    let input = input.trim();
+    let lower = input.to_lowercase();
+    if let Some(caps) = RELATIVE.captures(&lower) {
+        // a bare number like `15m` is not accepted, it is not clear which way it goes
+        if caps.get(1).is_some() || caps.get(4).is_some() {
+            let n = caps[2].parse::<i64>()?;
+            let duration = match &caps[3] {
+                "s" | "sec" | "secs" | "second" | "seconds" => Duration::seconds(n),
+                "m" | "min" | "mins" | "minute" | "minutes" => Duration::minutes(n),
+                "h" | "hr" | "hrs" | "hour" | "hours" => Duration::hours(n),
+                "d" | "day" | "days" => Duration::days(n),
+                "w" | "week" | "weeks" => Duration::weeks(n),
+                unit => return Err(anyhow!("unknown unit \"{}\"", unit)),
+            };
+            return Ok((now - duration).timestamp_millis());
+        }
+    }
+    if let Some(caps) = DAY.captures(&lower) {
+        let date = match &caps[1] {
+            "now" if caps.get(2).is_none() => return Ok(now.timestamp_millis()),
+            "now" => return Err(anyhow!("invalid time \"{}\"", input)),
+            "today" => now.date_naive(),
+            _ => now.date_naive() - Duration::days(1),
+        };
+        let number = |i| {
+            caps.get(i)
+                .map_or(Ok(0), |m: regex::Match<'_>| m.as_str().parse::<u32>())
+        };
+        let time = NaiveTime::from_hms_opt(number(2)?, number(3)?, number(4)?)
+            .ok_or_else(|| anyhow!("invalid time \"{}\"", input))?;
+        return local_millis(&date.and_time(time), input);
+    }
+    if input.len() >= EPOCH_MILLIS_MIN_DIGITS && input.chars().all(|c| c.is_ascii_digit()) {
+        return Ok(input.parse::<i64>()?);
+    }
+    if let Ok(dt) = DateTime::parse_from_rfc3339(input) {
+        return Ok(dt.timestamp_millis());
+    }
+    for format in ZONED_FORMATS.iter() {
+        if let Ok(dt) = DateTime::parse_from_str(input, format) {
+            return Ok(dt.timestamp_millis());
+        }
+    }
+    for format in std::iter::once(date_format).chain(LOCAL_FORMATS.iter().copied()) {
+        if let Ok(naive) = NaiveDateTime::parse_from_str(input, format) {
+            return local_millis(&naive, input);
+        }
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(input, "%Y-%m-%d") {
+        return local_millis(&date.and_time(NaiveTime::MIN), input);
+    }
+    Err(anyhow!("invalid time \"{}\"", input))
+}
+
+fn local_millis(naive: &NaiveDateTime, input: &str) -> Result<i64> {
+    Local
+        .from_local_datetime(naive)
+        .earliest()
+        .map(|dt| dt.timestamp_millis())
+        .ok_or_else(|| anyhow!("no such local time \"{}\"", input))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+    fn now() -> DateTime<Local> {
+        Local.with_ymd_and_hms(2021, 1, 2, 9, 30, 0).unwrap()
+    }
+
+    fn local(y: i32, mo: u32, d: u32, h: u32, mi: u32, s: u32) -> i64 {
+        Local
+            .with_ymd_and_hms(y, mo, d, h, mi, s)
+            .unwrap()
+            .timestamp_millis()
+    }
+
+    #[test]
+    fn test_parse_relative() {
+        let now = now().timestamp_millis();
+        assert_eq!(
+            now - 15 * 60 * 1000,
+            parse("-15m", self::now(), FORMAT).unwrap()
+        );
+        assert_eq!(
+            now - 2 * 3600 * 1000,
+            parse("2h ago", self::now(), FORMAT).unwrap()
+        );
+        assert_eq!(
+            now - 3 * 24 * 3600 * 1000,
+            parse("3 days ago", self::now(), FORMAT).unwrap()
+        );
+        assert_eq!(
+            now - 30 * 1000,
+            parse("- 30s", self::now(), FORMAT).unwrap()
+        );
+        assert!(parse("15m", self::now(), FORMAT).is_err());
+        assert!(parse("-15y", self::now(), FORMAT).is_err());
+    }
+
+    #[test]
+    fn test_parse_day() {
+        assert_eq!(
+            now().timestamp_millis(),
+            parse("now", now(), FORMAT).unwrap()
+        );
+        assert_eq!(
+            local(2021, 1, 2, 0, 0, 0),
+            parse("today", now(), FORMAT).unwrap()
+        );
+        assert_eq!(
+            local(2021, 1, 1, 14, 0, 0),
+            parse("Yesterday 14:00", now(), FORMAT).unwrap()
+        );
+        assert_eq!(
+            local(2021, 1, 1, 14, 0, 30),
+            parse("yesterday 14:00:30", now(), FORMAT).unwrap()
+        );
+        assert!(parse("yesterday 25:00", now(), FORMAT).is_err());
+        assert!(parse("now 14:00", now(), FORMAT).is_err());
+    }
+
+    #[test]
+    fn test_parse_absolute() {
+        // ISO 8601 with the timezone
+        assert_eq!(
+            1609426800000,
+            parse("2021-01-01T00:00:00+09:00", now(), FORMAT).unwrap()
+        );
+        assert_eq!(
+            1609459200000,
+            parse("2021-01-01T00:00:00Z", now(), FORMAT).unwrap()
+        );
+        assert_eq!(
+            1609426800500,
+            parse("2021-01-01 00:00:00.5+09:00", now(), FORMAT).unwrap()
+        );
+        // local time
+        assert_eq!(
+            local(2021, 1, 1, 12, 34, 56),
+            parse("2021-01-01 12:34:56", now(), FORMAT).unwrap()
+        );
+        assert_eq!(
+            local(2021, 1, 1, 12, 34, 56),
+            parse("2021/01/01 12:34:56", now(), "%Y/%m/%d %H:%M:%S").unwrap()
+        );
+        assert_eq!(
+            local(2021, 1, 1, 12, 34, 0),
+            parse("2021-01-01T12:34", now(), FORMAT).unwrap()
+        );
+        assert_eq!(
+            local(2021, 1, 1, 0, 0, 0),
+            parse("2021-01-01", now(), FORMAT).unwrap()
+        );
+        // epoch milliseconds
+        assert_eq!(
+            1609426800000,
+            parse("1609426800000", now(), FORMAT).unwrap()
+        );
+        assert!(parse("2021", now(), FORMAT).is_err());
+        assert_eq!(
+            "invalid time \"next week\"",
+            parse("next week", now(), FORMAT).unwrap_err().to_string()
+        );
+    }
+}
@@ -6,12 +6,17 @@ use tui::{backend::Backend, layout::Rect, Frame};
 
 use crate::key_event_wrapper::KeyEventWrapper;
 
+pub mod context_view;
 pub mod event_area;
+pub mod event_detail;
+pub mod export_dialog;
+pub mod filter_dialog;
 pub mod help;
 pub mod search_condition_dialog;
 pub mod search_info;
 pub mod side_menu;
 pub mod status_bar;
+pub mod stream_picker;
 pub mod textbox;
 
 #[async_trait]
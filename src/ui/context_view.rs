@@ -0,0 +1,224 @@
+use std::{
+    collections::BTreeMap,
+    marker::PhantomData,
+    sync::{Arc, Mutex},
+};
+
+use async_trait::async_trait;
+use crossterm::event::KeyEvent;
+use tui::{
+    backend::Backend,
+    layout::Rect,
+    style::{Modifier, Style},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState},
+    Frame,
+};
+
+use crate::{
+    constant::{self, KEYMAP, MATCH_STYLE},
+    key_event_wrapper::KeyEventWrapper,
+    keymap::{Action, Context},
+    loader::Loader,
+    state::logevents_state::LogEventsState,
+    ui::Drawable,
+    utils::*,
+};
+
+/// Popup of the log events around the hit in the same log stream, with the hit highlighted.
+/// The events are fetched into the state by the log event handler.
+pub struct ContextView<B>
+where
+    B: Backend,
+{
+    state: Arc<Mutex<LogEventsState>>,
+    list_state: ListState,
+    loader: Loader,
+    _phantom: PhantomData<B>,
+}
+
+impl<B> ContextView<B>
+where
+    B: Backend,
+{
+    pub fn new(state: Arc<Mutex<LogEventsState>>) -> Self {
+        ContextView {
+            state,
+            list_state: ListState::default(),
+            loader: Loader::new(constant::LOADER.clone()),
+            _phantom: PhantomData,
+        }
+    }
+
+    /// The cursor is put on the hit when the events are fetched
+    pub fn reset(&mut self) {
+        self.list_state = ListState::default();
+    }
+
+    fn len(&self) -> usize {
+        match self.state.try_lock() {
+            Ok(s) => s.context.as_ref().map_or(0, |c| c.events.len()),
+            Err(_) => 0,
+        }
+    }
+
+    fn next(&mut self) {
+        let len = self.len();
+        if len == 0 {
+            return;
+        }
+        let idx = match self.list_state.selected() {
+            Some(i) if i + 1 < len => i + 1,
+            Some(i) => i,
+            None => 0,
+        };
+        self.list_state.select(Some(idx));
+    }
+
+    fn previous(&mut self) {
+        if let Some(i) = self.list_state.selected() {
+            self.list_state.select(Some(i.saturating_sub(1)));
+        }
+    }
+
+    fn copy(&self) {
+        if let (Ok(s), Some(i)) = (self.state.try_lock(), self.list_state.selected()) {
+            if let Some(msg) = s
+                .context
+                .as_ref()
+                .and_then(|c| c.events.get(i))
+                .and_then(|e| e.message.as_ref())
+            {
+                copy_to_clipboard(msg);
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl<B> Drawable<B> for ContextView<B>
+where
+    B: Backend + Send,
+{
+    fn draw(&mut self, f: &mut Frame<'_, B>, area: Rect) {
+        let mut title = String::from("Context");
+        let mut items = vec![];
+        if let Ok(s) = self.state.try_lock() {
+            if let Some(context) = &s.context {
+                if let Some(stream) = &context.hit.log_stream_name {
+                    title = format!("Context of {}", stream);
+                }
+                let hit = context.hit_index();
+                if self.list_state.selected().is_none() && !context.is_fetching {
+                    self.list_state.select(hit.or(Some(0)));
+                }
+                items = context
+                    .events
+                    .iter()
+                    .enumerate()
+                    .map(|(i, e)| {
+                        let time = e
+                            .timestamp
+                            .map(|t| format_local_time(t, &constant::DATE_FORMAT))
+                            .unwrap_or_default();
+                        let msg = e.message.as_deref().unwrap_or_default().replace('\n', " ");
+                        let item = ListItem::new(format!("{} {}", time, msg));
+                        if Some(i) == hit {
+                            item.style(*MATCH_STYLE)
+                        } else {
+                            item
+                        }
+                    })
+                    .collect();
+                if context.is_fetching {
+                    items.push(ListItem::new(self.loader.get_char().to_string()));
+                } else if let Some(e) = &context.error {
+                    items.push(ListItem::new(format!("Failed to fetch the context: {}", e)));
+                }
+            }
+        }
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title(title))
+            .style(*constant::NORMAL_STYLE)
+            .highlight_style(Style::default().add_modifier(Modifier::BOLD))
+            .highlight_symbol(">> ");
+
+        f.render_widget(Clear, area);
+        f.render_stateful_widget(list, area, &mut self.list_state);
+    }
+
+    async fn handle_event(&mut self, event: KeyEvent) -> bool {
+        match KEYMAP.action(Context::Dialog, &event) {
+            Some(Action::NextItem) => {
+                self.next();
+            }
+            Some(Action::PrevItem) => {
+                self.previous();
+            }
+            Some(Action::Confirm) => {
+                self.copy();
+            }
+            // Cancel will be handled by the parent component
+            _ => {
+                return false;
+            }
+        }
+        true
+    }
+
+    fn push_key_maps<'a>(
+        &self,
+        maps: &'a mut BTreeMap<KeyEventWrapper, String>,
+    ) -> &'a mut BTreeMap<KeyEventWrapper, String> {
+        KEYMAP.push_key_map(maps, Action::NextItem, "Next log event");
+        KEYMAP.push_key_map(maps, Action::PrevItem, "Prev log event");
+        KEYMAP.push_key_map(maps, Action::Confirm, "Copy to clipboard");
+        KEYMAP.push_key_map(maps, Action::Cancel, "Close context");
+        maps
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::{KeyCode, KeyModifiers};
+    use tui::backend::TestBackend;
+
+    use super::*;
+    use crate::{
+        context::EventContext,
+        test_helper::{get_test_terminal, make_log_events},
+    };
+
+    #[tokio::test]
+    async fn test_draw_and_handle_event() {
+        let state = Arc::new(Mutex::new(LogEventsState::new()));
+        let mut view: ContextView<TestBackend> = ContextView::new(Arc::clone(&state));
+        let events = make_log_events(0, 4, 0);
+        let mut context = EventContext::new(events[2].clone());
+        state.lock().unwrap().context = Some(context.clone());
+        let mut terminal = get_test_terminal(50, 8);
+        terminal.draw(|f| view.draw(f, f.size())).unwrap();
+        // fetching
+        assert_eq!(None, view.list_state.selected());
+        context.set_result(Ok(events));
+        state.lock().unwrap().context = Some(context);
+        terminal.draw(|f| view.draw(f, f.size())).unwrap();
+        // the cursor is on the hit
+        assert_eq!(Some(2), view.list_state.selected());
+        let buffer = terminal.backend().buffer();
+        let line = |y| {
+            (0..50)
+                .map(|x| buffer.get(x, y).symbol.clone())
+                .collect::<String>()
+        };
+        assert!(line(3).contains(">> "));
+        assert!(line(3).contains(" log_event_2 "));
+        assert_eq!(MATCH_STYLE.bg, Some(buffer.get(10, 3).bg));
+        assert_ne!(MATCH_STYLE.bg, Some(buffer.get(10, 2).bg));
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        view.handle_event(key(KeyCode::Down)).await;
+        view.handle_event(key(KeyCode::Down)).await;
+        view.handle_event(key(KeyCode::Down)).await;
+        assert_eq!(Some(4), view.list_state.selected());
+        assert!(!view.handle_event(key(KeyCode::Esc)).await);
+    }
+}
@@ -2,46 +2,59 @@ use std::{
     collections::BTreeMap,
     marker::PhantomData,
     sync::{Arc, Mutex},
+    time::Duration,
 };
 
 use async_trait::async_trait;
-use chrono::{DateTime, Local, TimeZone};
-use clipboard::{ClipboardContext, ClipboardProvider};
-use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
-use lazy_static::lazy_static;
+use crossterm::event::KeyEvent;
 use tokio::sync::mpsc;
 use tui::{
     backend::Backend,
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
-    widgets::{Block, Borders, Row, Table, TableState},
+    widgets::{Block, Borders, Cell, Clear, Row, Table, TableState},
     Frame,
 };
 
 use crate::{
-    constant,
+    client::merged::EventSources,
+    constant::{self, ERROR_STYLE, KEYMAP, RETRY_INSTRUCTION},
     event::LogEventEvent,
+    export::{default_file_name, ExportFormat},
+    json_message,
     key_event_wrapper::KeyEventWrapper,
+    keymap::{Action, Context},
     loader::Loader,
+    local_search::LocalSearch,
+    log_level::LogLevel,
     state::{
         logevents_state::LogEventsState,
+        search_history::SearchHistory,
         search_state::{SearchMode, SearchState},
     },
-    ui::{search_condition_dialog::SearchConditionDialog, search_info::SearchInfo, Drawable},
+    stream_column::{self, StreamColumn},
+    ui::{
+        context_view::ContextView, event_detail::EventDetail, export_dialog::ExportDialog,
+        filter_dialog::FilterDialog, search_condition_dialog::SearchConditionDialog,
+        search_info::SearchInfo, stream_picker::StreamPicker, textbox::TextBox, Drawable,
+    },
+    utils::{copy_to_clipboard, format_local_time},
 };
 
-lazy_static! {
-    static ref TABLE_CONSTRAINT: [Constraint; 3] = [
-        Constraint::Length(2),
-        Constraint::Percentage(20),
-        Constraint::Percentage(80),
-    ];
-}
+/// width of each JSON field column
+const JSON_FIELD_WIDTH: u16 = 16;
 
 #[derive(Debug, PartialEq)]
 pub enum Selection {
     Events,
     Search,
+    Streams,
+    Export,
+    LocalSearch,
+    Filter,
+    JsonFields,
+    Detail,
+    Context,
 }
 
 pub struct EventArea<B>
@@ -55,6 +68,26 @@ where
     loader: Loader,
     search_info: SearchInfo<B>,
     search_condition_dialog: SearchConditionDialog<B>,
+    stream_picker: StreamPicker<B>,
+    export_dialog: ExportDialog<B>,
+    filter_dialog: FilterDialog<B>,
+    event_detail: EventDetail<B>,
+    context_view: ContextView<B>,
+    stream_column: StreamColumn,
+    /// log groups shown in the merged timeline, empty if the area shows one log group
+    merged_log_groups: Vec<String>,
+    /// log groups of the events in the merged timeline
+    event_sources: EventSources,
+    /// regex over the fetched log events, highlighted in the messages
+    local_search: Option<LocalSearch>,
+    local_search_input: TextBox<B>,
+    /// the cursor when the local search is opened, where the incremental search starts from
+    local_search_origin: Option<usize>,
+    /// the opened JSON messages are pretty-printed
+    pretty_json: bool,
+    /// fields of the JSON messages shown as the columns
+    json_fields: Vec<String>,
+    json_fields_input: TextBox<B>,
     selection: Selection,
     _phantom: PhantomData<B>,
 }
@@ -71,17 +104,59 @@ where
         let search_state = SearchState::new(String::default(), SearchMode::Tail);
         EventArea {
             log_group_name: log_group_name.to_string(),
+            stream_picker: StreamPicker::new(Arc::clone(&state)),
+            context_view: ContextView::new(Arc::clone(&state)),
             state,
             logevent_inst_tx,
             is_selected: false,
             loader: Loader::new(constant::LOADER.clone()),
             search_info: SearchInfo::new(search_state.clone()),
             search_condition_dialog: SearchConditionDialog::new(search_state),
+            export_dialog: ExportDialog::new(),
+            filter_dialog: FilterDialog::new(),
+            event_detail: EventDetail::new(),
+            stream_column: *constant::STREAM_COLUMN,
+            merged_log_groups: vec![],
+            event_sources: EventSources::default(),
+            local_search: None,
+            local_search_input: TextBox::new(true),
+            local_search_origin: None,
+            pretty_json: false,
+            json_fields: constant::JSON_FIELDS.clone(),
+            json_fields_input: TextBox::new(true),
             selection: Selection::Events,
             _phantom: PhantomData,
         }
     }
 
+    /// Start with the search condition instead of tail mode
+    pub fn with_search_state(mut self, search_state: SearchState) -> Self {
+        self.search_info = SearchInfo::new(search_state.clone());
+        self.search_condition_dialog.set_state(&search_state);
+        self
+    }
+
+    /// Record the searches to the history shared by the event areas
+    pub fn with_history(mut self, history: Arc<Mutex<SearchHistory>>) -> Self {
+        self.search_condition_dialog = self
+            .search_condition_dialog
+            .with_history(&self.log_group_name, history);
+        self
+    }
+
+    /// Show the log groups in one timeline with the log group column.
+    /// The state must be fed by the merged log source recording the log groups in `sources`.
+    pub fn with_merged_log_groups(
+        mut self,
+        log_group_names: Vec<String>,
+        sources: EventSources,
+    ) -> Self {
+        self.log_group_name = log_group_names.join(" + ");
+        self.merged_log_groups = log_group_names;
+        self.event_sources = sources;
+        self
+    }
+
     pub fn set_select(&mut self, select: bool) {
         self.is_selected = select;
     }
@@ -89,6 +164,57 @@ where
     pub fn log_group_name(&self) -> &str {
         self.log_group_name.as_str()
     }
+
+    /// The log groups shown in the area
+    pub fn log_group_names(&self) -> Vec<String> {
+        if self.is_merged() {
+            self.merged_log_groups.clone()
+        } else {
+            vec![self.log_group_name.clone()]
+        }
+    }
+
+    pub fn is_merged(&self) -> bool {
+        !self.merged_log_groups.is_empty()
+    }
+
+    pub fn search_state(&self) -> SearchState {
+        self.search_info.get_state()
+    }
+
+    /// Update the regex by the input and move the cursor to the first match from the origin.
+    /// The previous regex is kept while the input is invalid.
+    fn update_local_search(&mut self) {
+        let input = self.local_search_input.get_input();
+        if input.is_empty() {
+            self.local_search = None;
+            return;
+        }
+        if let Ok(mut search) = LocalSearch::new(&input) {
+            if let Ok(mut s) = self.state.lock() {
+                search.update(&s.events);
+                let from = self.local_search_origin.and_then(|i| i.checked_sub(1));
+                if let Some(idx) = search.next_match(from) {
+                    s.state.select(Some(idx));
+                }
+            }
+            self.local_search = Some(search);
+        }
+    }
+
+    /// Widths of the event table with the log group column of the merged timeline
+    /// and the JSON field columns before the event
+    fn table_constraint(&self) -> Vec<Constraint> {
+        let mut constraint = self.stream_column.table_constraint();
+        if self.is_merged() {
+            constraint.insert(2, stream_column::group_constraint());
+        }
+        let event = constraint.len() - 1;
+        for _ in &self.json_fields {
+            constraint.insert(event, Constraint::Length(JSON_FIELD_WIDTH));
+        }
+        constraint
+    }
 }
 
 impl<B> Default for EventArea<B>
@@ -98,14 +224,29 @@ where
     fn default() -> Self {
         // dummy sender
         let (tx, _) = mpsc::channel(1);
+        let state = Arc::new(Mutex::new(LogEventsState::default()));
         EventArea {
             log_group_name: String::from("Events"),
-            state: Arc::new(Mutex::new(LogEventsState::default())),
+            context_view: ContextView::new(Arc::clone(&state)),
+            stream_picker: StreamPicker::new(Arc::clone(&state)),
+            state,
             logevent_inst_tx: tx,
             is_selected: false,
             loader: Loader::new(constant::LOADER.clone()),
             search_info: SearchInfo::default(),
             search_condition_dialog: SearchConditionDialog::default(),
+            export_dialog: ExportDialog::new(),
+            filter_dialog: FilterDialog::new(),
+            event_detail: EventDetail::new(),
+            stream_column: *constant::STREAM_COLUMN,
+            merged_log_groups: vec![],
+            event_sources: EventSources::default(),
+            local_search: None,
+            local_search_input: TextBox::new(true),
+            local_search_origin: None,
+            pretty_json: false,
+            json_fields: constant::JSON_FIELDS.clone(),
+            json_fields_input: TextBox::new(true),
             selection: Selection::Events,
             _phantom: PhantomData,
         }
@@ -122,6 +263,14 @@ where
             .direction(Direction::Vertical)
             .constraints([Constraint::Min(1), Constraint::Percentage(100)].as_ref())
             .split(area);
+        let mut title = self.log_group_name.clone();
+        if let Some(search) = &mut self.local_search {
+            if let Ok(s) = self.state.try_lock() {
+                search.update(&s.events);
+                let count = search.matches().len();
+                title = format!("{} [/{}/ {} matches]", title, search.pattern(), count);
+            }
+        }
         let block = Block::default()
             .borders(Borders::ALL)
             .border_style(if self.is_selected {
@@ -129,79 +278,170 @@ where
             } else {
                 Style::default().fg(*constant::DESELECTED_COLOR)
             })
-            .title(self.log_group_name.as_ref());
+            .title(title);
         let mut rows = vec![];
         let mut state = TableState::default();
+        // header and widths of the Logs Insights results
+        let mut insights_columns: Option<(Vec<String>, Vec<Constraint>)> = None;
         if let Ok(s) = self.state.try_lock() {
-            // get event row width
-            let table_chunks = Layout::default()
-                .direction(Direction::Horizontal)
-                .constraints(TABLE_CONSTRAINT.as_ref())
-                .split(chunks[1]);
-            let width = table_chunks[2].width - 3;
-
             state = s.state.clone();
-            let opened_idx_list = s.events.opened_idx();
-            s.events.items().iter().enumerate().for_each(|(idx, item)| {
-                let mut msg = if let Some(msg) = &item.message {
-                    msg.clone()
-                } else {
-                    String::default()
-                };
-                let mut open = false;
-                let (event_msg, row_height) = if opened_idx_list.contains(&idx) {
-                    open = true;
-                    insert_newline(&mut msg, width)
-                } else {
-                    (msg, 1)
+            if let Some(insights) = &s.insights {
+                // dynamic columns of the Logs Insights results
+                let columns = insights.columns().clone();
+                let width = 100 / columns.len().max(1) as u16;
+                let widths = columns
+                    .iter()
+                    .map(|_| Constraint::Percentage(width))
+                    .collect::<Vec<Constraint>>();
+                insights
+                    .rows()
+                    .iter()
+                    .for_each(|row| rows.push(Row::new(row.clone())));
+                if let Some(e) = &s.error {
+                    rows.push(Row::new(vec![error_message(e)]).style(*ERROR_STYLE));
+                } else if s.is_fetching {
+                    rows.push(Row::new(vec![self.loader.get_char().to_string()]));
+                }
+                insights_columns = Some((columns, widths));
+            } else {
+                // get event row width
+                let table_chunks = Layout::default()
+                    .direction(Direction::Horizontal)
+                    .constraints(self.table_constraint())
+                    .split(chunks[1]);
+                let width = table_chunks.last().unwrap().width - 3;
+
+                let stream_column = self.stream_column;
+                let merged_log_groups = &self.merged_log_groups;
+                let event_sources = &self.event_sources;
+                let local_search = &self.local_search;
+                let pretty_json = self.pretty_json;
+                let json_fields = &self.json_fields;
+                // empty cells are inserted for the log group, log stream and JSON field columns if shown
+                let make_row = |marker: String, timestamp: String, msg: String| {
+                    let mut cells = vec![Cell::from(marker), Cell::from(timestamp)];
+                    if !merged_log_groups.is_empty() {
+                        cells.push(Cell::from(""));
+                    }
+                    if !stream_column.is_hidden() {
+                        cells.push(Cell::from(""));
+                    }
+                    cells.extend(json_fields.iter().map(|_| Cell::from("")));
+                    cells.push(Cell::from(msg));
+                    Row::new(cells)
                 };
-                rows.push(
-                    Row::new(vec![
-                        if open {
+                let opened_idx_list = s.events.opened_idx();
+                s.events.items().iter().enumerate().for_each(|(idx, item)| {
+                    let msg = if let Some(msg) = &item.message {
+                        msg.clone()
+                    } else {
+                        String::default()
+                    };
+                    let open = opened_idx_list.contains(&idx);
+                    // the fields are read when the events are pushed, only the opened ones are
+                    // parsed here
+                    let pretty = if pretty_json && open {
+                        json_message::parse(&msg).map(|object| json_message::highlight(&object))
+                    } else {
+                        None
+                    };
+                    let breaks = match &pretty {
+                        None if open => wrap_points(&msg, width),
+                        _ => vec![],
+                    };
+                    let row_height = match &pretty {
+                        Some(text) => text.height() as u16,
+                        None => breaks.len() as u16 + 1,
+                    };
+                    let mut cells = vec![
+                        Cell::from(if open {
                             "v".to_string()
                         } else {
                             ">".to_string()
-                        },
-                        if let Some(time) = item.timestamp {
-                            let dt: DateTime<Local> = Local.timestamp(time / 1000, 0);
-                            dt.to_string()
+                        }),
+                        Cell::from(
+                            item.timestamp
+                                .map(|t| format_local_time(t, "%Y-%m-%d %H:%M:%S %:z"))
+                                .unwrap_or_default(),
+                        ),
+                    ];
+                    if !merged_log_groups.is_empty() {
+                        cells.push(stream_column::group_cell(
+                            event_sources.get(item).as_deref(),
+                        ));
+                    }
+                    if !stream_column.is_hidden() {
+                        cells.push(stream_column.cell(item.log_stream_name.as_ref()));
+                    }
+                    let values = s.events.field_values(idx).unwrap_or_default();
+                    cells.extend(
+                        (0..json_fields.len())
+                            .map(|i| Cell::from(values.get(i).cloned().unwrap_or_default())),
+                    );
+                    cells.push(match (pretty, local_search) {
+                        (Some(text), _) => Cell::from(text),
+                        // highlighted before wrapped, for the matches across the wrap points
+                        (None, Some(search)) => Cell::from(search.highlight(&msg, &breaks)),
+                        (None, None) => Cell::from(wrap(&msg, &breaks)),
+                    });
+                    let row = Row::new(cells).height(row_height);
+                    rows.push(match s.events.level(idx) {
+                        Some(level) => row.style(level.style()),
+                        None => row,
+                    });
+                });
+                if let Some(e) = &s.error {
+                    rows.push(
+                        make_row("!".to_string(), "Error".to_string(), error_message(e))
+                            .style(*ERROR_STYLE),
+                    );
+                } else if self.search_condition_dialog.is_tail() {
+                    rows.push(make_row(
+                        "".to_string(),
+                        "Waiting for data...".to_string(),
+                        if s.live_tail {
+                            "streaming (live tail)".to_string()
                         } else {
-                            "".to_string()
+                            s.tail_interval
+                                .map_or_else(|| "...".to_string(), format_tail_interval)
                         },
-                        event_msg,
-                    ])
-                    .height(row_height),
-                );
-            });
-            if self.search_condition_dialog.is_tail() {
-                rows.push(Row::new(vec![
-                    "".to_string(),
-                    "Waiting for data...".to_string(),
-                    "...".to_string(),
-                ]));
-            } else if s.is_fetching {
-                rows.push(Row::new(vec![
-                    // TODO: export function
-                    self.loader.get_char().to_string(),
-                    "".to_string(),
-                    "".to_string(),
-                ]));
-            } else if s.next_token.is_some() {
-                rows.push(Row::new(vec![
-                    "".to_string(),
-                    "More...".to_string(),
-                    "...".to_string(),
-                ]));
+                    ));
+                } else if s.is_fetching {
+                    rows.push(make_row(
+                        // TODO: export function
+                        self.loader.get_char().to_string(),
+                        "".to_string(),
+                        "".to_string(),
+                    ));
+                } else if s.next_token.is_some() {
+                    rows.push(make_row(
+                        "".to_string(),
+                        "More...".to_string(),
+                        "...".to_string(),
+                    ));
+                }
             }
         }
+        let (header, widths) = match insights_columns {
+            Some((columns, widths)) => (columns, widths),
+            None => {
+                let mut header = vec![" ".to_string(), "Timestamp".to_string()];
+                if self.is_merged() {
+                    header.push("Group".to_string());
+                }
+                if !self.stream_column.is_hidden() {
+                    header.push("Stream".to_string());
+                }
+                header.extend(self.json_fields.iter().cloned());
+                header.push("Event".to_string());
+                (header, self.table_constraint())
+            }
+        };
         let table = if let Selection::Events = self.selection {
             Table::new(rows)
                 .block(block)
-                .header(
-                    Row::new(vec![" ", "Timestamp", "Event"])
-                        .style(Style::default().fg(Color::White)),
-                )
-                .widths(TABLE_CONSTRAINT.as_ref())
+                .header(Row::new(header).style(Style::default().fg(Color::White)))
+                .widths(&widths)
                 .highlight_style(Style::default().add_modifier(Modifier::BOLD))
                 .style(Style::default())
                 .column_spacing(1)
@@ -209,10 +449,39 @@ where
             Table::new(rows).block(block)
         };
 
+        if let Ok(s) = self.state.try_lock() {
+            let filter = s.events.filter();
+            self.search_info.set_hidden(if filter.is_empty() {
+                None
+            } else {
+                Some(s.events.hidden_count())
+            });
+            self.search_info.set_min_level(filter.min_level());
+        }
         self.search_info.draw(f, chunks[0]);
         f.render_stateful_widget(table, chunks[1], &mut state);
-        if let Selection::Search = self.selection {
-            self.search_condition_dialog.draw(f, chunks[1]);
+        match self.selection {
+            Selection::Search => self.search_condition_dialog.draw(f, chunks[1]),
+            Selection::Streams => self.stream_picker.draw(f, chunks[1]),
+            Selection::Export => self.export_dialog.draw(f, chunks[1]),
+            Selection::LocalSearch => {
+                let title = if LocalSearch::new(&self.local_search_input.get_input()).is_ok() {
+                    "Search (regex)"
+                } else {
+                    "Search (invalid regex)"
+                };
+                draw_input_popup(f, chunks[1], &mut self.local_search_input, title);
+            }
+            Selection::JsonFields => draw_input_popup(
+                f,
+                chunks[1],
+                &mut self.json_fields_input,
+                "JSON fields as columns (comma separated)",
+            ),
+            Selection::Filter => self.filter_dialog.draw(f, chunks[1]),
+            Selection::Detail => self.event_detail.draw(f, area),
+            Selection::Context => self.context_view.draw(f, area),
+            Selection::Events => {}
         }
     }
 
@@ -220,58 +489,177 @@ where
         if self.is_selected {
             let mut next_token = None;
             let mut need_more_fetching = false;
+            let mut retry = false;
             let mut change_search_condition = false;
+            let mut fetch_log_streams = false;
+            let mut export = None;
+            let mut fetch_context = None;
             if let Selection::Search = self.selection {
                 if self.search_condition_dialog.handle_event(event).await {
                     return true;
                 }
             }
+            if let Selection::Streams = self.selection {
+                if self.stream_picker.handle_event(event).await {
+                    return true;
+                }
+            }
+            if let Selection::Export = self.selection {
+                if self.export_dialog.handle_event(event).await {
+                    return true;
+                }
+            }
+            if let Selection::Filter = self.selection {
+                if self.filter_dialog.handle_event(event).await {
+                    return true;
+                }
+            }
+            if let Selection::Detail = self.selection {
+                if !self.event_detail.handle_event(event).await {
+                    if let Some(Action::Cancel) = KEYMAP.action(Context::Dialog, &event) {
+                        self.selection = Selection::Events;
+                    }
+                }
+                return true;
+            }
+            if let Selection::Context = self.selection {
+                if !self.context_view.handle_event(event).await {
+                    if let Some(Action::Cancel) = KEYMAP.action(Context::Dialog, &event) {
+                        if let Ok(mut s) = self.state.lock() {
+                            s.context = None;
+                        }
+                        self.selection = Selection::Events;
+                    }
+                }
+                return true;
+            }
+            if let Selection::JsonFields = self.selection {
+                match KEYMAP.action(Context::Dialog, &event) {
+                    Some(Action::Confirm) => {
+                        self.json_fields = self
+                            .json_fields_input
+                            .get_input()
+                            .split(',')
+                            .map(|name| name.trim().to_string())
+                            .filter(|name| !name.is_empty())
+                            .collect();
+                        if let Ok(mut s) = self.state.lock() {
+                            s.events.set_json_fields(self.json_fields.clone());
+                        }
+                        self.selection = Selection::Events;
+                    }
+                    Some(Action::Cancel) => {
+                        self.selection = Selection::Events;
+                    }
+                    _ => {
+                        self.json_fields_input.handle_event(event).await;
+                    }
+                }
+                return true;
+            }
+            if let Selection::LocalSearch = self.selection {
+                match KEYMAP.action(Context::Dialog, &event) {
+                    Some(Action::Confirm) => {
+                        self.selection = Selection::Events;
+                    }
+                    Some(Action::Cancel) => {
+                        self.local_search = None;
+                        self.selection = Selection::Events;
+                    }
+                    _ => {
+                        if self.local_search_input.handle_event(event).await {
+                            self.update_local_search();
+                        }
+                    }
+                }
+                return true;
+            }
             {
                 let mut state = self.state.lock();
-                if let Selection::Search = self.selection {
-                    // search condition dialog event handling
-                    match event.code {
-                        KeyCode::Esc => {
+                let action = if let Selection::Events = self.selection {
+                    KEYMAP.action(Context::EventArea, &event)
+                } else {
+                    KEYMAP.action(Context::Dialog, &event)
+                };
+                if let Selection::Export = self.selection {
+                    // export dialog event handling
+                    match action {
+                        Some(Action::Cancel) => {
                             self.selection = Selection::Events;
                         }
-                        KeyCode::Enter => {
-                            match self.search_condition_dialog.get_state() {
-                                Ok(s) => {
-                                    change_search_condition = !self.search_info.is_same_state(&s);
-                                    self.search_info.set_state(s);
-                                }
-                                Err(e) => {
-                                    println!("error: {:?}", e);
-                                }
+                        Some(Action::Confirm) => {
+                            export = Some((
+                                self.export_dialog.get_path(),
+                                self.export_dialog.fetch_all(),
+                            ));
+                            self.selection = Selection::Events;
+                        }
+                        _ => {}
+                    }
+                } else if let Selection::Filter = self.selection {
+                    // filter dialog event handling, the fetched events are filtered again
+                    match action {
+                        Some(Action::Cancel) => {
+                            self.selection = Selection::Events;
+                        }
+                        Some(Action::Confirm) => {
+                            if let Ok(s) = state.as_mut() {
+                                s.set_filter(self.filter_dialog.get_filter());
                             }
                             self.selection = Selection::Events;
                         }
                         _ => {}
                     }
+                } else if let Selection::Streams = self.selection {
+                    // log stream picker event handling
+                    match action {
+                        Some(Action::Cancel) => {
+                            self.selection = Selection::Events;
+                        }
+                        Some(Action::Confirm) => {
+                            let (names, prefix) = self.stream_picker.get_selection();
+                            let mut s = self.search_info.get_state();
+                            s.log_stream_names = names;
+                            s.log_stream_name_prefix = prefix;
+                            change_search_condition = !self.search_info.is_same_state(&s);
+                            self.search_info.set_state(s);
+                            self.selection = Selection::Events;
+                        }
+                        _ => {}
+                    }
+                } else if let Selection::Search = self.selection {
+                    // search condition dialog event handling
+                    match action {
+                        Some(Action::Cancel) => {
+                            self.selection = Selection::Events;
+                        }
+                        Some(Action::Confirm) => {
+                            // on an invalid term the dialog stays open, the error is shown under the inputs
+                            if let Ok(mut s) = self.search_condition_dialog.get_state() {
+                                // log streams are chosen by the log stream picker
+                                let current = self.search_info.get_state();
+                                s.log_stream_names = current.log_stream_names;
+                                s.log_stream_name_prefix = current.log_stream_name_prefix;
+                                self.search_condition_dialog.push_history(&s);
+                                change_search_condition = !self.search_info.is_same_state(&s);
+                                self.search_info.set_state(s);
+                                self.selection = Selection::Events;
+                            }
+                        }
+                        _ => {}
+                    }
                 } else {
-                    match event.code {
-                        KeyCode::Enter => {
+                    match action {
+                        Some(Action::CopyLogEvent) => {
                             if let Ok(s) = state {
-                                if let Some(idx) = s.state.selected() {
-                                    let context: Result<
-                                        ClipboardContext,
-                                        Box<dyn std::error::Error>,
-                                    > = ClipboardProvider::new();
-                                    match context {
-                                        Ok(mut ctx) => {
-                                            if let Some(text) = s.events.get_message(idx) {
-                                                ctx.set_contents(text.clone()).unwrap_or_else(|e| { log::warn!("Failed to write log event message to ClipboardContext: {}", e) });
-                                                log::info!("Log event message has been written to Clipboard. \nlog event: \n{}", &text);
-                                            }
-                                        }
-                                        Err(e) => {
-                                            log::warn!("Failed to get ClipboardContext: {}", e);
-                                        }
-                                    }
+                                if let Some(text) =
+                                    s.state.selected().and_then(|i| s.get_message(i))
+                                {
+                                    copy_to_clipboard(&text);
                                 }
                             }
                         }
-                        KeyCode::Tab => {
+                        Some(Action::ToggleLogEvent) => {
                             if let Ok(mut s) = state {
                                 if let Some(idx) = s.state.selected() {
                                     s.events.toggle_select(idx);
@@ -279,56 +667,183 @@ where
                             }
                             return true;
                         }
-                        KeyCode::Char(c) => match c {
-                            'j' => {
-                                if let Ok(s) = state.as_mut() {
-                                    s.next();
-                                    if !s.is_fetching && s.need_more_fetching() {
-                                        next_token = s.next_token.clone();
-                                        need_more_fetching = true;
-                                    }
+                        Some(Action::NextLogEvent) => {
+                            if let Ok(s) = state.as_mut() {
+                                s.next();
+                                if !s.is_fetching && s.need_more_fetching() {
+                                    next_token = s.next_token.clone();
+                                    need_more_fetching = true;
                                 }
                             }
-                            'J' => {
-                                if let Ok(s) = state.as_mut() {
-                                    s.next_by(*constant::LOGEVENT_STEP);
-                                    if !s.is_fetching && s.need_more_fetching() {
-                                        next_token = s.next_token.clone();
-                                        need_more_fetching = true;
-                                    }
+                        }
+                        Some(Action::NextLogEventByStep) => {
+                            if let Ok(s) = state.as_mut() {
+                                s.next_by(*constant::LOGEVENT_STEP);
+                                if !s.is_fetching && s.need_more_fetching() {
+                                    next_token = s.next_token.clone();
+                                    need_more_fetching = true;
                                 }
                             }
-                            'k' => {
-                                if let Ok(s) = state.as_mut() {
-                                    s.previous();
-                                }
+                        }
+                        Some(Action::PrevLogEvent) => {
+                            if let Ok(s) = state.as_mut() {
+                                s.previous();
+                            }
+                        }
+                        Some(Action::PrevLogEventByStep) => {
+                            if let Ok(s) = state.as_mut() {
+                                s.previous_by(*constant::LOGEVENT_STEP);
                             }
-                            'K' => {
-                                if let Ok(s) = state.as_mut() {
-                                    s.previous_by(*constant::LOGEVENT_STEP);
+                        }
+                        Some(Action::OpenSearchDialog) => {
+                            self.selection = Selection::Search;
+                        }
+                        Some(Action::OpenExportDialog) => {
+                            self.export_dialog.reset(&default_file_name(
+                                &self.log_group_name,
+                                ExportFormat::JsonLines,
+                            ));
+                            self.selection = Selection::Export;
+                        }
+                        Some(Action::OpenStreamPicker) => {
+                            self.stream_picker.reset(&self.search_info.get_state());
+                            self.selection = Selection::Streams;
+                            fetch_log_streams = true;
+                        }
+                        Some(Action::FirstLogEvent) => {
+                            if let Ok(s) = state.as_mut() {
+                                s.cursor_first();
+                            }
+                        }
+                        Some(Action::SwitchStreamColumn) => {
+                            self.stream_column = self.stream_column.next();
+                        }
+                        Some(Action::OpenFilterDialog) => {
+                            if let Ok(s) = state.as_ref() {
+                                self.filter_dialog.reset(s.events.filter());
+                            }
+                            self.selection = Selection::Filter;
+                        }
+                        Some(Action::ToggleNoiseFilter) => {
+                            if let Ok(s) = state.as_mut() {
+                                let mut filter = s.events.filter().clone();
+                                filter.toggle_presets();
+                                s.set_filter(filter);
+                            }
+                        }
+                        Some(Action::CycleMinLevel) => {
+                            if let Ok(s) = state.as_mut() {
+                                let mut filter = s.events.filter().clone();
+                                filter.set_min_level(LogLevel::next_min_level(filter.min_level()));
+                                s.set_filter(filter);
+                            }
+                        }
+                        Some(Action::OpenEventDetail) => {
+                            if let Ok(s) = state.as_ref() {
+                                // the rows of Logs Insights are not log events
+                                if let Some(item) = s
+                                    .state
+                                    .selected()
+                                    .filter(|_| s.insights.is_none())
+                                    .and_then(|i| s.events.items().get(i))
+                                {
+                                    self.event_detail.set_event(item.clone());
+                                    self.selection = Selection::Detail;
                                 }
                             }
-                            's' => {
-                                if let KeyModifiers::CONTROL = event.modifiers {
-                                    self.selection = Selection::Search;
+                        }
+                        Some(Action::ShowContext) => {
+                            if let Ok(s) = state.as_ref() {
+                                if let Some(item) = s
+                                    .state
+                                    .selected()
+                                    .filter(|_| s.insights.is_none())
+                                    .and_then(|i| s.events.items().get(i))
+                                {
+                                    // the log group of the event in the merged timeline
+                                    let log_group_name = self
+                                        .event_sources
+                                        .get(item)
+                                        .unwrap_or_else(|| self.log_group_name.clone());
+                                    fetch_context = Some((log_group_name, item.clone()));
+                                    self.context_view.reset();
+                                    self.selection = Selection::Context;
                                 }
                             }
-                            'g' => {
-                                if let Ok(s) = state.as_mut() {
-                                    s.cursor_first();
+                        }
+                        Some(Action::TogglePrettyJson) => {
+                            self.pretty_json = !self.pretty_json;
+                        }
+                        Some(Action::EditJsonFields) => {
+                            self.json_fields_input = TextBox::new(true);
+                            self.json_fields_input
+                                .set_input(&self.json_fields.join(", "));
+                            self.selection = Selection::JsonFields;
+                        }
+                        Some(Action::OpenLocalSearch) => {
+                            self.local_search_input = TextBox::new(true);
+                            self.local_search_origin =
+                                state.as_ref().ok().and_then(|s| s.state.selected());
+                            self.selection = Selection::LocalSearch;
+                        }
+                        Some(action @ Action::NextMatch | action @ Action::PrevMatch) => {
+                            if let (Some(search), Ok(s)) = (&mut self.local_search, state.as_mut())
+                            {
+                                search.update(&s.events);
+                                let from = s.state.selected();
+                                let idx = if let Action::NextMatch = action {
+                                    search.next_match(from)
+                                } else {
+                                    search.prev_match(from)
+                                };
+                                if idx.is_some() {
+                                    s.state.select(idx);
                                 }
                             }
-                            'G' => {
-                                if let Ok(s) = state.as_mut() {
-                                    s.cursor_last();
+                        }
+                        Some(Action::LastLogEvent) => {
+                            if let Ok(s) = state.as_mut() {
+                                s.cursor_last();
+                            }
+                        }
+                        Some(Action::Retry) => {
+                            if let Ok(s) = state.as_mut() {
+                                // the failed tail is retried by the next tick
+                                if s.error.take().is_some()
+                                    && self.search_info.get_state().mode != SearchMode::Tail
+                                {
+                                    next_token = s.next_token.clone();
+                                    retry = true;
                                 }
                             }
-                            _ => {}
-                        },
+                        }
                         _ => {}
                     }
                 }
             }
+            if fetch_log_streams {
+                let _ = self
+                    .logevent_inst_tx
+                    .send(LogEventEvent::FetchLogStreams(self.log_group_name.clone()))
+                    .await;
+            }
+            if let Some((log_group_name, hit)) = fetch_context {
+                let _ = self
+                    .logevent_inst_tx
+                    .send(LogEventEvent::FetchContext(log_group_name, hit))
+                    .await;
+            }
+            if let Some((path, fetch_all)) = export {
+                let _ = self
+                    .logevent_inst_tx
+                    .send(LogEventEvent::Export(
+                        self.log_group_name.clone(),
+                        Some(self.search_info.get_state()),
+                        path,
+                        fetch_all,
+                    ))
+                    .await;
+            }
             let state = self.search_info.get_state();
             if change_search_condition {
                 let _ = self
@@ -350,6 +865,18 @@ where
                         false,
                     ))
                     .await;
+            } else if retry {
+                // fetch the rest by the token, or all from the start
+                let need_reset = next_token.is_none();
+                let _ = self
+                    .logevent_inst_tx
+                    .send(LogEventEvent::FetchLogEvents(
+                        self.log_group_name.clone(),
+                        next_token,
+                        Some(state),
+                        need_reset,
+                    ))
+                    .await;
             }
         }
         false
@@ -359,56 +886,112 @@ where
         &self,
         maps: &'a mut BTreeMap<KeyEventWrapper, String>,
     ) -> &'a mut BTreeMap<KeyEventWrapper, String> {
-        if let Selection::Search = self.selection {
-            maps.insert(
-                KeyEventWrapper::new(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE)),
-                "Cancel search dialog".to_string(),
-            );
-            maps.insert(
-                KeyEventWrapper::new(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)),
-                "Confirm search dialog".to_string(),
-            );
+        if let Selection::Detail = self.selection {
+            self.event_detail.push_key_maps(maps);
+        } else if let Selection::Context = self.selection {
+            self.context_view.push_key_maps(maps);
+        } else if let Selection::JsonFields = self.selection {
+            KEYMAP.push_key_map(maps, Action::Cancel, "Cancel");
+            KEYMAP.push_key_map(maps, Action::Confirm, "Show the fields");
+            self.json_fields_input.push_key_maps(maps);
+        } else if let Selection::LocalSearch = self.selection {
+            KEYMAP.push_key_map(maps, Action::Cancel, "Clear search");
+            KEYMAP.push_key_map(maps, Action::Confirm, "Keep search");
+            self.local_search_input.push_key_maps(maps);
+        } else if let Selection::Filter = self.selection {
+            KEYMAP.push_key_map(maps, Action::Cancel, "Cancel filter");
+            KEYMAP.push_key_map(maps, Action::Confirm, "Add rule / apply filter");
+            self.filter_dialog.push_key_maps(maps);
+        } else if let Selection::Export = self.selection {
+            KEYMAP.push_key_map(maps, Action::Cancel, "Cancel export");
+            KEYMAP.push_key_map(maps, Action::Confirm, "Export to the file");
+            self.export_dialog.push_key_maps(maps);
+        } else if let Selection::Streams = self.selection {
+            KEYMAP.push_key_map(maps, Action::Cancel, "Cancel log stream picker");
+            KEYMAP.push_key_map(maps, Action::Confirm, "Search the log streams");
+            self.stream_picker.push_key_maps(maps);
+        } else if let Selection::Search = self.selection {
+            KEYMAP.push_key_map(maps, Action::Cancel, "Cancel search dialog");
+            KEYMAP.push_key_map(maps, Action::Confirm, "Confirm search dialog");
             self.search_condition_dialog.push_key_maps(maps);
         } else {
-            maps.insert(
-                KeyEventWrapper::new(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)),
-                "Copy to clipboard".to_string(),
-            );
-            maps.insert(
-                KeyEventWrapper::new(KeyEvent::new(KeyCode::Tab, KeyModifiers::NONE)),
-                "Toggle log event open".to_string(),
-            );
-            maps.insert(
-                KeyEventWrapper::new(KeyEvent::new(KeyCode::Char('j'), KeyModifiers::NONE)),
-                "Next log event".to_string(),
-            );
-            maps.insert(
-                KeyEventWrapper::new(KeyEvent::new(KeyCode::Char('k'), KeyModifiers::NONE)),
-                "Prev log event".to_string(),
-            );
-            maps.insert(
-                KeyEventWrapper::new(KeyEvent::new(KeyCode::Char('s'), KeyModifiers::CONTROL)),
-                "Open search dialog".to_string(),
-            );
+            KEYMAP.push_key_map(maps, Action::CopyLogEvent, "Copy to clipboard");
+            KEYMAP.push_key_map(maps, Action::ToggleLogEvent, "Toggle log event open");
+            KEYMAP.push_key_map(maps, Action::OpenEventDetail, "Event detail");
+            KEYMAP.push_key_map(maps, Action::ShowContext, "Show context");
+            KEYMAP.push_key_map(maps, Action::NextLogEvent, "Next log event");
+            KEYMAP.push_key_map(maps, Action::PrevLogEvent, "Prev log event");
+            KEYMAP.push_key_map(maps, Action::OpenSearchDialog, "Open search dialog");
+            KEYMAP.push_key_map(maps, Action::OpenStreamPicker, "Open log stream picker");
+            KEYMAP.push_key_map(maps, Action::SwitchStreamColumn, "Switch stream column");
+            KEYMAP.push_key_map(maps, Action::OpenExportDialog, "Export log events");
+            KEYMAP.push_key_map(maps, Action::OpenLocalSearch, "Search fetched events");
+            KEYMAP.push_key_map(maps, Action::OpenFilterDialog, "Filter");
+            KEYMAP.push_key_map(maps, Action::TogglePrettyJson, "Pretty JSON");
+            if matches!(self.state.try_lock(), Ok(s) if s.error.is_some()) {
+                KEYMAP.push_key_map(maps, Action::Retry, "Retry");
+            }
+            if self.local_search.is_some() {
+                KEYMAP.push_key_map(maps, Action::NextMatch, "Next match");
+                KEYMAP.push_key_map(maps, Action::PrevMatch, "Prev match");
+            }
         }
         maps
     }
 }
 
+/// The interval of tail mode, which is longer than the tail rate while throttled
+fn format_tail_interval(interval: Duration) -> String {
+    let secs = interval.as_secs_f64();
+    if interval > *constant::TAIL_RATE {
+        format!("polling every {}s (throttled)", secs)
+    } else {
+        format!("polling every {}s", secs)
+    }
+}
+
+/// The error row of the event table, with the key to retry
+fn error_message(error: &str) -> String {
+    format!("{} ({})", error.replace('\n', " "), *RETRY_INSTRUCTION)
+}
+
+/// One-line input with the title at the bottom of the event table
+fn draw_input_popup<B>(f: &mut Frame<'_, B>, area: Rect, input: &mut TextBox<B>, title: &str)
+where
+    B: Backend + Send,
+{
+    let mut popup_area = area;
+    popup_area.height = area.height.min(3);
+    popup_area.y = area.y + area.height - popup_area.height;
+    f.render_widget(Clear, popup_area);
+    // the text box has its own border, the title is drawn over it
+    input.draw(f, popup_area);
+    f.render_widget(
+        Block::default().borders(Borders::ALL).title(title),
+        popup_area,
+    );
+}
+
 // (row, height)
-fn insert_newline(row: &mut String, width: u16) -> (String, u16) {
-    let mut height: u16 = 1;
-    let mut len = row.len() as u16;
-    let mut current_pos = width;
-    if len > width {
-        while current_pos < len {
-            row.insert(current_pos as usize, '\n');
-            len = len.saturating_add(1);
-            current_pos = current_pos.saturating_add(width + 1);
-            height = height.saturating_add(1);
-        }
+/// Byte offsets where the opened message is wrapped at the width
+fn wrap_points(msg: &str, width: u16) -> Vec<usize> {
+    if width == 0 {
+        return vec![];
     }
-    (row.to_string(), height)
+    (1..)
+        .map(|i| i * width as usize)
+        .take_while(|&i| i < msg.len())
+        .filter(|&i| msg.is_char_boundary(i))
+        .collect()
+}
+
+/// The message with the newlines inserted at the wrap points
+fn wrap(msg: &str, breaks: &[usize]) -> String {
+    let mut wrapped = msg.to_string();
+    for &i in breaks.iter().rev() {
+        wrapped.insert(i, '\n');
+    }
+    wrapped
 }
 
 #[cfg(test)]
@@ -419,6 +1002,7 @@ mod tests {
     use tui::{backend::TestBackend, buffer::Buffer, style::Color};
 
     use super::*;
+    use crate::context::EventContext;
     use crate::logevents::LogEvents;
     use crate::state::search_state::SearchMode;
     use crate::test_helper::*;
@@ -551,6 +1135,403 @@ mod tests {
         test_case(&mut event_area, Color::White, lines);
     }
 
+    #[tokio::test]
+    async fn test_draw_stream_column() {
+        let (tx, _) = mpsc::channel(1);
+        let mut event_area: EventArea<TestBackend> = EventArea::new(
+            "test-log-group",
+            Arc::new(Mutex::new(LogEventsState::default())),
+            tx,
+        );
+        let mut events = make_log_events(0, 0, 1609426800000);
+        events[0].log_stream_name = Some(String::from("2021/01/01/[$LATEST]0123456789abcdef"));
+        event_area.state.lock().unwrap().events.set_items(events);
+        event_area.stream_column = StreamColumn::Full;
+        let line = format!(
+            "│>  {} 2021/01/01/[$LATEST]0123 log_event_0                                       │",
+            format_local_time(1609426800000, "%Y-%m-%d %H:%M:%S")
+        );
+        let lines = vec![
+            "query: [], mode: [Tail]                                                                             ",
+            "┌test-log-group────────────────────────────────────────────────────────────────────────────────────┐",
+            "│   Timestamp           Stream                   Event                                             │",
+            &line,
+            "│   Waiting for data...                          ...                                               │",
+            "│                                                                                                  │",
+            "│                                                                                                  │",
+            "│                                                                                                  │",
+            "│                                                                                                  │",
+            "└──────────────────────────────────────────────────────────────────────────────────────────────────┘",
+        ];
+        test_case(&mut event_area, Color::White, lines);
+    }
+
+    #[tokio::test]
+    async fn test_draw_merged() {
+        let (tx, _) = mpsc::channel(1);
+        let names = vec![String::from("api"), String::from("worker")];
+        let sources = EventSources::default();
+        let mut event_area: EventArea<TestBackend> =
+            EventArea::new("api", Arc::new(Mutex::new(LogEventsState::merged())), tx)
+                .with_merged_log_groups(names.clone(), sources.clone());
+        assert_eq!(names, event_area.log_group_names());
+        let events = make_log_events(0, 1, 1609426800000);
+        sources.insert("worker", &events[..1]);
+        sources.insert("api", &events[1..]);
+        event_area.state.lock().unwrap().events.set_items(events);
+        let mut terminal = get_test_terminal(100, 10);
+        terminal
+            .draw(|f| {
+                event_area.draw(f, f.size());
+            })
+            .unwrap();
+        let buffer = terminal.backend().buffer();
+        let line = |y: u16| {
+            (0..100)
+                .map(|x| buffer.get(x, y).symbol.clone())
+                .collect::<String>()
+        };
+        assert!(line(1).starts_with("┌api + worker─"));
+        assert!(line(2).contains("Timestamp           Group            Event"));
+        assert!(line(3).contains(" worker           log_event_0"));
+        assert!(line(4).contains(" api              log_event_1"));
+    }
+
+    #[tokio::test]
+    async fn test_handle_event_local_search() {
+        let mut event_area: EventArea<TestBackend> = EventArea {
+            is_selected: true,
+            ..Default::default()
+        };
+        // log_event_0 .. log_event_5
+        event_area
+            .state
+            .lock()
+            .unwrap()
+            .events
+            .set_items(make_log_events(0, 5, 1609426800000));
+        event_area.state.lock().unwrap().state.select(Some(2));
+        let key = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE);
+        event_area.handle_event(key('/')).await;
+        assert_eq!(Selection::LocalSearch, event_area.selection);
+        // incremental from the cursor
+        for c in "_[14]".chars() {
+            event_area.handle_event(key(c)).await;
+        }
+        assert_eq!(Some(4), event_area.state.lock().unwrap().state.selected());
+        // the last valid regex is kept while typing
+        event_area.handle_event(key('(')).await;
+        assert_eq!("_[14]", event_area.local_search.as_ref().unwrap().pattern());
+        event_area
+            .handle_event(KeyEvent::new(KeyCode::Backspace, KeyModifiers::NONE))
+            .await;
+        event_area
+            .handle_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE))
+            .await;
+        assert_eq!(Selection::Events, event_area.selection);
+        // n/N wraps around the fetched events
+        event_area.handle_event(key('n')).await;
+        assert_eq!(Some(1), event_area.state.lock().unwrap().state.selected());
+        event_area.handle_event(key('N')).await;
+        assert_eq!(Some(4), event_area.state.lock().unwrap().state.selected());
+        // highlighted and counted
+        let mut terminal = get_test_terminal(100, 10);
+        terminal.draw(|f| event_area.draw(f, f.size())).unwrap();
+        let buffer = terminal.backend().buffer();
+        let title = (1..40)
+            .map(|x| buffer.get(x, 1).symbol.clone())
+            .collect::<String>();
+        assert_eq!("Events [/_[14]/ 2 matches]─────────────", title);
+        assert_eq!(Color::Yellow, buffer.get(33, 4).bg);
+        assert_eq!(Color::Reset, buffer.get(32, 4).bg);
+        // cleared with Esc
+        event_area.handle_event(key('/')).await;
+        event_area
+            .handle_event(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE))
+            .await;
+        assert!(event_area.local_search.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_handle_event_filter() {
+        let (tx, mut rx) = mpsc::channel(1);
+        let mut event_area: EventArea<TestBackend> = EventArea {
+            logevent_inst_tx: tx,
+            is_selected: true,
+            ..Default::default()
+        };
+        let mut events = make_log_events(0, 1, 1609426800000);
+        events[0].message = Some(String::from("START RequestId: 1 Version: $LATEST"));
+        event_area
+            .state
+            .lock()
+            .unwrap()
+            .events
+            .push_items(&mut events, false);
+        // the fetched events are filtered by the presets without fetching them again
+        event_area
+            .handle_event(KeyEvent::new(KeyCode::Char('H'), KeyModifiers::SHIFT))
+            .await;
+        assert!(rx.try_recv().is_err());
+        assert_eq!(1, event_area.state.lock().unwrap().events.items().len());
+        let mut terminal = get_test_terminal(100, 10);
+        terminal.draw(|f| event_area.draw(f, f.size())).unwrap();
+        let search_info = (0..50)
+            .map(|x| terminal.backend().buffer().get(x, 0).symbol.clone())
+            .collect::<String>();
+        assert_eq!(
+            "query: [], mode: [1 minute], hidden: [1]          ",
+            search_info
+        );
+        // rules are added in the filter dialog
+        event_area
+            .handle_event(KeyEvent::new(KeyCode::Char('f'), KeyModifiers::NONE))
+            .await;
+        assert_eq!(Selection::Filter, event_area.selection);
+        for c in "-noise".chars() {
+            event_area
+                .handle_event(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE))
+                .await;
+        }
+        event_area
+            .handle_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE))
+            .await;
+        assert_eq!(Selection::Filter, event_area.selection);
+        event_area
+            .handle_event(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE))
+            .await;
+        assert_eq!(Selection::Events, event_area.selection);
+        assert!(event_area
+            .state
+            .lock()
+            .unwrap()
+            .events
+            .filter()
+            .rules()
+            .is_empty());
+        // shown again
+        event_area
+            .handle_event(KeyEvent::new(KeyCode::Char('H'), KeyModifiers::SHIFT))
+            .await;
+        assert!(rx.try_recv().is_err());
+        assert_eq!(2, event_area.state.lock().unwrap().events.items().len());
+    }
+
+    #[tokio::test]
+    async fn test_handle_event_min_level() {
+        let (tx, mut rx) = mpsc::channel(2);
+        let mut event_area: EventArea<TestBackend> = EventArea {
+            logevent_inst_tx: tx,
+            is_selected: true,
+            ..Default::default()
+        };
+        let mut events = make_log_events(0, 2, 1609426800000);
+        events[0].message = Some(String::from("INFO started"));
+        events[1].message = Some(String::from("ERROR failed"));
+        event_area
+            .state
+            .lock()
+            .unwrap()
+            .events
+            .push_items(&mut events, false);
+        // all -> INFO -> WARN, filtered by the detected levels without fetching again
+        for _ in 0..2 {
+            event_area
+                .handle_event(KeyEvent::new(KeyCode::Char('L'), KeyModifiers::SHIFT))
+                .await;
+        }
+        assert!(rx.try_recv().is_err());
+        assert_eq!(2, event_area.state.lock().unwrap().events.items().len());
+        let mut terminal = get_test_terminal(100, 10);
+        terminal.draw(|f| event_area.draw(f, f.size())).unwrap();
+        let buffer = terminal.backend().buffer();
+        let search_info = (0..60)
+            .map(|x| buffer.get(x, 0).symbol.clone())
+            .collect::<String>();
+        assert_eq!(
+            "query: [], mode: [1 minute], level: [WARN+], hidden: [1]    ",
+            search_info
+        );
+        // the rows are coloured by the level
+        let row = |y| {
+            (0..100)
+                .map(|x| buffer.get(x, y).symbol.clone())
+                .collect::<String>()
+        };
+        let y = (0..10).find(|&y| row(y).contains("ERROR failed")).unwrap();
+        assert_eq!(Color::Red, buffer.get(5, y).fg);
+        // the events without the level are shown
+        let y = (0..10).find(|&y| row(y).contains("log_event_2")).unwrap();
+        assert_eq!(Color::Reset, buffer.get(5, y).fg);
+        // WARN -> ERROR -> all
+        for _ in 0..2 {
+            event_area
+                .handle_event(KeyEvent::new(KeyCode::Char('L'), KeyModifiers::SHIFT))
+                .await;
+        }
+        assert_eq!(3, event_area.state.lock().unwrap().events.items().len());
+    }
+
+    #[tokio::test]
+    async fn test_handle_event_detail() {
+        let mut event_area: EventArea<TestBackend> = EventArea {
+            is_selected: true,
+            ..Default::default()
+        };
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        // no event is selected
+        event_area.handle_event(key(KeyCode::Char('v'))).await;
+        assert_eq!(Selection::Events, event_area.selection);
+        {
+            let mut state = event_area.state.lock().unwrap();
+            state
+                .events
+                .push_items(&mut make_log_events(0, 2, 0), false);
+            state.state.select(Some(1));
+        }
+        event_area.handle_event(key(KeyCode::Char('v'))).await;
+        assert_eq!(Selection::Detail, event_area.selection);
+        let mut terminal = get_test_terminal(60, 20);
+        terminal.draw(|f| event_area.draw(f, f.size())).unwrap();
+        let line = |y| {
+            (0..60)
+                .map(|x| terminal.backend().buffer().get(x, y).symbol.clone())
+                .collect::<String>()
+        };
+        assert!(line(0).starts_with("┌Event detail"));
+        assert!(line(2).contains(">> Event ID        1"));
+        assert!(event_area.handle_event(key(KeyCode::Esc)).await);
+        assert_eq!(Selection::Events, event_area.selection);
+    }
+
+    #[tokio::test]
+    async fn test_handle_event_context() {
+        let (tx, mut rx) = mpsc::channel(1);
+        let mut event_area: EventArea<TestBackend> = EventArea {
+            logevent_inst_tx: tx,
+            is_selected: true,
+            ..Default::default()
+        };
+        let mut events = make_log_events(0, 2, 0);
+        events[1].log_stream_name = Some(String::from("stream"));
+        {
+            let mut state = event_area.state.lock().unwrap();
+            state.events.push_items(&mut events.clone(), false);
+            state.state.select(Some(1));
+        }
+        let join = tokio::spawn(async move { rx.recv().await });
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        event_area.handle_event(key(KeyCode::Char('x'))).await;
+        assert_eq!(Selection::Context, event_area.selection);
+        assert_eq!(
+            Some(LogEventEvent::FetchContext(
+                String::from("Events"),
+                events[1].clone()
+            )),
+            join.await.unwrap()
+        );
+        event_area.state.lock().unwrap().context = Some(EventContext::new(events[1].clone()));
+        assert!(event_area.handle_event(key(KeyCode::Esc)).await);
+        assert_eq!(Selection::Events, event_area.selection);
+        assert!(event_area.state.lock().unwrap().context.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_handle_event_retry() {
+        let (tx, mut rx) = mpsc::channel(1);
+        let mut event_area: EventArea<TestBackend> = EventArea {
+            logevent_inst_tx: tx,
+            is_selected: true,
+            ..Default::default()
+        };
+        let key = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::SHIFT);
+        // nothing to retry
+        event_area.handle_event(key('R')).await;
+        assert!(rx.try_recv().is_err());
+        {
+            let mut state = event_area.state.lock().unwrap();
+            state
+                .events
+                .push_items(&mut make_log_events(0, 1, 0), false);
+            state.next_token = Some(String::from("token"));
+            state.set_error(&anyhow::anyhow!("ThrottlingException:\nRate exceeded"));
+        }
+        let mut terminal = get_test_terminal(100, 10);
+        terminal.draw(|f| event_area.draw(f, f.size())).unwrap();
+        let buffer = terminal.backend().buffer();
+        let row = |y| {
+            (0..100)
+                .map(|x| buffer.get(x, y).symbol.clone())
+                .collect::<String>()
+        };
+        let y = (0..10)
+            .find(|&y| {
+                row(y).contains(&format!(
+                    "ThrottlingException: Rate exceeded ({})",
+                    *RETRY_INSTRUCTION
+                ))
+            })
+            .unwrap();
+        assert_eq!(Color::Red, buffer.get(5, y).fg);
+        // the rest of the events are fetched again by the token
+        event_area.handle_event(key('R')).await;
+        assert_eq!(
+            Some(LogEventEvent::FetchLogEvents(
+                String::from("Events"),
+                Some(String::from("token")),
+                Some(event_area.search_info.get_state()),
+                false
+            )),
+            rx.recv().await
+        );
+        assert!(event_area.state.lock().unwrap().error.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_draw_json() {
+        let mut event_area: EventArea<TestBackend> = EventArea {
+            is_selected: true,
+            ..Default::default()
+        };
+        let mut events = make_log_events(0, 1, 1609426800000);
+        events[0].message = Some(String::from(r#"{"level":"ERROR","msg":"timeout"}"#));
+        event_area.state.lock().unwrap().events.set_items(events);
+        event_area.state.lock().unwrap().events.toggle_select(0);
+        let key = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE);
+        event_area.handle_event(key('p')).await;
+        assert!(event_area.pretty_json);
+        // choose the fields
+        event_area
+            .handle_event(KeyEvent::new(KeyCode::Char('C'), KeyModifiers::SHIFT))
+            .await;
+        assert_eq!(Selection::JsonFields, event_area.selection);
+        for c in "level, ,http.status".chars() {
+            event_area.handle_event(key(c)).await;
+        }
+        event_area
+            .handle_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE))
+            .await;
+        assert_eq!(
+            vec![String::from("level"), String::from("http.status")],
+            event_area.json_fields
+        );
+        let mut terminal = get_test_terminal(100, 10);
+        terminal.draw(|f| event_area.draw(f, f.size())).unwrap();
+        let buffer = terminal.backend().buffer();
+        let line = |y: u16| {
+            (0..100)
+                .map(|x| buffer.get(x, y).symbol.clone())
+                .collect::<String>()
+        };
+        assert!(line(2).contains("Timestamp           level            http.status      Event"));
+        // pretty-printed in the opened row
+        assert!(line(3).contains(" ERROR                             {"));
+        assert!(line(4).contains(r#"    "level": "ERROR","#));
+        assert!(line(5).contains(r#"    "msg": "timeout""#));
+        assert!(line(6).contains("  }"));
+        assert!(line(7).contains("    log_event_1"));
+    }
+
     #[tokio::test]
     async fn test_handle_event_basis() {
         let log_group_name = String::from("test_log_gruop");
@@ -708,14 +1689,85 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_handle_event_stream_picker() {
+        let log_group_name = String::from("test_log_gruop");
+        let (tx, mut rx) = mpsc::channel(1);
+        let mut event_area: EventArea<TestBackend> = EventArea {
+            log_group_name: log_group_name.clone(),
+            logevent_inst_tx: tx,
+            is_selected: true,
+            ..Default::default()
+        };
+        // show the log stream picker up and fetch log streams
+        assert!(
+            !event_area
+                .handle_event(KeyEvent::new(KeyCode::Char('l'), KeyModifiers::CONTROL))
+                .await
+        );
+        assert_eq!(event_area.selection, Selection::Streams);
+        assert_eq!(
+            Some(LogEventEvent::FetchLogStreams(log_group_name.clone())),
+            rx.recv().await
+        );
+        event_area.state.lock().unwrap().log_streams =
+            vec![String::from("stream-1"), String::from("stream-2")];
+        // check the first log stream
+        for code in [KeyCode::Down, KeyCode::Tab].iter() {
+            assert!(
+                event_area
+                    .handle_event(KeyEvent::new(*code, KeyModifiers::NONE))
+                    .await
+            );
+        }
+        // Hit Enter, search again with the log stream
+        let join = tokio::spawn(async move {
+            if let Some(LogEventEvent::FetchLogEvents(_, _, Some(state), need_reset)) =
+                rx.recv().await
+            {
+                assert_eq!(vec![String::from("stream-1")], state.log_stream_names);
+                assert!(need_reset);
+            } else {
+                panic!("FetchLogEvents is expected");
+            }
+        });
+        assert!(
+            !event_area
+                .handle_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE))
+                .await
+        );
+        join.await.unwrap();
+        assert_eq!(event_area.selection, Selection::Events);
+        assert_eq!(
+            vec![String::from("stream-1")],
+            event_area.search_info.get_state().log_stream_names
+        );
+    }
+
     #[test]
-    fn test_insert_newline() {
-        let (result_str, result_height) = insert_newline(
-            &mut String::from("1234567890 abcdefghijklmn ABCDEFGHIJKLMN"),
-            5,
+    fn test_format_tail_interval() {
+        assert_eq!(
+            format!("polling every {}s", constant::TAIL_RATE.as_secs_f64()),
+            format_tail_interval(*constant::TAIL_RATE)
         );
+        assert_eq!(
+            format!(
+                "polling every {}s (throttled)",
+                constant::TAIL_RATE.as_secs_f64() * 4.0
+            ),
+            format_tail_interval(*constant::TAIL_RATE * 4)
+        );
+    }
+
+    #[test]
+    fn test_wrap() {
+        let msg = "1234567890 abcdefghijklmn ABCDEFGHIJKLMN";
+        let breaks = wrap_points(msg, 5);
         let expected_str = String::from("12345\n67890\n abcd\nefghi\njklmn\n ABCD\nEFGHI\nJKLMN");
-        assert_eq!(expected_str, result_str);
-        assert_eq!(8, result_height);
+        assert_eq!(expected_str, wrap(msg, &breaks));
+        assert_eq!(7, breaks.len());
+        assert!(wrap_points("12345", 5).is_empty());
+        // not in the middle of a character
+        assert_eq!(vec![2, 6], wrap_points("ab\u{3042}cd", 2));
     }
 }
@@ -0,0 +1,380 @@
+use std::{collections::BTreeMap, marker::PhantomData};
+
+use async_trait::async_trait;
+use crossterm::event::KeyEvent;
+use rusoto_logs::FilteredLogEvent;
+use serde_json::Value;
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::Text,
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph, Wrap},
+    Frame,
+};
+
+use crate::{
+    constant::{self, KEYMAP},
+    json_message,
+    key_event_wrapper::KeyEventWrapper,
+    keymap::{Action, Context},
+    ui::Drawable,
+    utils::*,
+};
+
+/// Field of the log event shown in the detail view. The value is copied to the clipboard as shown.
+#[derive(Debug, Clone, PartialEq)]
+struct Field {
+    name: &'static str,
+    value: String,
+}
+
+/// Full view of one log event with its metadata.
+/// The message of the selected field (raw or pretty-printed) is shown below the fields and can be scrolled.
+pub struct EventDetail<B>
+where
+    B: Backend,
+{
+    event: FilteredLogEvent,
+    fields: Vec<Field>,
+    list_state: ListState,
+    /// the first line of the message shown
+    scroll: u16,
+    /// the number of the lines of the message shown at once, updated when it is drawn
+    page_height: u16,
+    _phantom: PhantomData<B>,
+}
+
+impl<B> EventDetail<B>
+where
+    B: Backend,
+{
+    pub fn new() -> Self {
+        EventDetail {
+            event: FilteredLogEvent::default(),
+            fields: vec![],
+            list_state: ListState::default(),
+            scroll: 0,
+            page_height: 1,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Show the log event from the top
+    pub fn set_event(&mut self, event: FilteredLogEvent) {
+        self.fields = make_fields(&event);
+        self.event = event;
+        self.list_state.select(Some(0));
+        self.scroll = 0;
+    }
+
+    /// The value of the selected field
+    pub fn selected_value(&self) -> Option<&str> {
+        self.list_state
+            .selected()
+            .and_then(|i| self.fields.get(i))
+            .map(|field| field.value.as_str())
+    }
+
+    fn next(&mut self) {
+        let idx = match self.list_state.selected() {
+            Some(i) if i + 1 < self.fields.len() => i + 1,
+            Some(i) => i,
+            None => 0,
+        };
+        self.list_state.select(Some(idx));
+        self.scroll = 0;
+    }
+
+    fn previous(&mut self) {
+        if let Some(i) = self.list_state.selected() {
+            self.list_state.select(Some(i.saturating_sub(1)));
+        }
+        self.scroll = 0;
+    }
+
+    /// The pretty-printed message if it is selected, otherwise the raw message
+    fn message(&self) -> Text<'static> {
+        let msg = self.event.message.as_deref().unwrap_or_default();
+        let pretty = self
+            .list_state
+            .selected()
+            .and_then(|i| self.fields.get(i))
+            .is_some_and(|field| field.name == PRETTY_MESSAGE);
+        match json_message::parse(msg) {
+            Some(object) if pretty => json_message::highlight(&object),
+            _ => Text::from(msg.to_string()),
+        }
+    }
+}
+
+impl<B> Default for EventDetail<B>
+where
+    B: Backend,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl<B> Drawable<B> for EventDetail<B>
+where
+    B: Backend + Send,
+{
+    fn draw(&mut self, f: &mut Frame<'_, B>, area: Rect) {
+        let outer_block = Block::default().borders(Borders::ALL).title("Event detail");
+        let inner_area = get_inner_area(&area);
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(
+                [
+                    Constraint::Length(self.fields.len() as u16 + 2),
+                    Constraint::Min(3),
+                ]
+                .as_ref(),
+            )
+            .split(inner_area);
+
+        let name_width = self.fields.iter().map(|f| f.name.len()).max().unwrap_or(0);
+        let items = self
+            .fields
+            .iter()
+            .map(|field| {
+                // the messages are shown below
+                let value = field.value.lines().next().unwrap_or_default();
+                ListItem::new(format!(
+                    "{:width$}  {}",
+                    field.name,
+                    value,
+                    width = name_width
+                ))
+            })
+            .collect::<Vec<ListItem<'_>>>();
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL))
+            .style(*constant::NORMAL_STYLE)
+            .highlight_style(Style::default().add_modifier(Modifier::BOLD))
+            .highlight_symbol(">> ");
+
+        // clamp the scroll by the lines of the wrapped message
+        let text = self.message();
+        let width = chunks[1].width.saturating_sub(2).max(1) as usize;
+        let lines = text
+            .lines
+            .iter()
+            .map(|spans| spans.width().max(1).div_ceil(width))
+            .sum::<usize>() as u16;
+        self.page_height = chunks[1].height.saturating_sub(2).max(1);
+        self.scroll = self.scroll.min(lines.saturating_sub(self.page_height));
+        let message = Paragraph::new(text)
+            .block(Block::default().borders(Borders::ALL).title(format!(
+                "Message [{}-{}/{}]",
+                (self.scroll + 1).min(lines),
+                (self.scroll + self.page_height).min(lines),
+                lines
+            )))
+            .wrap(Wrap { trim: false })
+            .scroll((self.scroll, 0));
+
+        f.render_widget(Clear, area);
+        f.render_widget(outer_block, area);
+        f.render_stateful_widget(list, chunks[0], &mut self.list_state);
+        f.render_widget(message, chunks[1]);
+    }
+
+    async fn handle_event(&mut self, event: KeyEvent) -> bool {
+        match KEYMAP.action(Context::Dialog, &event) {
+            Some(Action::NextItem) => {
+                self.next();
+            }
+            Some(Action::PrevItem) => {
+                self.previous();
+            }
+            Some(Action::ScrollDown) => {
+                self.scroll = self.scroll.saturating_add(self.page_height);
+            }
+            Some(Action::ScrollUp) => {
+                self.scroll = self.scroll.saturating_sub(self.page_height);
+            }
+            Some(Action::Confirm) => {
+                if let Some(value) = self.selected_value() {
+                    copy_to_clipboard(value);
+                }
+            }
+            // Cancel will be handled by the parent component
+            _ => {
+                return false;
+            }
+        }
+        true
+    }
+
+    fn push_key_maps<'a>(
+        &self,
+        maps: &'a mut BTreeMap<KeyEventWrapper, String>,
+    ) -> &'a mut BTreeMap<KeyEventWrapper, String> {
+        KEYMAP.push_key_map(maps, Action::NextItem, "Next field");
+        KEYMAP.push_key_map(maps, Action::PrevItem, "Prev field");
+        KEYMAP.push_key_map(maps, Action::Confirm, "Copy field");
+        KEYMAP.push_key_map(maps, Action::ScrollDown, "Scroll message down");
+        KEYMAP.push_key_map(maps, Action::ScrollUp, "Scroll message up");
+        KEYMAP.push_key_map(maps, Action::Cancel, "Close detail");
+        maps
+    }
+}
+
+const PRETTY_MESSAGE: &str = "Pretty message";
+
+fn make_fields(event: &FilteredLogEvent) -> Vec<Field> {
+    let field = |name, value: Option<String>| Field {
+        name,
+        value: value.unwrap_or_default(),
+    };
+    let msg = event.message.clone().unwrap_or_default();
+    let mut fields = vec![
+        field("Event ID", event.event_id.clone()),
+        field("Log stream", event.log_stream_name.clone()),
+        field("Timestamp", event.timestamp.map(format_time)),
+        field("Ingestion time", event.ingestion_time.map(format_time)),
+        field(
+            "Ingestion lag",
+            match (event.timestamp, event.ingestion_time) {
+                (Some(timestamp), Some(ingestion_time)) => {
+                    Some(format_lag(ingestion_time - timestamp))
+                }
+                _ => None,
+            },
+        ),
+    ];
+    if let Some(object) = json_message::parse(&msg) {
+        fields.push(field(
+            PRETTY_MESSAGE,
+            serde_json::to_string_pretty(&Value::Object(object)).ok(),
+        ));
+    }
+    fields.push(field("Message", Some(msg)));
+    fields
+}
+
+/// The local time of the epoch milliseconds
+fn format_time(millis: i64) -> String {
+    format_local_time(millis, "%Y-%m-%d %H:%M:%S%.3f %:z")
+}
+
+/// The time between the timestamp and the ingestion time, negative if the event is from the future
+fn format_lag(millis: i64) -> String {
+    let sign = if millis < 0 { "-" } else { "" };
+    let millis = millis.abs();
+    if millis < 1000 {
+        format!("{}{}ms", sign, millis)
+    } else if millis < 60 * 1000 {
+        format!("{}{}.{:03}s", sign, millis / 1000, millis % 1000)
+    } else {
+        format!("{}{}m {}s", sign, millis / 60 / 1000, millis / 1000 % 60)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::{KeyCode, KeyModifiers};
+    use tui::backend::TestBackend;
+
+    use super::*;
+    use crate::test_helper::{get_test_terminal, key_maps_test_case};
+
+    fn make_event(msg: &str) -> FilteredLogEvent {
+        FilteredLogEvent {
+            event_id: Some(String::from("1")),
+            log_stream_name: Some(String::from("stream")),
+            message: Some(msg.to_string()),
+            timestamp: Some(1609426800000),
+            ingestion_time: Some(1609426801500),
+        }
+    }
+
+    #[test]
+    fn test_make_fields() {
+        let fields = make_fields(&make_event(r#"{"a": 1}"#));
+        assert_eq!(
+            vec![
+                "Event ID",
+                "Log stream",
+                "Timestamp",
+                "Ingestion time",
+                "Ingestion lag",
+                "Pretty message",
+                "Message"
+            ],
+            fields.iter().map(|f| f.name).collect::<Vec<&str>>()
+        );
+        assert_eq!("1.500s", fields[4].value);
+        assert_eq!("{\n  \"a\": 1\n}", fields[5].value);
+        // the pretty message is only for JSON
+        let fields = make_fields(&FilteredLogEvent {
+            message: Some(String::from("done")),
+            ..Default::default()
+        });
+        assert_eq!(6, fields.len());
+        assert_eq!("", fields[4].value);
+        assert_eq!("done", fields[5].value);
+    }
+
+    #[test]
+    fn test_format_lag() {
+        assert_eq!("120ms", format_lag(120));
+        assert_eq!("-3.004s", format_lag(-3004));
+        assert_eq!("2m 5s", format_lag(125_000));
+    }
+
+    #[tokio::test]
+    async fn test_handle_event() {
+        let mut detail: EventDetail<TestBackend> = EventDetail::new();
+        let msg = (0..30)
+            .map(|i| format!("line {}", i))
+            .collect::<Vec<String>>()
+            .join("\n");
+        detail.set_event(make_event(&msg));
+        assert_eq!(Some("1"), detail.selected_value());
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        for _ in 0..10 {
+            detail.handle_event(key(KeyCode::Down)).await;
+        }
+        assert_eq!(Some(msg.as_str()), detail.selected_value());
+        let mut terminal = get_test_terminal(40, 20);
+        terminal.draw(|f| detail.draw(f, f.size())).unwrap();
+        let line = |terminal: &tui::Terminal<TestBackend>, y| {
+            (0..40)
+                .map(|x| terminal.backend().buffer().get(x, y).symbol.clone())
+                .collect::<String>()
+        };
+        // 20 - 2 (border) - 8 (fields) - 2 (border) = 8 lines
+        assert!(line(&terminal, 9).contains("Message [1-8/30]"));
+        assert!(line(&terminal, 10).contains("line 0 "));
+        assert!(detail.handle_event(key(KeyCode::PageDown)).await);
+        terminal.draw(|f| detail.draw(f, f.size())).unwrap();
+        assert!(line(&terminal, 9).contains("Message [9-16/30]"));
+        assert!(line(&terminal, 10).contains("line 8 "));
+        // clamped at the bottom
+        for _ in 0..5 {
+            detail.handle_event(key(KeyCode::PageDown)).await;
+        }
+        terminal.draw(|f| detail.draw(f, f.size())).unwrap();
+        assert!(line(&terminal, 9).contains("Message [23-30/30]"));
+        detail.handle_event(key(KeyCode::PageUp)).await;
+        terminal.draw(|f| detail.draw(f, f.size())).unwrap();
+        assert!(line(&terminal, 9).contains("Message [15-22/30]"));
+        // the parent closes it
+        assert!(!detail.handle_event(key(KeyCode::Esc)).await);
+    }
+
+    #[test]
+    fn test_push_key_maps() {
+        let detail: EventDetail<TestBackend> = EventDetail::new();
+        key_maps_test_case(
+            &detail,
+            KeyEvent::new(KeyCode::PageDown, KeyModifiers::NONE),
+            "Scroll message down",
+        );
+    }
+}
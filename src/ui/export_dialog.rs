@@ -0,0 +1,176 @@
+use std::{collections::BTreeMap, marker::PhantomData, path::PathBuf};
+
+use async_trait::async_trait;
+use crossterm::event::KeyEvent;
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+
+use crate::{
+    constant::{self, KEYMAP},
+    key_event_wrapper::KeyEventWrapper,
+    keymap::{Action, Context},
+    ui::{textbox::TextBox, Drawable},
+    utils::*,
+};
+
+/// Dialog to input the file to export log events to.
+/// The format is decided by the extension of the file.
+pub struct ExportDialog<B>
+where
+    B: Backend,
+{
+    path_input: TextBox<B>,
+    fetch_all: bool,
+    _phantom: PhantomData<B>,
+}
+
+impl<B> ExportDialog<B>
+where
+    B: Backend,
+{
+    pub fn new() -> Self {
+        ExportDialog {
+            path_input: TextBox::new(true),
+            fetch_all: true,
+            _phantom: PhantomData,
+        }
+    }
+
+    pub fn reset(&mut self, default_path: &str) {
+        self.path_input.set_input(default_path);
+        self.fetch_all = true;
+    }
+
+    pub fn get_path(&self) -> PathBuf {
+        PathBuf::from(self.path_input.get_input())
+    }
+
+    /// true if the rest of the log events should be fetched by the next token
+    pub fn fetch_all(&self) -> bool {
+        self.fetch_all
+    }
+}
+
+impl<B> Default for ExportDialog<B>
+where
+    B: Backend,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl<B> Drawable<B> for ExportDialog<B>
+where
+    B: Backend + Send,
+{
+    fn draw(&mut self, f: &mut Frame<'_, B>, area: Rect) {
+        let outer_block = Block::default().borders(Borders::ALL).title("Export");
+        let outer_area = get_inner_area(&area);
+        let inner_area = get_inner_area(&outer_area);
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(
+                [
+                    Constraint::Length(1),
+                    Constraint::Length(3),
+                    Constraint::Length(1),
+                ]
+                .as_ref(),
+            )
+            .split(inner_area);
+
+        let path_title =
+            Paragraph::new("File (.jsonl / .csv / others as text)").block(Block::default());
+        let fetch_all = Paragraph::new(format!(
+            "{} fetch all pages",
+            if self.fetch_all { "[X]" } else { "[ ]" }
+        ))
+        .block(Block::default())
+        .style(*constant::NORMAL_STYLE);
+
+        f.render_widget(Clear, outer_area);
+        f.render_widget(outer_block, outer_area);
+        f.render_widget(path_title, chunks[0]);
+        self.path_input.draw(f, chunks[1]);
+        f.render_widget(fetch_all, chunks[2]);
+    }
+
+    async fn handle_event(&mut self, event: KeyEvent) -> bool {
+        match KEYMAP.action(Context::Dialog, &event) {
+            Some(Action::ToggleItem) => {
+                self.fetch_all = !self.fetch_all;
+            }
+            // events Confirm and Cancel will be handled by the parent component
+            Some(Action::Confirm) | Some(Action::Cancel) => {
+                return false;
+            }
+            _ => {
+                self.path_input.handle_event(event).await;
+            }
+        }
+        true
+    }
+
+    fn push_key_maps<'a>(
+        &self,
+        maps: &'a mut BTreeMap<KeyEventWrapper, String>,
+    ) -> &'a mut BTreeMap<KeyEventWrapper, String> {
+        KEYMAP.push_key_map(maps, Action::ToggleItem, "Toggle fetch all pages");
+        self.path_input.push_key_maps(maps);
+        maps
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::{KeyCode, KeyModifiers};
+    use tui::backend::TestBackend;
+
+    use super::*;
+    use crate::test_helper::key_maps_test_case;
+
+    #[tokio::test]
+    async fn test_handle_event() {
+        let mut dialog: ExportDialog<TestBackend> = ExportDialog::new();
+        dialog.reset("out.csv");
+        assert_eq!(PathBuf::from("out.csv"), dialog.get_path());
+        assert!(dialog.fetch_all());
+        // input appended to the default path
+        assert!(
+            dialog
+                .handle_event(KeyEvent::new(KeyCode::Char('x'), KeyModifiers::NONE))
+                .await
+        );
+        assert_eq!(PathBuf::from("out.csvx"), dialog.get_path());
+        assert!(
+            dialog
+                .handle_event(KeyEvent::new(KeyCode::Tab, KeyModifiers::NONE))
+                .await
+        );
+        assert!(!dialog.fetch_all());
+        assert!(
+            !dialog
+                .handle_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE))
+                .await
+        );
+        // reset
+        dialog.reset("out.log");
+        assert!(dialog.fetch_all());
+    }
+
+    #[test]
+    fn test_push_key_maps() {
+        let dialog: ExportDialog<TestBackend> = ExportDialog::new();
+        key_maps_test_case(
+            &dialog,
+            KeyEvent::new(KeyCode::Tab, KeyModifiers::NONE),
+            "Toggle fetch all pages",
+        );
+    }
+}
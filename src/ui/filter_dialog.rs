@@ -0,0 +1,300 @@
+use std::{collections::BTreeMap, marker::PhantomData};
+
+use async_trait::async_trait;
+use crossterm::event::KeyEvent;
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
+    Frame,
+};
+
+use crate::{
+    constant::{self, KEYMAP},
+    key_event_wrapper::KeyEventWrapper,
+    keymap::{Action, Context},
+    local_filter::{FilterRule, LocalFilter, Preset},
+    ui::{textbox::TextBox, Drawable},
+    utils::*,
+};
+
+/// Dialog to edit the local filter of the event area.
+/// The presets are listed first, followed by the rules.
+pub struct FilterDialog<B>
+where
+    B: Backend,
+{
+    filter: LocalFilter,
+    rule_input: TextBox<B>,
+    list_state: ListState,
+    /// shown when the input is not a valid rule
+    error: Option<String>,
+    _phantom: PhantomData<B>,
+}
+
+impl<B> FilterDialog<B>
+where
+    B: Backend,
+{
+    pub fn new() -> Self {
+        FilterDialog {
+            filter: LocalFilter::default(),
+            rule_input: TextBox::new(true),
+            list_state: ListState::default(),
+            error: None,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Start editing the current filter
+    pub fn reset(&mut self, filter: &LocalFilter) {
+        self.filter = filter.clone();
+        self.rule_input.set_input("");
+        self.list_state = ListState::default();
+        self.error = None;
+    }
+
+    pub fn get_filter(&self) -> LocalFilter {
+        self.filter.clone()
+    }
+
+    fn len(&self) -> usize {
+        Preset::ALL.len() + self.filter.rules().len()
+    }
+
+    fn next(&mut self) {
+        let idx = match self.list_state.selected() {
+            Some(i) if i + 1 < self.len() => i + 1,
+            Some(i) => i,
+            None => 0,
+        };
+        self.list_state.select(Some(idx));
+    }
+
+    fn previous(&mut self) {
+        if let Some(i) = self.list_state.selected() {
+            self.list_state.select(Some(i.saturating_sub(1)));
+        }
+    }
+
+    /// Enable/disable the preset or switch the rule between include and exclude
+    fn toggle(&mut self) {
+        match self.list_state.selected() {
+            Some(i) if i < Preset::ALL.len() => self.filter.toggle_preset(Preset::ALL[i]),
+            Some(i) => self.filter.toggle_rule(i - Preset::ALL.len()),
+            None => {}
+        }
+    }
+
+    fn delete(&mut self) {
+        if let Some(i) = self.list_state.selected() {
+            if i >= Preset::ALL.len() {
+                self.filter.remove_rule(i - Preset::ALL.len());
+                if i >= self.len() {
+                    self.list_state.select(Some(self.len() - 1));
+                }
+            }
+        }
+    }
+
+    /// Add the rule of the input. false if the input is empty.
+    fn add_rule(&mut self) -> bool {
+        let input = self.rule_input.get_input();
+        if input.is_empty() {
+            return false;
+        }
+        match FilterRule::parse(&input) {
+            Ok(rule) => {
+                self.filter.push_rule(rule);
+                self.rule_input.set_input("");
+                self.error = None;
+            }
+            Err(e) => {
+                self.error = Some(e.to_string());
+            }
+        }
+        true
+    }
+}
+
+impl<B> Default for FilterDialog<B>
+where
+    B: Backend,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl<B> Drawable<B> for FilterDialog<B>
+where
+    B: Backend + Send,
+{
+    fn draw(&mut self, f: &mut Frame<'_, B>, area: Rect) {
+        let outer_block = Block::default().borders(Borders::ALL).title("Local filter");
+        let outer_area = get_inner_area(&area);
+        let inner_area = get_inner_area(&outer_area);
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(
+                [
+                    Constraint::Length(1),
+                    Constraint::Length(3),
+                    Constraint::Min(1),
+                ]
+                .as_ref(),
+            )
+            .split(inner_area);
+
+        let rule_title = Paragraph::new(match &self.error {
+            Some(e) => format!("Invalid rule: {}", e),
+            None => String::from("Rule (+include, -exclude, /regex/)"),
+        })
+        .block(Block::default());
+        let presets = Preset::ALL.iter().map(|preset| {
+            let check = if self.filter.presets().contains(preset) {
+                "[X]"
+            } else {
+                "[ ]"
+            };
+            ListItem::new(format!("{}{}", check, preset.description()))
+        });
+        let rules = self
+            .filter
+            .rules()
+            .iter()
+            .map(|rule| ListItem::new(format!("   {}", rule)));
+        let list = List::new(presets.chain(rules).collect::<Vec<ListItem<'_>>>())
+            .block(Block::default().borders(Borders::ALL))
+            .style(*constant::NORMAL_STYLE)
+            .highlight_style(Style::default().add_modifier(Modifier::BOLD))
+            .highlight_symbol(">> ");
+
+        f.render_widget(Clear, outer_area);
+        f.render_widget(outer_block, outer_area);
+        f.render_widget(rule_title, chunks[0]);
+        self.rule_input.draw(f, chunks[1]);
+        f.render_stateful_widget(list, chunks[2], &mut self.list_state);
+    }
+
+    async fn handle_event(&mut self, event: KeyEvent) -> bool {
+        match KEYMAP.action(Context::Dialog, &event) {
+            Some(Action::NextItem) => {
+                self.next();
+            }
+            Some(Action::PrevItem) => {
+                self.previous();
+            }
+            Some(Action::ToggleItem) => {
+                self.toggle();
+            }
+            Some(Action::DeleteItem) => {
+                self.delete();
+            }
+            // Confirm with the empty input and Cancel will be handled by the parent component
+            Some(Action::Confirm) => {
+                return self.add_rule();
+            }
+            Some(Action::Cancel) => {
+                return false;
+            }
+            _ => {
+                self.rule_input.handle_event(event).await;
+            }
+        }
+        true
+    }
+
+    fn push_key_maps<'a>(
+        &self,
+        maps: &'a mut BTreeMap<KeyEventWrapper, String>,
+    ) -> &'a mut BTreeMap<KeyEventWrapper, String> {
+        KEYMAP.push_key_map(maps, Action::NextItem, "Next rule");
+        KEYMAP.push_key_map(maps, Action::PrevItem, "Prev rule");
+        KEYMAP.push_key_map(
+            maps,
+            Action::ToggleItem,
+            "Toggle preset / include or exclude",
+        );
+        KEYMAP.push_key_map(maps, Action::DeleteItem, "Delete rule");
+        self.rule_input.push_key_maps(maps);
+        maps
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::{KeyCode, KeyModifiers};
+    use tui::backend::TestBackend;
+
+    use super::*;
+    use crate::test_helper::key_maps_test_case;
+
+    async fn input(dialog: &mut FilterDialog<TestBackend>, text: &str) -> bool {
+        for c in text.chars() {
+            dialog
+                .handle_event(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE))
+                .await;
+        }
+        dialog
+            .handle_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE))
+            .await
+    }
+
+    #[tokio::test]
+    async fn test_handle_event() {
+        let mut dialog: FilterDialog<TestBackend> = FilterDialog::new();
+        assert!(input(&mut dialog, "health").await);
+        assert!(input(&mut dialog, "+/ERROR|WARN/").await);
+        // invalid regex is not added
+        assert!(input(&mut dialog, "-/(/").await);
+        assert!(dialog.error.is_some());
+        dialog.rule_input.set_input("");
+        let rules = |d: &FilterDialog<TestBackend>| {
+            d.get_filter()
+                .rules()
+                .iter()
+                .map(|r| r.to_string())
+                .collect::<Vec<String>>()
+        };
+        assert_eq!(vec!["-health", "+/ERROR|WARN/"], rules(&dialog));
+        // enable the first preset
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        dialog.handle_event(key(KeyCode::Down)).await;
+        dialog.handle_event(key(KeyCode::Tab)).await;
+        assert_eq!(&[Preset::LambdaPlatform], dialog.get_filter().presets());
+        // the presets are not deleted
+        dialog
+            .handle_event(KeyEvent::new(KeyCode::Char('d'), KeyModifiers::CONTROL))
+            .await;
+        assert_eq!(2, dialog.get_filter().rules().len());
+        // the last rule
+        for _ in 0..5 {
+            dialog.handle_event(key(KeyCode::Down)).await;
+        }
+        dialog.handle_event(key(KeyCode::Tab)).await;
+        assert_eq!(vec!["-health", "-/ERROR|WARN/"], rules(&dialog));
+        dialog
+            .handle_event(KeyEvent::new(KeyCode::Char('d'), KeyModifiers::CONTROL))
+            .await;
+        assert_eq!(vec!["-health"], rules(&dialog));
+        assert_eq!(Some(2), dialog.list_state.selected());
+        // parent handles
+        assert!(!dialog.handle_event(key(KeyCode::Enter)).await);
+        assert!(!dialog.handle_event(key(KeyCode::Esc)).await);
+        dialog.reset(&LocalFilter::default());
+        assert!(dialog.get_filter().is_empty());
+    }
+
+    #[test]
+    fn test_push_key_maps() {
+        let dialog: FilterDialog<TestBackend> = FilterDialog::new();
+        key_maps_test_case(
+            &dialog,
+            KeyEvent::new(KeyCode::Char('d'), KeyModifiers::CONTROL),
+            "Delete rule",
+        );
+    }
+}
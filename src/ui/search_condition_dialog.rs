@@ -2,23 +2,28 @@ use std::{
     collections::BTreeMap,
     fmt::{Display, Formatter, Result},
     marker::PhantomData,
+    sync::{Arc, Mutex},
 };
 
+use anyhow::anyhow;
 use async_trait::async_trait;
-use chrono::{DateTime, Local, NaiveDateTime, TimeZone, Utc};
-use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use chrono::Local;
+use crossterm::event::KeyEvent;
 use lazy_static::lazy_static;
 use tui::{
     backend::Backend,
     layout::{Constraint, Direction, Layout, Rect},
-    widgets::{Block, Borders, Paragraph},
+    style::{Color, Modifier, Style},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
     Frame,
 };
 
 use crate::{
-    constant,
+    constant::{self, KEYMAP},
     key_event_wrapper::KeyEventWrapper,
-    state::search_state::*,
+    keymap::{Action, Context},
+    state::{search_history::SearchHistory, search_state::*},
+    time_input,
     ui::{textbox::TextBox, Drawable},
     utils::*,
 };
@@ -146,6 +151,18 @@ impl Radios {
     }
 }
 
+/// shown over the dialog
+enum Popup<B>
+where
+    B: Backend,
+{
+    None,
+    /// name of the search to save
+    SaveAs(TextBox<B>),
+    /// saved searches of the log group
+    Saved(ListState),
+}
+
 pub struct SearchConditionDialog<B>
 where
     B: Backend,
@@ -157,6 +174,13 @@ where
     term_mode: CustomInputMode,
     term_from: TextBox<B>,
     term_to: TextBox<B>,
+    log_group_name: String,
+    history: Arc<Mutex<SearchHistory>>,
+    /// position in the history while browsing it with Up/Down in the query
+    history_idx: Option<usize>,
+    /// the condition being edited before browsing the history
+    draft: Option<SearchState>,
+    popup: Popup<B>,
     _phantom: PhantomData<B>,
 }
 
@@ -173,10 +197,177 @@ where
             term_mode: CustomInputMode::None,
             term_from: TextBox::new(false),
             term_to: TextBox::new(false),
+            log_group_name: String::default(),
+            history: Arc::new(Mutex::new(SearchHistory::default())),
+            history_idx: None,
+            draft: None,
+            popup: Popup::None,
             _phantom: PhantomData,
         }
     }
 
+    /// Share the history of the searches in the log group
+    pub fn with_history(
+        mut self,
+        log_group_name: &str,
+        history: Arc<Mutex<SearchHistory>>,
+    ) -> Self {
+        self.log_group_name = log_group_name.to_string();
+        self.history = history;
+        self
+    }
+
+    /// Show the condition in the inputs
+    pub fn set_state(&mut self, state: &SearchState) {
+        self.state = state.clone();
+        self.query_input.set_input(&state.query);
+        self.radios.select(&state.mode);
+        let (from, to) = match state.mode {
+            SearchMode::FromTo(from, to) => (from, to),
+            _ => (None, None),
+        };
+        self.term_from.set_input(&format_timestamp(from));
+        self.term_to.set_input(&format_timestamp(to));
+    }
+
+    /// Record the confirmed condition as the newest one in the history
+    pub fn push_history(&mut self, state: &SearchState) {
+        self.history
+            .lock()
+            .unwrap()
+            .push(&self.log_group_name, state.clone());
+        self.history_idx = None;
+        self.draft = None;
+    }
+
+    /// Replace the inputs with the older search
+    fn history_previous(&mut self) {
+        let history = self
+            .history
+            .lock()
+            .unwrap()
+            .history(&self.log_group_name)
+            .to_vec();
+        if history.is_empty() {
+            return;
+        }
+        let idx = match self.history_idx {
+            Some(i) => i.saturating_sub(1),
+            None => {
+                let mut draft = self.state.clone();
+                draft.query = self.query_input.get_input();
+                self.draft = Some(draft);
+                history.len() - 1
+            }
+        };
+        self.history_idx = Some(idx);
+        self.set_state(&history[idx]);
+    }
+
+    /// Replace the inputs with the newer search, or the draft after the newest one.
+    /// Returns false if the history is not being browsed.
+    fn history_next(&mut self) -> bool {
+        let idx = match self.history_idx {
+            Some(i) => i + 1,
+            None => return false,
+        };
+        let history = self
+            .history
+            .lock()
+            .unwrap()
+            .history(&self.log_group_name)
+            .to_vec();
+        if let Some(state) = history.get(idx) {
+            self.history_idx = Some(idx);
+            self.set_state(state);
+        } else {
+            self.history_idx = None;
+            if let Some(draft) = self.draft.take() {
+                self.set_state(&draft);
+            }
+        }
+        true
+    }
+
+    fn saved_searches(&self) -> Vec<(String, SearchState)> {
+        self.history
+            .lock()
+            .unwrap()
+            .saved(&self.log_group_name)
+            .into_iter()
+            .map(|(name, state)| (name.clone(), state.clone()))
+            .collect()
+    }
+
+    async fn handle_popup_event(&mut self, event: KeyEvent)
+    where
+        B: Send,
+    {
+        let action = KEYMAP.action(Context::Dialog, &event);
+        // put back unless closed
+        let popup = std::mem::replace(&mut self.popup, Popup::None);
+        self.popup = match popup {
+            Popup::SaveAs(mut name_input) => match action {
+                Some(Action::Confirm) => {
+                    let name = name_input.get_input();
+                    match self.get_state() {
+                        Ok(state) if !name.is_empty() => {
+                            self.history.lock().unwrap().save_as(
+                                &self.log_group_name,
+                                &name,
+                                state,
+                            );
+                        }
+                        Ok(_) => {}
+                        Err(e) => log::warn!("Failed to save the search: {}", e),
+                    }
+                    Popup::None
+                }
+                Some(Action::Cancel) => Popup::None,
+                _ => {
+                    name_input.handle_event(event).await;
+                    Popup::SaveAs(name_input)
+                }
+            },
+            Popup::Saved(mut list_state) => {
+                let saved = self.saved_searches();
+                let selected = list_state.selected().and_then(|i| saved.get(i));
+                match action {
+                    Some(Action::NextItem) => {
+                        let idx = list_state.selected().map_or(0, |i| i + 1);
+                        if idx < saved.len() {
+                            list_state.select(Some(idx));
+                        }
+                    }
+                    Some(Action::PrevItem) => {
+                        if let Some(i) = list_state.selected() {
+                            list_state.select(Some(i.saturating_sub(1)));
+                        }
+                    }
+                    Some(Action::DeleteItem) => {
+                        if let Some((name, _)) = selected {
+                            self.history
+                                .lock()
+                                .unwrap()
+                                .remove_saved(&self.log_group_name, name);
+                            list_state.select(None);
+                        }
+                    }
+                    Some(Action::Confirm) => {
+                        if let Some((_, state)) = selected {
+                            self.set_state(state);
+                        }
+                        return;
+                    }
+                    Some(Action::Cancel) => return,
+                    _ => {}
+                }
+                Popup::Saved(list_state)
+            }
+            Popup::None => Popup::None,
+        };
+    }
+
     pub fn get_state(&self) -> anyhow::Result<SearchState> {
         let mut s = self.state.clone();
         s.mode = if let SearchMode::FromTo(_, _) = s.mode {
@@ -261,30 +452,92 @@ where
         }
     }
 
+    /// Parse the custom term inputs by `time_input::parse`. The error tells which input is invalid.
     fn get_timestamps(&self) -> anyhow::Result<(Option<i64>, Option<i64>)> {
-        let fmt = &*constant::DATE_FORMAT;
-        let from = if self.term_from.get_input().is_empty() {
-            None
-        } else {
-            let naive = NaiveDateTime::parse_from_str(&self.term_from.get_input(), &fmt)?;
-            let local = Local.from_local_datetime(&naive).unwrap();
-            let utc: DateTime<Utc> = DateTime::from(local);
-            Some(utc.timestamp_millis())
-        };
-        let to = if self.term_to.get_input().is_empty() {
-            None
-        } else {
-            let naive = NaiveDateTime::parse_from_str(&self.term_to.get_input(), &fmt)?;
-            let local = Local.from_local_datetime(&naive).unwrap();
-            let utc: DateTime<Utc> = DateTime::from(local);
-            Some(utc.timestamp_millis())
+        let now = Local::now();
+        let parse = |label: &str, input: String| -> anyhow::Result<Option<i64>> {
+            if input.trim().is_empty() {
+                return Ok(None);
+            }
+            time_input::parse(&input, now, &constant::DATE_FORMAT)
+                .map(Some)
+                .map_err(|e| anyhow!("{}: {}", label, e))
         };
+        let from = parse("from", self.term_from.get_input())?;
+        let to = parse("to", self.term_to.get_input())?;
+        if let (Some(f), Some(t)) = (from, to) {
+            if f > t {
+                return Err(anyhow!("from is after to"));
+            }
+        }
         Ok((from, to))
     }
 
+    /// The parsed absolute range or the validation error under the custom term inputs,
+    /// None while both inputs are empty
+    fn term_preview(&self) -> Option<(String, Style)> {
+        if self.term_from.get_input().is_empty() && self.term_to.get_input().is_empty() {
+            return None;
+        }
+        Some(match self.get_timestamps() {
+            Ok((from, to)) => (
+                format!("{} ~ {}", format_timestamp(from), format_timestamp(to)),
+                *constant::NORMAL_STYLE,
+            ),
+            Err(e) => (e.to_string(), Style::default().fg(Color::Red)),
+        })
+    }
+
     pub fn is_tail(&self) -> bool {
         self.state.mode == SearchMode::Tail
     }
+
+    fn draw_popup(&mut self, f: &mut Frame<'_, B>, area: Rect)
+    where
+        B: Send,
+    {
+        let area = get_inner_area(&get_inner_area(&area));
+        let saved = self.saved_searches();
+        match &mut self.popup {
+            Popup::SaveAs(name_input) => {
+                let mut popup_area = area;
+                popup_area.height = popup_area.height.min(5);
+                let block = Block::default()
+                    .borders(Borders::ALL)
+                    .title("Save search as");
+                f.render_widget(Clear, popup_area);
+                f.render_widget(block, popup_area);
+                name_input.draw(f, get_inner_area(&popup_area));
+            }
+            Popup::Saved(list_state) => {
+                let items = saved
+                    .iter()
+                    .map(|(name, state)| {
+                        ListItem::new(format!("{}: {} [{}]", name, state.query, state.mode))
+                    })
+                    .collect::<Vec<ListItem<'_>>>();
+                let list = List::new(items)
+                    .block(
+                        Block::default()
+                            .borders(Borders::ALL)
+                            .title("Saved searches"),
+                    )
+                    .style(*constant::NORMAL_STYLE)
+                    .highlight_style(Style::default().add_modifier(Modifier::BOLD))
+                    .highlight_symbol(">> ");
+                f.render_widget(Clear, area);
+                f.render_stateful_widget(list, area, list_state);
+            }
+            Popup::None => {}
+        }
+    }
+
+    fn toggle_query_mode(&mut self) {
+        self.state.query_mode = match self.state.query_mode {
+            QueryMode::Filter => QueryMode::Insights,
+            QueryMode::Insights => QueryMode::Filter,
+        };
+    }
 }
 
 impl<B> Default for SearchConditionDialog<B>
@@ -300,11 +553,23 @@ where
             term_mode: CustomInputMode::From,
             term_from: TextBox::default(),
             term_to: TextBox::default(),
+            log_group_name: String::default(),
+            history: Arc::new(Mutex::new(SearchHistory::default())),
+            history_idx: None,
+            draft: None,
+            popup: Popup::None,
             _phantom: PhantomData,
         }
     }
 }
 
+/// The timestamp in DATE_FORMAT (local time) for the custom term inputs
+fn format_timestamp(timestamp: Option<i64>) -> String {
+    timestamp
+        .map(|t| format_local_time(t, &constant::DATE_FORMAT))
+        .unwrap_or_default()
+}
+
 #[async_trait]
 impl<B> Drawable<B> for SearchConditionDialog<B>
 where
@@ -312,9 +577,12 @@ where
 {
     fn draw(&mut self, f: &mut Frame<'_, B>, area: Rect) {
         // compute draw area
-        let outer_block = Block::default()
-            .borders(Borders::ALL)
-            .title("Search Condition");
+        let (title, query_label) = if self.state.is_insights() {
+            ("Search Condition [Logs Insights]", "Query (Logs Insights)")
+        } else {
+            ("Search Condition", "Query")
+        };
+        let outer_block = Block::default().borders(Borders::ALL).title(title);
         let outer_area = get_inner_area(&area);
         let inner_area = get_inner_area(&outer_area);
         // prepare inner area
@@ -332,7 +600,7 @@ where
             .split(inner_area);
 
         // input query
-        let query_title = Paragraph::new("Query").block(Block::default());
+        let query_title = Paragraph::new(query_label).block(Block::default());
 
         // input term
         let radio_areas = Layout::default()
@@ -395,32 +663,57 @@ where
         self.term_from.draw(f, custom_input_areas[0]);
         f.render_widget(paragraph, custom_input_areas[1]);
         self.term_to.draw(f, custom_input_areas[2]);
+        if let Some((text, style)) = self.term_preview() {
+            let last = radio_areas.last().unwrap();
+            let preview_area = Rect::new(last.x, last.y + 3, last.width, 1);
+            if preview_area.bottom() <= inner_area.bottom() {
+                f.render_widget(Paragraph::new(text).style(style), preview_area);
+            }
+        }
+        self.draw_popup(f, area);
     }
 
     async fn handle_event(&mut self, event: KeyEvent) -> bool {
+        if !matches!(self.popup, Popup::None) {
+            self.handle_popup_event(event).await;
+            return true;
+        }
+        // must be checked before the text boxes consume the character
+        if KEYMAP.is(Action::ToggleQueryMode, &event) {
+            self.toggle_query_mode();
+            return true;
+        }
         if !self.query_input.handle_event(event).await
             && !self.term_from.handle_event(event).await
             && !self.term_to.handle_event(event).await
         {
-            match event.code {
-                KeyCode::Down => {
+            match KEYMAP.action(Context::Dialog, &event) {
+                // the query box goes to the newer search first
+                Some(Action::NextItem) if self.focus != 0 || !self.history_next() => {
                     self.next();
                 }
-                KeyCode::Up => {
-                    self.previous();
+                Some(Action::PrevItem) => {
+                    if self.focus == 0 {
+                        self.history_previous();
+                    } else {
+                        self.previous();
+                    }
+                }
+                Some(Action::SaveSearch) => {
+                    self.popup = Popup::SaveAs(TextBox::new(true));
+                }
+                Some(Action::OpenSavedSearches) => {
+                    self.popup = Popup::Saved(ListState::default());
                 }
-                KeyCode::Char(' ') => {
+                Some(Action::SelectItem) => {
                     self.select();
                 }
-                KeyCode::Tab => {
+                Some(Action::ToggleItem) => {
                     self.toggle_term_mode();
                     self.update_input_states();
                 }
-                // events Enter and Esc will be handled by the parent component
-                KeyCode::Enter => {
-                    return false;
-                }
-                KeyCode::Esc => {
+                // events Confirm and Cancel will be handled by the parent component
+                Some(Action::Confirm) | Some(Action::Cancel) => {
                     return false;
                 }
                 _ => {}
@@ -433,22 +726,13 @@ where
         &self,
         maps: &'a mut BTreeMap<KeyEventWrapper, String>,
     ) -> &'a mut BTreeMap<KeyEventWrapper, String> {
-        maps.insert(
-            KeyEventWrapper::new(KeyEvent::new(KeyCode::Down, KeyModifiers::NONE)),
-            "Move focus".to_string(),
-        );
-        maps.insert(
-            KeyEventWrapper::new(KeyEvent::new(KeyCode::Up, KeyModifiers::NONE)),
-            "Move focus".to_string(),
-        );
-        maps.insert(
-            KeyEventWrapper::new(KeyEvent::new(KeyCode::Char(' '), KeyModifiers::NONE)),
-            "Select the period".to_string(),
-        );
-        maps.insert(
-            KeyEventWrapper::new(KeyEvent::new(KeyCode::Tab, KeyModifiers::NONE)),
-            "Toggle period focus".to_string(),
-        );
+        KEYMAP.push_key_map(maps, Action::NextItem, "Move focus");
+        KEYMAP.push_key_map(maps, Action::PrevItem, "Move focus");
+        KEYMAP.push_key_map(maps, Action::SelectItem, "Select the period");
+        KEYMAP.push_key_map(maps, Action::ToggleItem, "Toggle period focus");
+        KEYMAP.push_key_map(maps, Action::ToggleQueryMode, "Toggle Logs Insights");
+        KEYMAP.push_key_map(maps, Action::SaveSearch, "Save search");
+        KEYMAP.push_key_map(maps, Action::OpenSavedSearches, "Saved searches");
         self.query_input.push_key_maps(maps);
         self.term_from.push_key_maps(maps);
         self.term_to.push_key_maps(maps);
@@ -458,8 +742,9 @@ where
 
 #[cfg(test)]
 mod tests {
+    use chrono::{TimeZone, Utc};
     use crossterm::event::{KeyCode, KeyModifiers};
-    use tui::{backend::TestBackend, buffer::Buffer, style::Color};
+    use tui::{backend::TestBackend, buffer::Buffer};
 
     use super::*;
     use crate::test_helper::{get_test_terminal, key_maps_test_case};
@@ -810,6 +1095,49 @@ mod tests {
         assert_eq!(chrono::Duration::days(9), to - from);
     }
 
+    #[test]
+    fn test_term_preview() {
+        let mut dialog: SearchConditionDialog<TestBackend> =
+            SearchConditionDialog::new(SearchState::default());
+        let mut terminal = get_test_terminal(50, 20);
+        let mut draw = |dialog: &mut SearchConditionDialog<TestBackend>| {
+            terminal.draw(|f| dialog.draw(f, f.size())).unwrap();
+            let buffer = terminal.backend().buffer().clone();
+            let line = (2..48)
+                .map(|x| buffer.get(x, 16).symbol.clone())
+                .collect::<String>();
+            (line.trim_end().to_string(), buffer.get(2, 16).fg)
+        };
+        // the absolute range
+        dialog.term_from.set_input("2021-01-01T00:00:00Z");
+        dialog.term_to.set_input("1609462800000");
+        let (line, fg) = draw(&mut dialog);
+        assert_eq!(
+            format!(
+                "{} ~ {}",
+                format_timestamp(Some(1609459200000)),
+                format_timestamp(Some(1609462800000))
+            ),
+            line
+        );
+        assert_eq!(Color::White, fg);
+        // relative times are parsed
+        dialog.term_from.set_input("-15m");
+        dialog.term_to.set_input("now");
+        let (from, to) = dialog.get_timestamps().unwrap();
+        assert_eq!(15 * 60 * 1000, to.unwrap() - from.unwrap());
+        // the errors
+        dialog.term_from.set_input("next week");
+        let (line, fg) = draw(&mut dialog);
+        assert_eq!("from: invalid time \"next week\"", line);
+        assert_eq!(Color::Red, fg);
+        dialog.term_from.set_input("now");
+        dialog.term_to.set_input("1h ago");
+        assert_eq!("from is after to", draw(&mut dialog).0);
+        dialog.state.mode = SearchMode::FromTo(None, None);
+        assert!(dialog.get_state().is_err());
+    }
+
     #[test]
     fn test_push_key_maps() {
         let dialog: SearchConditionDialog<TestBackend> =
@@ -834,5 +1162,132 @@ mod tests {
             KeyEvent::new(KeyCode::Tab, KeyModifiers::NONE),
             "Toggle period focus",
         );
+        key_maps_test_case(
+            &dialog,
+            KeyEvent::new(KeyCode::Char('t'), KeyModifiers::CONTROL),
+            "Toggle Logs Insights",
+        );
+    }
+
+    #[tokio::test]
+    async fn test_dialog_toggle_query_mode() {
+        let mut dialog: SearchConditionDialog<TestBackend> =
+            SearchConditionDialog::new(SearchState::default());
+        assert!(!dialog.get_state().unwrap().is_insights());
+        assert!(
+            dialog
+                .handle_event(KeyEvent::new(KeyCode::Char('t'), KeyModifiers::CONTROL))
+                .await
+        );
+        assert!(dialog.get_state().unwrap().is_insights());
+        // not typed into the query
+        assert_eq!(String::default(), dialog.query_input.get_input());
+        dialog
+            .handle_event(KeyEvent::new(KeyCode::Char('t'), KeyModifiers::CONTROL))
+            .await;
+        assert!(!dialog.get_state().unwrap().is_insights());
+    }
+
+    async fn press(dialog: &mut SearchConditionDialog<TestBackend>, code: KeyCode) {
+        dialog
+            .handle_event(KeyEvent::new(code, KeyModifiers::NONE))
+            .await;
+    }
+
+    async fn type_text(dialog: &mut SearchConditionDialog<TestBackend>, text: &str) {
+        for ch in text.chars() {
+            press(dialog, KeyCode::Char(ch)).await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dialog_history() {
+        let history = Arc::new(Mutex::new(SearchHistory::default()));
+        let mut dialog: SearchConditionDialog<TestBackend> =
+            SearchConditionDialog::new(SearchState::default())
+                .with_history("group", Arc::clone(&history));
+        let first = SearchState::new(String::from("ERROR"), SearchMode::OneHour);
+        let second = SearchState::new(String::from("WARN"), SearchMode::FromTo(Some(0), None));
+        dialog.push_history(&first);
+        dialog.push_history(&second);
+        // the other log group
+        history
+            .lock()
+            .unwrap()
+            .push("other", SearchState::default());
+        type_text(&mut dialog, "draft").await;
+        // Up: older
+        press(&mut dialog, KeyCode::Up).await;
+        assert_eq!(second.query, dialog.query_input.get_input());
+        assert_eq!(format_timestamp(Some(0)), dialog.term_from.get_input());
+        assert!(dialog.radios.custom.is_selected);
+        press(&mut dialog, KeyCode::Up).await;
+        press(&mut dialog, KeyCode::Up).await;
+        assert_eq!(first, dialog.get_state().unwrap());
+        assert_eq!(0, dialog.focus);
+        // Down: newer, then the draft
+        press(&mut dialog, KeyCode::Down).await;
+        assert_eq!(second.query, dialog.query_input.get_input());
+        press(&mut dialog, KeyCode::Down).await;
+        assert_eq!("draft", dialog.query_input.get_input());
+        assert_eq!(SearchMode::OneMinute, dialog.state.mode);
+        assert_eq!(0, dialog.focus);
+        // Down moves the focus after browsing
+        press(&mut dialog, KeyCode::Down).await;
+        assert_eq!(1, dialog.focus);
+    }
+
+    #[tokio::test]
+    async fn test_dialog_saved_searches() {
+        let history = Arc::new(Mutex::new(SearchHistory::default()));
+        let mut dialog: SearchConditionDialog<TestBackend> =
+            SearchConditionDialog::new(SearchState::default())
+                .with_history("group", Arc::clone(&history));
+        type_text(&mut dialog, "ERROR").await;
+        // save as "errors"
+        dialog
+            .handle_event(KeyEvent::new(KeyCode::Char('s'), KeyModifiers::CONTROL))
+            .await;
+        type_text(&mut dialog, "errors").await;
+        // Enter is not passed to the parent while naming
+        assert!(
+            dialog
+                .handle_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE))
+                .await
+        );
+        assert_eq!("ERROR", dialog.query_input.get_input());
+        assert_eq!(
+            vec![(
+                &String::from("errors"),
+                &SearchState::new(String::from("ERROR"), SearchMode::OneMinute)
+            )],
+            history.lock().unwrap().saved("group")
+        );
+        // recall it
+        dialog.set_state(&SearchState::default());
+        dialog
+            .handle_event(KeyEvent::new(KeyCode::Char('r'), KeyModifiers::CONTROL))
+            .await;
+        press(&mut dialog, KeyCode::Down).await;
+        press(&mut dialog, KeyCode::Enter).await;
+        assert_eq!("ERROR", dialog.query_input.get_input());
+        assert!(matches!(dialog.popup, Popup::None));
+        // delete it
+        dialog
+            .handle_event(KeyEvent::new(KeyCode::Char('r'), KeyModifiers::CONTROL))
+            .await;
+        press(&mut dialog, KeyCode::Down).await;
+        dialog
+            .handle_event(KeyEvent::new(KeyCode::Char('d'), KeyModifiers::CONTROL))
+            .await;
+        assert!(history.lock().unwrap().saved("group").is_empty());
+        press(&mut dialog, KeyCode::Esc).await;
+        assert!(matches!(dialog.popup, Popup::None));
+        // Esc is passed to the parent again
+        assert!(
+            !dialog
+                .handle_event(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE))
+                .await
+        );
     }
 }
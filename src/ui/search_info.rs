@@ -9,13 +9,17 @@ use tui::{
     Frame,
 };
 
-use crate::{state::search_state::*, ui::Drawable};
+use crate::{log_level::LogLevel, state::search_state::*, ui::Drawable};
 
 pub struct SearchInfo<B>
 where
     B: Backend,
 {
     state: SearchState,
+    /// the number of the events hidden by the local filter, if any rule is set
+    hidden: Option<usize>,
+    /// the minimum level of the local filter
+    min_level: Option<LogLevel>,
     _phantom: PhantomData<B>,
 }
 
@@ -26,12 +30,37 @@ where
     pub fn new(state: SearchState) -> Self {
         SearchInfo {
             state,
+            hidden: None,
+            min_level: None,
             _phantom: PhantomData,
         }
     }
 
     fn get_msg(&self) -> String {
-        format!("query: [{}], mode: [{}]", self.state.query, self.state.mode)
+        let msg = if self.state.is_insights() {
+            format!(
+                "insights: [{}], mode: [{}]",
+                self.state.query, self.state.mode
+            )
+        } else {
+            format!("query: [{}], mode: [{}]", self.state.query, self.state.mode)
+        };
+        let msg = match self.min_level {
+            Some(level) => format!("{}, level: [{}+]", msg, level),
+            None => msg,
+        };
+        match self.hidden {
+            Some(hidden) => format!("{}, hidden: [{}]", msg, hidden),
+            None => msg,
+        }
+    }
+
+    pub fn set_hidden(&mut self, hidden: Option<usize>) {
+        self.hidden = hidden;
+    }
+
+    pub fn set_min_level(&mut self, min_level: Option<LogLevel>) {
+        self.min_level = min_level;
     }
 
     pub fn set_state(&mut self, new_state: SearchState) {
@@ -54,6 +83,8 @@ where
     fn default() -> Self {
         SearchInfo {
             state: SearchState::default(),
+            hidden: None,
+            min_level: None,
             _phantom: PhantomData,
         }
     }
@@ -92,7 +123,7 @@ mod tests {
     use tui::backend::TestBackend;
 
     use super::*;
-    use crate::state::search_state::{SearchMode, SearchState};
+    use crate::state::search_state::{QueryMode, SearchMode, SearchState};
 
     #[test]
     fn test_set_state() {
@@ -104,4 +135,24 @@ mod tests {
         search_info.set_state(state.clone());
         assert!(search_info.is_same_state(&state));
     }
+
+    #[test]
+    fn test_get_msg() {
+        let mut state = SearchState::new(String::from("fields @message"), SearchMode::OneHour);
+        let search_info: SearchInfo<TestBackend> = SearchInfo::new(state.clone());
+        assert!(search_info
+            .get_msg()
+            .starts_with("query: [fields @message]"));
+        state.query_mode = QueryMode::Insights;
+        let mut search_info: SearchInfo<TestBackend> = SearchInfo::new(state);
+        assert!(search_info
+            .get_msg()
+            .starts_with("insights: [fields @message]"));
+        search_info.set_hidden(Some(12));
+        assert!(search_info.get_msg().ends_with(", hidden: [12]"));
+        search_info.set_min_level(Some(LogLevel::Warn));
+        assert!(search_info
+            .get_msg()
+            .ends_with(", level: [WARN+], hidden: [12]"));
+    }
 }
@@ -6,6 +6,7 @@ use std::{
 
 use async_trait::async_trait;
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use tokio::sync::mpsc;
 use tui::{
     backend::Backend,
     layout::Rect,
@@ -15,8 +16,13 @@ use tui::{
 };
 
 use crate::{
-    constant, key_event_wrapper::KeyEventWrapper, loader::Loader,
-    state::loggroups_state::LogGroupsState, ui::Drawable,
+    constant::{self, KEYMAP},
+    event::LogGroupEvent,
+    key_event_wrapper::KeyEventWrapper,
+    keymap::{Action, Context},
+    loader::Loader,
+    state::loggroups_state::LogGroupsState,
+    ui::Drawable,
 };
 
 pub struct SideMenu<B>
@@ -28,6 +34,8 @@ where
     selected_log_groups: Vec<String>,
     loader: Loader,
     query: String,
+    /// sends the fetch of the log groups again after the failure
+    loggroup_inst_tx: Option<mpsc::Sender<LogGroupEvent>>,
     _phantom: PhantomData<B>,
 }
 
@@ -42,17 +50,36 @@ where
             selected_log_groups: vec![],
             loader: Loader::new(constant::LOADER.clone()),
             query: String::from(""),
+            loggroup_inst_tx: None,
             _phantom: PhantomData,
         }
     }
 
+    /// Fetch the log groups again by the retry key after the failure
+    pub fn with_loggroup_inst_tx(mut self, loggroup_inst_tx: mpsc::Sender<LogGroupEvent>) -> Self {
+        self.loggroup_inst_tx = Some(loggroup_inst_tx);
+        self
+    }
+
     pub fn set_select(&mut self, select: bool) {
         self.is_selected = select;
     }
 
+    /// The failure of the last fetch of the log groups
+    pub fn error(&self) -> Option<String> {
+        self.state.try_lock().ok().and_then(|s| s.error.clone())
+    }
+
     pub fn selected_log_groups(&self) -> &Vec<String> {
         self.selected_log_groups.as_ref()
     }
+
+    /// Select the log groups without the key input, e.g. by the workspace.
+    /// They are checked in the list after the log groups are fetched.
+    pub fn set_selected_log_groups(&mut self, log_groups: Vec<String>) {
+        self.state.lock().unwrap().select_later(log_groups.clone());
+        self.selected_log_groups = log_groups;
+    }
 }
 
 impl<B> Default for SideMenu<B>
@@ -66,6 +93,7 @@ where
             selected_log_groups: vec![],
             loader: Loader::new(constant::LOADER.clone()),
             query: String::from(""),
+            loggroup_inst_tx: None,
             _phantom: PhantomData,
         }
     }
@@ -114,28 +142,27 @@ where
 
     async fn handle_event(&mut self, event: KeyEvent) -> bool {
         if self.is_selected {
-            let mut state = self.state.try_lock();
-            match event.code {
-                KeyCode::Char(c) => {
-                    if c != '?' {
-                        // ? is the key to toggle help dialog
-                        self.query.push(c);
-                    }
+            // the list is empty after the failed fetch, so the key is not typed into the query
+            if KEYMAP.is(Action::Retry, &event) && self.state.lock().unwrap().error.take().is_some()
+            {
+                if let Some(tx) = self.loggroup_inst_tx.as_mut() {
+                    let _ = tx.send(LogGroupEvent::FetchLogGroups).await;
                 }
-                KeyCode::Backspace => {
-                    self.query.pop();
-                }
-                KeyCode::Down => {
+                return false;
+            }
+            let mut state = self.state.try_lock();
+            match KEYMAP.action(Context::SideMenu, &event) {
+                Some(Action::NextLogGroup) => {
                     if let Ok(s) = state.as_mut() {
                         s.next();
                     }
                 }
-                KeyCode::Up => {
+                Some(Action::PrevLogGroup) => {
                     if let Ok(s) = state.as_mut() {
                         s.previous();
                     }
                 }
-                KeyCode::Enter => {
+                Some(Action::SelectLogGroup) => {
                     if let Ok(s) = state.as_mut() {
                         if let Some(idx) = s.get_current_idx() {
                             s.select(idx);
@@ -143,7 +170,21 @@ where
                         }
                     }
                 }
-                _ => {}
+                _ => match event.code {
+                    // left to the key bindings, e.g. Ctrl+T to open a tab
+                    KeyCode::Char(_)
+                        if event
+                            .modifiers
+                            .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) => {}
+                    // the help key toggles help dialog
+                    KeyCode::Char(c) if !KEYMAP.is(Action::ShowHelp, &event) => {
+                        self.query.push(c);
+                    }
+                    KeyCode::Backspace => {
+                        self.query.pop();
+                    }
+                    _ => {}
+                },
             }
         }
         false
@@ -162,18 +203,12 @@ where
                 KeyEventWrapper::new(KeyEvent::new(KeyCode::Backspace, KeyModifiers::NONE)),
                 "Incremental filtering (remove)".to_string(),
             );
-            maps.insert(
-                KeyEventWrapper::new(KeyEvent::new(KeyCode::Up, KeyModifiers::NONE)),
-                "Prev log group".to_string(),
-            );
-            maps.insert(
-                KeyEventWrapper::new(KeyEvent::new(KeyCode::Down, KeyModifiers::NONE)),
-                "Next log group".to_string(),
-            );
-            maps.insert(
-                KeyEventWrapper::new(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)),
-                "Select log group".to_string(),
-            );
+            KEYMAP.push_key_map(maps, Action::PrevLogGroup, "Prev log group");
+            KEYMAP.push_key_map(maps, Action::NextLogGroup, "Next log group");
+            KEYMAP.push_key_map(maps, Action::SelectLogGroup, "Select log group");
+            if self.error().is_some() {
+                KEYMAP.push_key_map(maps, Action::Retry, "Retry");
+            }
         }
         maps
     }
@@ -288,4 +323,28 @@ mod tests {
         );
         assert_eq!(String::from("a"), side_menu.query);
     }
+
+    #[tokio::test]
+    async fn test_handle_event_retry() {
+        let (tx, mut rx) = tokio::sync::mpsc::channel(1);
+        let mut side_menu: SideMenu<TestBackend> = SideMenu::default().with_loggroup_inst_tx(tx);
+        side_menu.set_select(true);
+        let retry = KeyEvent::new(KeyCode::Char('R'), KeyModifiers::NONE);
+        side_menu.state.lock().unwrap().error = Some(String::from("AccessDeniedException"));
+        assert_eq!(
+            Some(String::from("AccessDeniedException")),
+            side_menu.error()
+        );
+        let mut maps = BTreeMap::new();
+        side_menu.push_key_maps(&mut maps);
+        assert!(maps.values().any(|v| v == "Retry"));
+        assert!(!side_menu.handle_event(retry).await);
+        assert_eq!(None, side_menu.error());
+        assert!(side_menu.query.is_empty());
+        assert!(matches!(rx.try_recv(), Ok(LogGroupEvent::FetchLogGroups)));
+        // typed into the query without the failure
+        assert!(!side_menu.handle_event(retry).await);
+        assert_eq!(String::from("R"), side_menu.query);
+        assert!(rx.try_recv().is_err());
+    }
 }
@@ -8,11 +8,12 @@ use crossterm::event::KeyEvent;
 use tui::{
     backend::Backend,
     layout::{Alignment, Rect},
+    text::{Span, Spans},
     widgets::{Block, Borders, Paragraph, Wrap},
     Frame,
 };
 
-use crate::{state::status_bar_state::StatusBarState, ui::Drawable};
+use crate::{constant::ERROR_STYLE, state::status_bar_state::StatusBarState, ui::Drawable};
 
 pub struct StatusBar<B>
 where
@@ -36,6 +37,14 @@ where
     pub fn update_text(&mut self, new_text: &str) {
         self.state.lock().unwrap().message = new_text.to_string();
     }
+
+    pub fn update_progress(&mut self, progress: Option<String>) {
+        self.state.lock().unwrap().progress = progress;
+    }
+
+    pub fn update_error(&mut self, error: Option<String>) {
+        self.state.lock().unwrap().error = error;
+    }
 }
 
 impl<B> Default for StatusBar<B>
@@ -59,10 +68,17 @@ where
 {
     fn draw(&mut self, f: &mut Frame<'_, B>, area: Rect) {
         let state = self.state.try_lock();
-        let message = match state.as_ref() {
-            Ok(s) => s.message.as_str(),
-            Err(_) => "",
-        };
+        let mut spans = vec![];
+        if let Ok(s) = state.as_ref() {
+            if let Some(error) = &s.error {
+                spans.push(Span::styled(format!("{} ", error), *ERROR_STYLE));
+            }
+            if let Some(progress) = &s.progress {
+                spans.push(Span::raw(format!("{} ", progress)));
+            }
+            spans.push(Span::raw(s.message.clone()));
+        }
+        let message = Spans::from(spans);
         let block = Block::default().borders(Borders::NONE);
         let paragraph = Paragraph::new(message)
             .block(block)
@@ -117,6 +133,28 @@ mod tests {
             "                    ",
         ];
         test_case(&mut status_bar, lines);
+        // progress
+        status_bar.state.lock().unwrap().message = String::from("message");
+        status_bar.update_progress(Some(String::from("exporting")));
+        let lines = vec![
+            "   exporting message",
+            "                    ",
+            "                    ",
+            "                    ",
+        ];
+        test_case(&mut status_bar, lines);
+        // error
+        status_bar.update_progress(None);
+        status_bar.update_error(Some(String::from("[g] x")));
+        let mut terminal = get_test_terminal(20, 1);
+        terminal.draw(|f| status_bar.draw(f, f.size())).unwrap();
+        let buffer = terminal.backend().buffer();
+        let line = (0..20)
+            .map(|x| buffer.get(x, 0).symbol.clone())
+            .collect::<String>();
+        assert_eq!("       [g] x message", line);
+        assert_eq!(ERROR_STYLE.fg, Some(buffer.get(7, 0).fg));
+        assert_ne!(ERROR_STYLE.fg, Some(buffer.get(13, 0).fg));
     }
 
     #[tokio::test]
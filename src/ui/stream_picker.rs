@@ -0,0 +1,306 @@
+use std::{
+    collections::BTreeMap,
+    marker::PhantomData,
+    sync::{Arc, Mutex},
+};
+
+use async_trait::async_trait;
+use crossterm::event::KeyEvent;
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
+    Frame,
+};
+
+use crate::{
+    constant::{self, KEYMAP},
+    key_event_wrapper::KeyEventWrapper,
+    keymap::{Action, Context},
+    loader::Loader,
+    state::{logevents_state::LogEventsState, search_state::SearchState},
+    ui::{textbox::TextBox, Drawable},
+    utils::*,
+};
+
+/// Dialog to choose the log streams to search.
+/// Checked log streams take priority over the prefix.
+pub struct StreamPicker<B>
+where
+    B: Backend,
+{
+    state: Arc<Mutex<LogEventsState>>,
+    prefix_input: TextBox<B>,
+    list_state: ListState,
+    checked: Vec<String>,
+    loader: Loader,
+    _phantom: PhantomData<B>,
+}
+
+impl<B> StreamPicker<B>
+where
+    B: Backend,
+{
+    pub fn new(state: Arc<Mutex<LogEventsState>>) -> Self {
+        StreamPicker {
+            state,
+            prefix_input: TextBox::new(true),
+            list_state: ListState::default(),
+            checked: vec![],
+            loader: Loader::new(constant::LOADER.clone()),
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Restore the selection from the current search condition
+    pub fn reset(&mut self, condition: &SearchState) {
+        self.checked = condition.log_stream_names.clone();
+        self.prefix_input
+            .set_input(condition.log_stream_name_prefix.as_deref().unwrap_or(""));
+        self.list_state = ListState::default();
+    }
+
+    /// (log stream names, log stream name prefix)
+    pub fn get_selection(&self) -> (Vec<String>, Option<String>) {
+        let prefix = self.prefix_input.get_input();
+        (
+            self.checked.clone(),
+            if prefix.is_empty() {
+                None
+            } else {
+                Some(prefix)
+            },
+        )
+    }
+
+    /// log streams starting with the prefix
+    fn filtered_streams(&self) -> Vec<String> {
+        let prefix = self.prefix_input.get_input();
+        match self.state.try_lock() {
+            Ok(s) => s
+                .log_streams
+                .iter()
+                .filter(|name| name.starts_with(prefix.as_str()))
+                .cloned()
+                .collect(),
+            Err(_) => vec![],
+        }
+    }
+
+    fn next(&mut self) {
+        let len = self.filtered_streams().len();
+        if len == 0 {
+            self.list_state.select(None);
+            return;
+        }
+        let idx = match self.list_state.selected() {
+            Some(i) if i + 1 < len => i + 1,
+            Some(i) => i,
+            None => 0,
+        };
+        self.list_state.select(Some(idx));
+    }
+
+    fn previous(&mut self) {
+        if let Some(i) = self.list_state.selected() {
+            self.list_state.select(Some(i.saturating_sub(1)));
+        }
+    }
+
+    fn toggle(&mut self) {
+        let streams = self.filtered_streams();
+        if let Some(name) = self.list_state.selected().and_then(|i| streams.get(i)) {
+            if let Some(pos) = self.checked.iter().position(|c| c == name) {
+                self.checked.remove(pos);
+            } else {
+                self.checked.push(name.clone());
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl<B> Drawable<B> for StreamPicker<B>
+where
+    B: Backend + Send,
+{
+    fn draw(&mut self, f: &mut Frame<'_, B>, area: Rect) {
+        let outer_block = Block::default().borders(Borders::ALL).title("Log Streams");
+        let outer_area = get_inner_area(&area);
+        let inner_area = get_inner_area(&outer_area);
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(
+                [
+                    Constraint::Length(1),
+                    Constraint::Length(3),
+                    Constraint::Min(1),
+                ]
+                .as_ref(),
+            )
+            .split(inner_area);
+
+        let prefix_title = Paragraph::new("Prefix").block(Block::default());
+        let mut items = self
+            .filtered_streams()
+            .into_iter()
+            .map(|name| {
+                if self.checked.contains(&name) {
+                    ListItem::new(format!("[X]{}", name))
+                } else {
+                    ListItem::new(format!("[ ]{}", name))
+                }
+            })
+            .collect::<Vec<ListItem<'_>>>();
+        if let Ok(s) = self.state.try_lock() {
+            if s.is_fetching_log_streams {
+                items.push(ListItem::new(self.loader.get_char().to_string()));
+            }
+        }
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL))
+            .style(*constant::NORMAL_STYLE)
+            .highlight_style(Style::default().add_modifier(Modifier::BOLD))
+            .highlight_symbol(">> ");
+
+        f.render_widget(Clear, outer_area);
+        f.render_widget(outer_block, outer_area);
+        f.render_widget(prefix_title, chunks[0]);
+        self.prefix_input.draw(f, chunks[1]);
+        f.render_stateful_widget(list, chunks[2], &mut self.list_state);
+    }
+
+    async fn handle_event(&mut self, event: KeyEvent) -> bool {
+        match KEYMAP.action(Context::Dialog, &event) {
+            Some(Action::NextItem) => {
+                self.next();
+            }
+            Some(Action::PrevItem) => {
+                self.previous();
+            }
+            Some(Action::ToggleItem) => {
+                self.toggle();
+            }
+            // events Confirm and Cancel will be handled by the parent component
+            Some(Action::Confirm) | Some(Action::Cancel) => {
+                return false;
+            }
+            _ => {
+                if self.prefix_input.handle_event(event).await {
+                    // the list is filtered by the new prefix
+                    self.list_state.select(None);
+                }
+            }
+        }
+        true
+    }
+
+    fn push_key_maps<'a>(
+        &self,
+        maps: &'a mut BTreeMap<KeyEventWrapper, String>,
+    ) -> &'a mut BTreeMap<KeyEventWrapper, String> {
+        KEYMAP.push_key_map(maps, Action::NextItem, "Next log stream");
+        KEYMAP.push_key_map(maps, Action::PrevItem, "Prev log stream");
+        KEYMAP.push_key_map(maps, Action::ToggleItem, "Toggle log stream");
+        self.prefix_input.push_key_maps(maps);
+        maps
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::{KeyCode, KeyModifiers};
+    use tui::backend::TestBackend;
+
+    use super::*;
+    use crate::test_helper::key_maps_test_case;
+
+    fn get_picker() -> StreamPicker<TestBackend> {
+        let state = Arc::new(Mutex::new(LogEventsState::new()));
+        state.lock().unwrap().log_streams = vec![
+            String::from("app/web/1"),
+            String::from("app/web/2"),
+            String::from("app/worker/1"),
+        ];
+        StreamPicker::new(state)
+    }
+
+    #[tokio::test]
+    async fn test_handle_event() {
+        let mut picker = get_picker();
+        assert_eq!((vec![], None), picker.get_selection());
+        // check the second log stream
+        for code in [KeyCode::Down, KeyCode::Down, KeyCode::Tab].iter() {
+            assert!(
+                picker
+                    .handle_event(KeyEvent::new(*code, KeyModifiers::NONE))
+                    .await
+            );
+        }
+        assert_eq!(
+            (vec![String::from("app/web/2")], None),
+            picker.get_selection()
+        );
+        // uncheck
+        picker
+            .handle_event(KeyEvent::new(KeyCode::Tab, KeyModifiers::NONE))
+            .await;
+        assert!(picker.get_selection().0.is_empty());
+        // prefix filters the list
+        for c in "app/wo".chars() {
+            picker
+                .handle_event(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE))
+                .await;
+        }
+        assert_eq!(
+            vec![String::from("app/worker/1")],
+            picker.filtered_streams()
+        );
+        assert_eq!(
+            (vec![], Some(String::from("app/wo"))),
+            picker.get_selection()
+        );
+        // parent handles
+        assert!(
+            !picker
+                .handle_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE))
+                .await
+        );
+        assert!(
+            !picker
+                .handle_event(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE))
+                .await
+        );
+    }
+
+    #[test]
+    fn test_reset() {
+        let mut picker = get_picker();
+        let condition = SearchState {
+            log_stream_names: vec![String::from("app/web/1")],
+            log_stream_name_prefix: Some(String::from("app/")),
+            ..Default::default()
+        };
+        picker.reset(&condition);
+        assert_eq!(
+            (vec![String::from("app/web/1")], Some(String::from("app/"))),
+            picker.get_selection()
+        );
+    }
+
+    #[test]
+    fn test_push_key_maps() {
+        let picker = get_picker();
+        key_maps_test_case(
+            &picker,
+            KeyEvent::new(KeyCode::Tab, KeyModifiers::NONE),
+            "Toggle log stream",
+        );
+        key_maps_test_case(
+            &picker,
+            KeyEvent::new(KeyCode::Down, KeyModifiers::NONE),
+            "Next log stream",
+        );
+    }
+}
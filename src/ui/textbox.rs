@@ -38,6 +38,12 @@ where
         self.input.clone()
     }
 
+    /// Replace the input and move the cursor to the end
+    pub fn set_input(&mut self, input: &str) {
+        self.input = input.to_string();
+        self.cursor = self.input.len();
+    }
+
     fn get_text_to_show(&self) -> String {
         let mut input_cloned = self.input.clone();
         input_cloned.insert(self.cursor, '|');
@@ -103,6 +109,13 @@ where
                 KeyCode::Right => {
                     self.cursor_next();
                 }
+                // left to the key bindings, e.g. Ctrl+N of the emacs keymap
+                KeyCode::Char(_)
+                    if event.modifiers == KeyModifiers::CONTROL
+                        || event.modifiers == KeyModifiers::ALT =>
+                {
+                    return false;
+                }
                 KeyCode::Char(c) => {
                     self.input.insert(self.cursor, c);
                     self.cursor_next();
@@ -275,6 +288,14 @@ mod tests {
         assert_eq!(3, textbox.cursor);
     }
 
+    #[test]
+    fn test_set_input() {
+        let mut textbox: TextBox<TestBackend> = TextBox::new(true);
+        textbox.set_input("new input");
+        assert_eq!("new input".to_string(), textbox.get_input());
+        assert_eq!(9, textbox.cursor);
+    }
+
     #[test]
     fn test_push_key_maps() {
         // selected
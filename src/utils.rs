@@ -1,21 +1,24 @@
 use std::{collections::BTreeMap, str::FromStr};
 
 use anyhow::Result;
-use rusoto_core::{request::HttpClient, Region};
+use chrono::{Local, TimeZone};
+use clipboard::{ClipboardContext, ClipboardProvider};
+use rusoto_core::{request::HttpClient, Client, Region};
 use rusoto_credential::ProfileProvider;
 use rusoto_iam::{GetRoleRequest, Iam, IamClient};
 use rusoto_logs::CloudWatchLogsClient;
 use rusoto_sts::{StsAssumeRoleSessionCredentialsProvider, StsClient};
 use tui::layout::Rect;
 
-use crate::key_event_wrapper::KeyEventWrapper;
+use crate::{client::cloudwatch::CloudWatchLogSource, key_event_wrapper::KeyEventWrapper};
 
-pub async fn get_aws_client(
+/// CloudWatch Logs with the live tail, signed by the credentials of the profile or the role
+pub async fn get_aws_source(
     profile: Option<&str>,
     region: Option<&str>,
     role_name: Option<&str>,
     role_arn: Option<&str>,
-) -> Result<CloudWatchLogsClient> {
+) -> Result<CloudWatchLogSource> {
     let region = if let Some(r) = region {
         Region::from_str(r)?
     } else {
@@ -70,19 +73,19 @@ pub async fn get_aws_client(
             None,
         ));
     }
-    if let Some(ap) = assumed_provider {
-        Ok(CloudWatchLogsClient::new_with(
-            HttpClient::new()?,
-            ap,
-            region,
-        ))
+    // shared with the live tail, which is not in CloudWatchLogsClient
+    let client = if let Some(ap) = assumed_provider {
+        Client::new_with(ap, HttpClient::new()?)
     } else {
-        Ok(CloudWatchLogsClient::new_with(
-            HttpClient::new()?,
-            get_aws_provider(profile)?,
-            region,
+        Client::new_with(get_aws_provider(profile)?, HttpClient::new()?)
+    };
+    Ok(
+        CloudWatchLogSource::new(CloudWatchLogsClient::new_with_client(
+            client.clone(),
+            region.clone(),
         ))
-    }
+        .with_live_tail(client, region),
+    )
 }
 
 fn get_aws_provider(profile: Option<&str>) -> Result<ProfileProvider> {
@@ -102,6 +105,36 @@ pub fn get_inner_area(area: &Rect) -> Rect {
     area_cloned
 }
 
+pub fn copy_to_clipboard(text: &str) {
+    let context: Result<ClipboardContext, Box<dyn std::error::Error>> = ClipboardProvider::new();
+    match context {
+        Ok(mut ctx) => {
+            ctx.set_contents(text.to_string()).unwrap_or_else(|e| {
+                log::warn!(
+                    "Failed to write log event message to ClipboardContext: {}",
+                    e
+                )
+            });
+            log::info!(
+                "Log event message has been written to Clipboard. \nlog event: \n{}",
+                text
+            );
+        }
+        Err(e) => {
+            log::warn!("Failed to get ClipboardContext: {}", e);
+        }
+    }
+}
+
+/// The local time of the epoch milliseconds in the format, empty if it is out of range
+pub fn format_local_time(millis: i64, format: &str) -> String {
+    Local
+        .timestamp_millis_opt(millis)
+        .single()
+        .map(|dt| dt.format(format).to_string())
+        .unwrap_or_default()
+}
+
 pub fn key_maps_stringify(maps: &BTreeMap<KeyEventWrapper, String>) -> String {
     let mut datas = vec![];
     for (k, v) in maps.iter() {
@@ -161,4 +194,10 @@ mod tests {
         );
         assert_eq!(expected, result);
     }
+
+    #[test]
+    fn test_format_local_time() {
+        assert_eq!(".123", format_local_time(1609426800123, "%.3f"));
+        assert_eq!("", format_local_time(i64::MAX, "%Y"));
+    }
 }
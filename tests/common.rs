@@ -1,7 +1,30 @@
-use rusoto_logs::CloudWatchLogsClient;
+// each test crate uses some of the helpers
+#![allow(dead_code)]
+
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use rusoto_logs::{CloudWatchLogsClient, FilteredLogEvent};
 use rusoto_mock::{
     MockCredentialsProvider, MockRequestDispatcher, MockResponseReader, ReadMockResponse,
 };
+use tokio::{
+    sync::mpsc::{self, Receiver, Sender},
+    task::JoinHandle,
+    time::delay_for,
+};
+
+use megane::{
+    client::LogClient,
+    event::{LogEventEvent, TailLogEventEvent},
+    handler::{
+        logevent_event_handler::LogEventEventHandler,
+        tail_logevent_event_handler::TailLogEventEventHandler, EventHandler,
+    },
+    state::{logevents_state::LogEventsState, search_state::SearchState},
+};
 
 pub fn get_mock_client(filename: &str) -> CloudWatchLogsClient {
     CloudWatchLogsClient::new_with(
@@ -11,3 +34,81 @@ pub fn get_mock_client(filename: &str) -> CloudWatchLogsClient {
         Default::default(),
     )
 }
+
+pub fn event(id: &str, timestamp: i64) -> FilteredLogEvent {
+    FilteredLogEvent {
+        event_id: Some(id.to_string()),
+        message: Some(format!("log_event_{}", id)),
+        timestamp: Some(timestamp),
+        ..Default::default()
+    }
+}
+
+/// Events from 1 to `len`, a second apart
+pub fn events(len: i64) -> Vec<FilteredLogEvent> {
+    (1..=len).map(|i| event(&i.to_string(), i * 1000)).collect()
+}
+
+/// Run the log event handler in the background.
+/// The instructions to the tail handler are left in the receiver.
+pub fn spawn_logevent_handler(
+    client: LogClient,
+) -> (
+    Sender<LogEventEvent>,
+    Receiver<TailLogEventEvent>,
+    JoinHandle<()>,
+    Arc<Mutex<LogEventsState>>,
+) {
+    let state = Arc::new(Mutex::new(LogEventsState::new()));
+    let (inst_tx, inst_rx) = mpsc::channel::<LogEventEvent>(1);
+    let (tail_inst_tx, tail_inst_rx) = mpsc::channel::<TailLogEventEvent>(1);
+    let mut handler = LogEventEventHandler::new(client, Arc::clone(&state), inst_rx, tail_inst_tx);
+    let handle = tokio::spawn(async move {
+        // the handler is not stopped by the failures
+        handler.run().await.unwrap();
+    });
+    (inst_tx, tail_inst_rx, handle, state)
+}
+
+/// The fetch of the log group from the start
+pub fn fetch_log_events(log_group_name: &str, search_state: SearchState) -> LogEventEvent {
+    LogEventEvent::FetchLogEvents(log_group_name.to_string(), None, Some(search_state), true)
+}
+
+/// Run the tail handler in the background, the live tail sessions are replaced after the limit
+pub fn spawn_tail_handler(
+    client: LogClient,
+    session_limit: Duration,
+) -> (
+    Sender<TailLogEventEvent>,
+    JoinHandle<()>,
+    Arc<Mutex<LogEventsState>>,
+) {
+    let state = Arc::new(Mutex::new(LogEventsState::new()));
+    let (tail_inst_tx, tail_inst_rx) = mpsc::channel::<TailLogEventEvent>(1);
+    let mut handler = TailLogEventEventHandler::new(client, Arc::clone(&state), tail_inst_rx)
+        .with_session_limit(session_limit);
+    let handle = tokio::spawn(async move {
+        handler.run().await.unwrap();
+    });
+    (tail_inst_tx, handle, state)
+}
+
+/// The start of the tail mode of the log group
+pub fn start_tail(log_group_name: &str, search_state: SearchState) -> TailLogEventEvent {
+    TailLogEventEvent::Start(log_group_name.to_string(), None, Some(search_state), true)
+}
+
+/// Wait for the background task of the handler
+pub async fn wait_until<F>(f: F)
+where
+    F: Fn() -> bool,
+{
+    for _ in 0..300 {
+        if f() {
+            return;
+        }
+        delay_for(Duration::from_millis(10)).await;
+    }
+    panic!("timed out");
+}
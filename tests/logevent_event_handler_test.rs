@@ -1,45 +1,29 @@
-use std::sync::{Arc, Mutex};
+use rusoto_logs::FilteredLogEvent;
 
 use megane::{
-    client::LogClient,
+    client::{memory::MemoryLogSource, scripted::ScriptedLogSource, LogClient},
     event::{LogEventEvent, TailLogEventEvent},
-    handler::{logevent_event_handler::LogEventEventHandler, EventHandler},
-    state::{
-        logevents_state::LogEventsState,
-        search_state::{SearchMode, SearchState},
-    },
+    export::ExportProgress,
+    state::search_state::{QueryMode, SearchMode, SearchState},
 };
 
 mod common;
 
+use common::{event, events, fetch_log_events, spawn_logevent_handler, wait_until};
+
 #[tokio::test]
 async fn test_run_basis() {
-    let state = Arc::new(Mutex::new(LogEventsState::new()));
-    let (mut inst_tx, inst_rx) = tokio::sync::mpsc::channel::<LogEventEvent>(1);
-    let (tail_inst_tx, _tail_inst_rx) = tokio::sync::mpsc::channel::<TailLogEventEvent>(1);
     let mock_client = common::get_mock_client("logevents_01.json");
-    let mut handler = LogEventEventHandler::new(
-        LogClient::new(mock_client),
-        Arc::clone(&state),
-        inst_rx,
-        tail_inst_tx,
-    );
-    let handle = tokio::spawn(async move {
-        handler.run().await.unwrap();
-    });
-    let search_state = Some(SearchState::new(String::default(), SearchMode::TwelveHours));
+    let (mut inst_tx, _tail_inst_rx, handle, state) =
+        spawn_logevent_handler(LogClient::new(mock_client));
+    let search_state = SearchState::new(String::default(), SearchMode::TwelveHours);
     assert!(inst_tx
-        .send(LogEventEvent::FetchLogEvents(
-            "log group name".to_string(),
-            None,
-            search_state,
-            true
-        ))
+        .send(fetch_log_events("log group name", search_state))
         .await
         .is_ok());
     assert!(inst_tx.send(LogEventEvent::Abort).await.is_ok());
 
-    let _ = handle.await.unwrap();
+    handle.await.unwrap();
 
     for i in 0..=4 {
         assert_eq!(
@@ -60,23 +44,206 @@ async fn test_run_basis() {
     }
 }
 
+#[tokio::test]
+async fn test_run_with_memory_source() {
+    let source = MemoryLogSource::new().with_events("log group name", events(3));
+    let (mut inst_tx, _tail_inst_rx, handle, state) =
+        spawn_logevent_handler(LogClient::from_source(source));
+    let search_state = SearchState::new(String::default(), SearchMode::FromTo(None, None));
+    assert!(inst_tx
+        .send(fetch_log_events("log group name", search_state))
+        .await
+        .is_ok());
+    assert!(inst_tx.send(LogEventEvent::Abort).await.is_ok());
+
+    handle.await.unwrap();
+
+    assert_eq!(3, state.lock().unwrap().events.items().len());
+    assert_eq!(
+        Some(String::from("log_event_3")),
+        state.lock().unwrap().events.items()[2].message
+    );
+}
+
+#[tokio::test]
+async fn test_run_insights_query() {
+    let source = MemoryLogSource::new().with_events("log group name", events(2));
+    let (mut inst_tx, _tail_inst_rx, handle, state) =
+        spawn_logevent_handler(LogClient::from_source(source));
+    let mut search_state = SearchState::new(
+        String::from("fields @timestamp, @message"),
+        SearchMode::OneHour,
+    );
+    search_state.query_mode = QueryMode::Insights;
+    assert!(inst_tx
+        .send(fetch_log_events("log group name", search_state))
+        .await
+        .is_ok());
+    // the query runs in the background
+    wait_until(|| {
+        let state = state.lock().unwrap();
+        state.insights.is_some() && !state.is_fetching
+    })
+    .await;
+    assert!(inst_tx.send(LogEventEvent::Abort).await.is_ok());
+
+    handle.await.unwrap();
+
+    let state = state.lock().unwrap();
+    assert!(!state.is_fetching);
+    let insights = state.insights.as_ref().unwrap();
+    assert_eq!(&vec!["@timestamp", "@message"], insights.columns());
+    assert_eq!(
+        Some(String::from("2000\tlog_event_2")),
+        state.get_message(1)
+    );
+}
+
+#[tokio::test]
+async fn test_run_insights_query_cancel() {
+    // the running query does not block the pane, and is stopped by the next fetch
+    let source = ScriptedLogSource::new().then_events(events(1), None);
+    let (mut inst_tx, _tail_inst_rx, handle, state) =
+        spawn_logevent_handler(LogClient::from_source(source.clone()));
+    let mut search_state = SearchState::new(String::from("fields @message"), SearchMode::OneHour);
+    search_state.query_mode = QueryMode::Insights;
+    assert!(inst_tx
+        .send(fetch_log_events("log group name", search_state))
+        .await
+        .is_ok());
+    wait_until(|| state.lock().unwrap().insights.is_some()).await;
+    assert!(inst_tx
+        .send(fetch_log_events(
+            "log group name",
+            SearchState::new(String::default(), SearchMode::FromTo(None, None))
+        ))
+        .await
+        .is_ok());
+    wait_until(|| source.stopped_queries() == vec![String::from("log group name")]).await;
+    assert!(inst_tx.send(LogEventEvent::Abort).await.is_ok());
+    handle.await.unwrap();
+
+    let state = state.lock().unwrap();
+    assert!(state.insights.is_none());
+    assert!(!state.is_fetching);
+    assert_eq!(1, state.events.items().len());
+}
+
+#[tokio::test]
+async fn test_run_fetch_log_streams() {
+    let events = vec!["stream-1", "stream-2", "stream-1"]
+        .into_iter()
+        .map(|name| FilteredLogEvent {
+            log_stream_name: Some(name.to_string()),
+            ..Default::default()
+        })
+        .collect();
+    let source = MemoryLogSource::new().with_events("log group name", events);
+    let (mut inst_tx, _tail_inst_rx, handle, state) =
+        spawn_logevent_handler(LogClient::from_source(source));
+    assert!(inst_tx
+        .send(LogEventEvent::FetchLogStreams("log group name".to_string()))
+        .await
+        .is_ok());
+    assert!(inst_tx.send(LogEventEvent::Abort).await.is_ok());
+
+    handle.await.unwrap();
+
+    let state = state.lock().unwrap();
+    assert!(!state.is_fetching_log_streams);
+    assert_eq!(
+        vec![String::from("stream-1"), String::from("stream-2")],
+        state.log_streams
+    );
+}
+
+#[tokio::test]
+async fn test_run_fetch_context() {
+    let events = (1..=6)
+        .map(|i| FilteredLogEvent {
+            log_stream_name: Some(format!("stream-{}", i % 2)),
+            ..event(&i.to_string(), i * 1000)
+        })
+        .collect::<Vec<FilteredLogEvent>>();
+    let hit = events[3].clone();
+    let source = MemoryLogSource::new().with_events("log group name", events);
+    let (mut inst_tx, _tail_inst_rx, handle, state) =
+        spawn_logevent_handler(LogClient::from_source(source));
+    assert!(inst_tx
+        .send(LogEventEvent::FetchContext(
+            "log group name".to_string(),
+            hit
+        ))
+        .await
+        .is_ok());
+    assert!(inst_tx.send(LogEventEvent::Abort).await.is_ok());
+
+    handle.await.unwrap();
+
+    // the events of stream-0 around log_event_4
+    let state = state.lock().unwrap();
+    let context = state.context.as_ref().unwrap();
+    assert!(!context.is_fetching);
+    assert_eq!(
+        vec!["log_event_2", "log_event_4", "log_event_6"],
+        context
+            .events
+            .iter()
+            .map(|e| e.message.clone().unwrap())
+            .collect::<Vec<String>>()
+    );
+    assert_eq!(Some(1), context.hit_index());
+}
+
+#[tokio::test]
+async fn test_run_export() {
+    let source = MemoryLogSource::new().with_events("log group name", events(3));
+    let (mut inst_tx, _tail_inst_rx, handle, state) =
+        spawn_logevent_handler(LogClient::from_source(source));
+    let search_state = SearchState::new(String::default(), SearchMode::FromTo(None, None));
+    let path = std::env::temp_dir().join("megane_test_run_export.log");
+    assert!(inst_tx
+        .send(fetch_log_events("log group name", search_state.clone()))
+        .await
+        .is_ok());
+    assert!(inst_tx
+        .send(LogEventEvent::Export(
+            "log group name".to_string(),
+            Some(search_state),
+            path.clone(),
+            true
+        ))
+        .await
+        .is_ok());
+    // the export runs in the background
+    wait_until(|| {
+        matches!(
+            state.lock().unwrap().export_progress,
+            Some(ExportProgress::Done(_, _))
+        )
+    })
+    .await;
+    assert!(inst_tx.send(LogEventEvent::Abort).await.is_ok());
+
+    handle.await.unwrap();
+
+    assert_eq!(
+        Some(ExportProgress::Done(path.clone(), 3)),
+        state.lock().unwrap().export_progress
+    );
+    let exported = std::fs::read_to_string(&path).unwrap();
+    assert_eq!(3, exported.lines().count());
+    assert!(exported.ends_with("log_event_3\n"));
+    std::fs::remove_file(&path).unwrap();
+}
+
 #[tokio::test]
 async fn test_run_send_tail() {
-    let state = Arc::new(Mutex::new(LogEventsState::new()));
-    let (mut inst_tx, inst_rx) = tokio::sync::mpsc::channel::<LogEventEvent>(1);
-    let (tail_inst_tx, mut tail_inst_rx) = tokio::sync::mpsc::channel::<TailLogEventEvent>(1);
     let mock_client = common::get_mock_client("logevents_01.json");
-    let mut handler = LogEventEventHandler::new(
-        LogClient::new(mock_client),
-        Arc::clone(&state),
-        inst_rx,
-        tail_inst_tx,
-    );
-    let handle = tokio::spawn(async move {
-        handler.run().await.unwrap();
-    });
-    let search_state = Some(SearchState::new(String::default(), SearchMode::Tail));
-    let search_state_clone = search_state.clone();
+    let (mut inst_tx, mut tail_inst_rx, handle, _state) =
+        spawn_logevent_handler(LogClient::new(mock_client));
+    let search_state = SearchState::new(String::default(), SearchMode::Tail);
+    let search_state_clone = Some(search_state.clone());
 
     let assert_handle = tokio::spawn(async move {
         let event = tail_inst_rx.recv().await.unwrap();
@@ -87,16 +254,44 @@ async fn test_run_send_tail() {
     });
 
     assert!(inst_tx
-        .send(LogEventEvent::FetchLogEvents(
-            "log group name".to_string(),
-            None,
-            search_state,
-            true
-        ))
+        .send(fetch_log_events("log group name", search_state))
+        .await
+        .is_ok());
+    assert!(inst_tx.send(LogEventEvent::Abort).await.is_ok());
+
+    handle.await.unwrap();
+    assert_handle.await.unwrap();
+}
+
+#[tokio::test]
+async fn test_run_error() {
+    let source = MemoryLogSource::new().with_events("log group name", events(3));
+    let (mut inst_tx, _tail_inst_rx, handle, state) =
+        spawn_logevent_handler(LogClient::from_source(source));
+    let condition = SearchState::new(String::default(), SearchMode::FromTo(None, None));
+    // the failure is recorded in the state
+    assert!(inst_tx
+        .send(fetch_log_events("missing", condition.clone()))
+        .await
+        .is_ok());
+    wait_until(|| state.lock().unwrap().error.is_some()).await;
+    {
+        let state = state.lock().unwrap();
+        assert_eq!(
+            Some(String::from("log group missing does not exist")),
+            state.error
+        );
+        assert!(!state.is_fetching);
+    }
+    // retry
+    assert!(inst_tx
+        .send(fetch_log_events("log group name", condition))
         .await
         .is_ok());
     assert!(inst_tx.send(LogEventEvent::Abort).await.is_ok());
+    handle.await.unwrap();
 
-    let _ = handle.await.unwrap();
-    let _ = assert_handle.await.unwrap();
+    let state = state.lock().unwrap();
+    assert_eq!(None, state.error);
+    assert_eq!(3, state.events.items().len());
 }
@@ -1,7 +1,7 @@
 use std::sync::{Arc, Mutex};
 
 use megane::{
-    client::LogClient,
+    client::{memory::MemoryLogSource, LogClient},
     event::LogGroupEvent,
     handler::{loggroup_event_handler::LogGroupEventHandler, EventHandler},
     state::loggroups_state::LogGroupsState,
@@ -41,3 +41,45 @@ async fn test_run() {
         );
     }
 }
+
+/// Fetch the log groups once by a new handler
+async fn fetch_log_groups(client: &LogClient, state: &Arc<Mutex<LogGroupsState>>) {
+    let (mut inst_tx, inst_rx) = tokio::sync::mpsc::channel::<LogGroupEvent>(1);
+    let mut handler = LogGroupEventHandler::new(client.clone(), Arc::clone(state), inst_rx);
+    let handle = tokio::spawn(async move {
+        handler.run().await.unwrap();
+    });
+    assert!(inst_tx.send(LogGroupEvent::FetchLogGroups).await.is_ok());
+    assert!(inst_tx.send(LogGroupEvent::Abort).await.is_ok());
+    handle.await.unwrap();
+}
+
+#[tokio::test]
+async fn test_run_error() {
+    let state = Arc::new(Mutex::new(LogGroupsState::new()));
+    let client = LogClient::from_source(
+        MemoryLogSource::new()
+            .with_events("log_group_1", vec![])
+            .with_failures("AccessDeniedException", 1),
+    );
+    fetch_log_groups(&client, &state).await;
+    assert_eq!(
+        Some(String::from("AccessDeniedException")),
+        state.lock().unwrap().error
+    );
+    assert!(!state.lock().unwrap().log_groups.has_items());
+    assert!(!state.lock().unwrap().is_fetching);
+    // fetched again by the retry
+    fetch_log_groups(&client, &state).await;
+    assert_eq!(None, state.lock().unwrap().error);
+    assert_eq!(
+        Some(String::from("log_group_1")),
+        state
+            .lock()
+            .unwrap()
+            .log_groups
+            .get_item(0)
+            .unwrap()
+            .log_group_name
+    );
+}
@@ -1,9 +1,17 @@
-use std::sync::{Arc, Mutex};
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use anyhow::anyhow;
+use chrono::Utc;
+use rusoto_logs::FilteredLogEvent;
+use tokio::{sync::mpsc::Sender, task::JoinHandle, time::delay_for};
 
 use megane::{
-    client::LogClient,
+    client::{live_tail, memory::MemoryLogSource, scripted::ScriptedLogSource, LogClient},
+    constant::{LIVE_TAIL_SESSION_LIMIT, RETRY_BASE_DELAY, TAIL_OVERLAP, TAIL_RATE},
     event::TailLogEventEvent,
-    handler::{tail_logevent_event_handler::TailLogEventEventHandler, EventHandler},
     state::{
         logevents_state::LogEventsState,
         search_state::{SearchMode, SearchState},
@@ -12,34 +20,23 @@ use megane::{
 
 mod common;
 
+use common::{event, spawn_tail_handler, start_tail, wait_until};
+
 #[tokio::test]
 async fn test_run_basis() {
     // start tail mode and fetch some logs
-    let state = Arc::new(Mutex::new(LogEventsState::new()));
-    let (mut tail_inst_tx, tail_inst_rx) = tokio::sync::mpsc::channel::<TailLogEventEvent>(1);
     let mock_client = common::get_mock_client("logevents_01.json");
-    let mut handler = TailLogEventEventHandler::new(
-        LogClient::new(mock_client),
-        Arc::clone(&state),
-        tail_inst_rx,
-    );
-    let handle = tokio::spawn(async move {
-        handler.run().await.unwrap();
-    });
-    let search_state = Some(SearchState::new(String::default(), SearchMode::TwelveHours));
+    let (mut tail_inst_tx, handle, state) =
+        spawn_tail_handler(LogClient::new(mock_client), *LIVE_TAIL_SESSION_LIMIT);
+    let search_state = SearchState::new(String::default(), SearchMode::TwelveHours);
     assert!(tail_inst_tx
-        .send(TailLogEventEvent::Start(
-            "log group name".to_string(),
-            None,
-            search_state,
-            true
-        ))
+        .send(start_tail("log group name", search_state))
         .await
         .is_ok());
     assert!(tail_inst_tx.send(TailLogEventEvent::Tick).await.is_ok());
     assert!(tail_inst_tx.send(TailLogEventEvent::Abort).await.is_ok());
 
-    let _ = handle.await.unwrap();
+    handle.await.unwrap();
 
     for i in 0..=4 {
         assert_eq!(
@@ -63,32 +60,295 @@ async fn test_run_basis() {
 #[tokio::test]
 async fn test_run_stop() {
     // fetch some logs but stop tail mode and delete all fetched logs
-    let state = Arc::new(Mutex::new(LogEventsState::new()));
-    let (mut tail_inst_tx, tail_inst_rx) = tokio::sync::mpsc::channel::<TailLogEventEvent>(1);
     let mock_client = common::get_mock_client("logevents_01.json");
-    let mut handler = TailLogEventEventHandler::new(
-        LogClient::new(mock_client),
-        Arc::clone(&state),
-        tail_inst_rx,
-    );
-    let handle = tokio::spawn(async move {
-        handler.run().await.unwrap();
-    });
-    let search_state = Some(SearchState::new(String::default(), SearchMode::TwelveHours));
+    let (mut tail_inst_tx, handle, state) =
+        spawn_tail_handler(LogClient::new(mock_client), *LIVE_TAIL_SESSION_LIMIT);
+    let search_state = SearchState::new(String::default(), SearchMode::TwelveHours);
     assert!(tail_inst_tx
-        .send(TailLogEventEvent::Start(
-            "log group name".to_string(),
-            None,
-            search_state,
-            true
-        ))
+        .send(start_tail("log group name", search_state))
         .await
         .is_ok());
     assert!(tail_inst_tx.send(TailLogEventEvent::Tick).await.is_ok());
     assert!(tail_inst_tx.send(TailLogEventEvent::Stop).await.is_ok());
     assert!(tail_inst_tx.send(TailLogEventEvent::Abort).await.is_ok());
 
-    let _ = handle.await.unwrap();
+    handle.await.unwrap();
 
     assert!(state.lock().unwrap().events.items().is_empty());
 }
+
+#[tokio::test]
+async fn test_run_error() {
+    // the failed tick is recorded and the next tick is still handled
+    let (mut tail_inst_tx, handle, state) = spawn_tail_handler(
+        LogClient::from_source(MemoryLogSource::new()),
+        *LIVE_TAIL_SESSION_LIMIT,
+    );
+    let search_state = SearchState::new(String::default(), SearchMode::Tail);
+    assert!(tail_inst_tx
+        .send(start_tail("missing", search_state))
+        .await
+        .is_ok());
+    assert!(tail_inst_tx.send(TailLogEventEvent::Tick).await.is_ok());
+    assert!(tail_inst_tx.send(TailLogEventEvent::Tick).await.is_ok());
+    assert!(tail_inst_tx.send(TailLogEventEvent::Abort).await.is_ok());
+
+    handle.await.unwrap();
+
+    let state = state.lock().unwrap();
+    assert_eq!(
+        Some(String::from("log group missing does not exist")),
+        state.error
+    );
+    assert!(!state.is_fetching);
+}
+
+#[tokio::test]
+async fn test_run_throttled() {
+    // the polling slows down while throttled and speeds up after the success
+    let events = (1..=3)
+        .map(|i| event(&i.to_string(), Utc::now().timestamp_millis()))
+        .collect::<Vec<FilteredLogEvent>>();
+    let source = MemoryLogSource::new()
+        .with_events("log group name", events)
+        .with_failures("ThrottlingException: Rate exceeded", 2);
+    let (mut tail_inst_tx, handle, state) =
+        spawn_tail_handler(LogClient::from_source(source), *LIVE_TAIL_SESSION_LIMIT);
+    let search_state = SearchState::new(String::default(), SearchMode::Tail);
+    assert!(tail_inst_tx
+        .send(start_tail("log group name", search_state))
+        .await
+        .is_ok());
+    // fetch (throttled, every 2 ticks), skip, fetch (throttled, every 4 ticks), skip x3,
+    // fetch (every 2 ticks), skip
+    for _ in 0..8 {
+        assert!(tail_inst_tx.send(TailLogEventEvent::Tick).await.is_ok());
+    }
+    assert!(tail_inst_tx.send(TailLogEventEvent::Abort).await.is_ok());
+
+    handle.await.unwrap();
+
+    let state = state.lock().unwrap();
+    assert_eq!(None, state.error);
+    assert_eq!(3, state.events.items().len());
+    assert_eq!(Some(*TAIL_RATE * 2), state.tail_interval);
+}
+
+const START: i64 = 1609426800000;
+
+/// Start tail mode of the handler from START
+async fn start_handler(
+    source: ScriptedLogSource,
+    session_limit: Duration,
+) -> (
+    Sender<TailLogEventEvent>,
+    JoinHandle<()>,
+    Arc<Mutex<LogEventsState>>,
+) {
+    let (mut tail_inst_tx, handle, state) =
+        spawn_tail_handler(LogClient::from_source(source), session_limit);
+    let search_state = SearchState::new(String::default(), SearchMode::FromTo(Some(START), None));
+    assert!(tail_inst_tx
+        .send(start_tail("log group name", search_state))
+        .await
+        .is_ok());
+    (tail_inst_tx, handle, state)
+}
+
+async fn run_ticks(source: ScriptedLogSource, ticks: usize) -> Arc<Mutex<LogEventsState>> {
+    let (mut tail_inst_tx, handle, state) = start_handler(source, Duration::from_secs(60)).await;
+    for _ in 0..ticks {
+        assert!(tail_inst_tx.send(TailLogEventEvent::Tick).await.is_ok());
+    }
+    assert!(tail_inst_tx.send(TailLogEventEvent::Abort).await.is_ok());
+    handle.await.unwrap();
+    state
+}
+
+fn event_ids(state: &LogEventsState) -> Vec<String> {
+    state
+        .events
+        .items()
+        .iter()
+        .map(|e| e.event_id.clone().unwrap_or_default())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_run_overlap() {
+    // the pages of the query, then the query from the high-water mark fetching the overlap again
+    let source = ScriptedLogSource::new()
+        .then_events(
+            vec![event("1", START + 1000), event("2", START + 2000)],
+            Some("page-2"),
+        )
+        .then_events(
+            vec![event("2", START + 2000), event("3", START + 3000)],
+            None,
+        )
+        .then_events(
+            vec![
+                event("3", START + 3000),
+                event("late", START + 2500),
+                event("4", START + 4000),
+            ],
+            None,
+        );
+    let state = run_ticks(source.clone(), 3).await;
+
+    let state = state.lock().unwrap();
+    assert_eq!(vec!["1", "2", "3", "late", "4"], event_ids(&state));
+    assert_eq!(None, state.error);
+    let overlap = TAIL_OVERLAP.as_millis() as i64;
+    assert_eq!(
+        vec![
+            (None, (Some(START), None)),
+            (Some(String::from("page-2")), (Some(START), None)),
+            (None, (Some(START + 3000 - overlap), None)),
+        ],
+        source.calls()
+    );
+    // the live tail is not supported, not started again
+    assert_eq!(1, source.live_tail_starts());
+}
+
+#[tokio::test]
+async fn test_run_no_new_events() {
+    // the high-water mark stays while no events come, nothing is pushed twice
+    let source = ScriptedLogSource::new()
+        .then_events(vec![event("1", START + 1000)], None)
+        .then_events(vec![], None)
+        .then_events(vec![event("1", START + 1000)], None);
+    let state = run_ticks(source.clone(), 4).await;
+
+    let state = state.lock().unwrap();
+    assert_eq!(vec!["1"], event_ids(&state));
+    let overlap = TAIL_OVERLAP.as_millis() as i64;
+    assert!(source.calls()[1..]
+        .iter()
+        .all(|call| call == &(None, (Some(START + 1000 - overlap), None))));
+}
+
+#[tokio::test]
+async fn test_run_resume_after_error() {
+    // the failed page is fetched again by the next tick, no events are dropped
+    let source = ScriptedLogSource::new()
+        .then_events(vec![event("1", START + 1000)], Some("page-2"))
+        .then_error("connection reset")
+        .then_events(vec![event("2", START + 2000)], None);
+    let state = run_ticks(source.clone(), 3).await;
+
+    let state = state.lock().unwrap();
+    assert_eq!(vec!["1", "2"], event_ids(&state));
+    assert_eq!(None, state.error);
+    assert_eq!(
+        vec![
+            None,
+            Some(String::from("page-2")),
+            Some(String::from("page-2"))
+        ],
+        source
+            .calls()
+            .into_iter()
+            .map(|(token, _)| token)
+            .collect::<Vec<_>>()
+    );
+}
+
+#[tokio::test]
+async fn test_run_live_tail() {
+    // the events before the session are polled once, then streamed without polling
+    let (mut live_tx, session) = live_tail::channel(1);
+    let source = ScriptedLogSource::new()
+        .then_live_tail(session)
+        .then_events(vec![event("1", START + 1000)], None);
+    let (mut tail_inst_tx, handle, state) =
+        start_handler(source.clone(), Duration::from_secs(60)).await;
+    assert!(tail_inst_tx.send(TailLogEventEvent::Tick).await.is_ok());
+    wait_until(|| state.lock().unwrap().events.items().len() == 1).await;
+    assert!(state.lock().unwrap().live_tail);
+    assert!(live_tx
+        .send(Ok(vec![event("1", START + 1000), event("2", START + 2000)]))
+        .await
+        .is_ok());
+    wait_until(|| state.lock().unwrap().events.items().len() == 2).await;
+    assert!(tail_inst_tx.send(TailLogEventEvent::Tick).await.is_ok());
+    assert_eq!(1, source.calls().len());
+    // closed by the server, no more sessions and polled again
+    drop(live_tx);
+    wait_until(|| !state.lock().unwrap().live_tail).await;
+    assert!(tail_inst_tx.send(TailLogEventEvent::Tick).await.is_ok());
+    wait_until(|| source.calls().len() == 2).await;
+    assert!(tail_inst_tx.send(TailLogEventEvent::Abort).await.is_ok());
+    handle.await.unwrap();
+
+    assert_eq!(vec!["1", "2"], event_ids(&state.lock().unwrap()));
+    assert_eq!(2, source.live_tail_starts());
+    assert_eq!(
+        (
+            None,
+            (Some(START + 2000 - TAIL_OVERLAP.as_millis() as i64), None)
+        ),
+        source.calls()[1]
+    );
+}
+
+#[tokio::test]
+async fn test_run_live_tail_reconnect() {
+    // polls while the session fails, and starts it again after the backoff
+    let (mut live_tx, session) = live_tail::channel(1);
+    let source = ScriptedLogSource::new()
+        .then_live_tail_error("connection refused")
+        .then_live_tail(session);
+    let (mut tail_inst_tx, handle, state) =
+        start_handler(source.clone(), Duration::from_secs(60)).await;
+    assert!(tail_inst_tx.send(TailLogEventEvent::Tick).await.is_ok());
+    assert!(tail_inst_tx.send(TailLogEventEvent::Tick).await.is_ok());
+    wait_until(|| source.calls().len() == 2).await;
+    assert_eq!(1, source.live_tail_starts());
+    assert!(!state.lock().unwrap().live_tail);
+    delay_for(*RETRY_BASE_DELAY).await;
+    assert!(tail_inst_tx.send(TailLogEventEvent::Tick).await.is_ok());
+    wait_until(|| state.lock().unwrap().live_tail).await;
+    assert!(live_tx
+        .send(Ok(vec![event("1", START + 1000)]))
+        .await
+        .is_ok());
+    wait_until(|| state.lock().unwrap().events.items().len() == 1).await;
+    // the stream fails
+    assert!(live_tx.send(Err(anyhow!("stream reset"))).await.is_ok());
+    wait_until(|| !state.lock().unwrap().live_tail).await;
+    assert!(tail_inst_tx.send(TailLogEventEvent::Abort).await.is_ok());
+    handle.await.unwrap();
+
+    assert_eq!(2, source.live_tail_starts());
+    assert_eq!(None, state.lock().unwrap().error);
+}
+
+#[tokio::test]
+async fn test_run_live_tail_refresh() {
+    // the session is replaced after the limit, and the events left in it are polled
+    let (mut old_tx, old_session) = live_tail::channel(1);
+    let (mut new_tx, new_session) = live_tail::channel(1);
+    let source = ScriptedLogSource::new()
+        .then_live_tail(old_session)
+        .then_live_tail(new_session)
+        .then_events(vec![], None)
+        .then_events(vec![event("1", START + 1000)], None);
+    let (mut tail_inst_tx, handle, state) =
+        start_handler(source.clone(), Duration::from_millis(0)).await;
+    assert!(tail_inst_tx.send(TailLogEventEvent::Tick).await.is_ok());
+    assert!(tail_inst_tx.send(TailLogEventEvent::Tick).await.is_ok());
+    wait_until(|| source.calls().len() == 2).await;
+    assert_eq!(2, source.live_tail_starts());
+    assert!(old_tx.send(Ok(vec![])).await.is_err());
+    assert!(new_tx
+        .send(Ok(vec![event("1", START + 1000), event("2", START + 2000)]))
+        .await
+        .is_ok());
+    wait_until(|| state.lock().unwrap().events.items().len() == 2).await;
+    assert!(tail_inst_tx.send(TailLogEventEvent::Abort).await.is_ok());
+    handle.await.unwrap();
+
+    assert_eq!(vec!["1", "2"], event_ids(&state.lock().unwrap()));
+}